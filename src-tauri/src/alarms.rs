@@ -0,0 +1,512 @@
+//! Alarm Monitoring
+//! Edge detection on the 0xC0 alarm status for push notifications, and
+//! operator acknowledgement of active alarms, and telling BMS-internal
+//! communication faults apart from our own link dropping. Critical alarms
+//! can optionally run a command or call a webhook for unattended sites.
+
+use crate::bms_types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+/// Severity that triggers a `critical-alarm` event
+pub const CRITICAL_SEVERITY: u8 = 3;
+
+/// Event emitted when a critical alarm is newly raised
+pub const CRITICAL_ALARM_EVENT: &str = "critical-alarm";
+
+/// Active alarm joined with its description
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlarmDetail {
+    pub bit: u8,
+    pub description: String,
+    pub severity: u8,
+    /// Acknowledged by the operator since it was last raised
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+/// Defined alarms first, then reserved bits as "Unknown alarm bit N"
+pub fn active_alarm_details(data: &BmsData) -> Vec<AlarmDetail> {
+    let Some(ref alarm_status) = data.alarm_status else {
+        return Vec::new();
+    };
+
+    alarm_status
+        .active_alarms
+        .iter()
+        .chain(&alarm_status.unknown_alarms)
+        .map(|&bit| match alarm_info(bit) {
+            Some(info) => AlarmDetail {
+                bit,
+                description: info.description.to_string(),
+                severity: info.severity,
+                acknowledged: false,
+            },
+            None => AlarmDetail {
+                bit,
+                description: format!("Unknown alarm bit {}", bit),
+                severity: 1,
+                acknowledged: false,
+            },
+        })
+        .collect()
+}
+
+/// Tracks which critical alarms are active so each one is reported only when
+/// it is raised, not on every poll while it stays active
+#[derive(Debug, Default)]
+pub struct CriticalAlarmWatch {
+    active: u64,
+}
+
+impl CriticalAlarmWatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Critical alarms raised since the previous update
+    pub fn update(&mut self, status: Option<&AlarmStatus>) -> Vec<AlarmDetail> {
+        let Some(status) = status else {
+            return Vec::new();
+        };
+
+        let critical = ALARM_TABLE
+            .iter()
+            .filter(|info| info.severity >= CRITICAL_SEVERITY)
+            .fold(0u64, |acc, info| acc | (1u64 << info.bit as u8))
+            & status.raw_status;
+        let raised = critical & !self.active;
+        self.active = critical;
+
+        ALARM_TABLE
+            .iter()
+            .filter(|info| raised & (1u64 << info.bit as u8) != 0)
+            .map(|info| AlarmDetail {
+                bit: info.bit as u8,
+                description: info.description.to_string(),
+                severity: info.severity,
+                acknowledged: false,
+            })
+            .collect()
+    }
+
+    /// Forget the active set, e.g. after reconnecting
+    pub fn reset(&mut self) {
+        self.active = 0;
+    }
+}
+
+/// Default minimum time between two actions for the same alarm bit
+pub const DEFAULT_ALARM_ACTION_DEBOUNCE: Duration = Duration::from_secs(60);
+
+/// Connect/read/write timeout of an alarm webhook call
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What to do when a critical alarm is raised
+///
+/// Nothing runs unless an action is set with `set_alarm_action`. A command is
+/// started directly, never through a shell, so alarm text can't inject
+/// anything; `{bit}`, `{description}` and `{severity}` are replaced inside
+/// each argument. A webhook gets the alarm as a JSON POST; only plain
+/// `http://` URLs are supported.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum AlarmAction {
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    Webhook {
+        url: String,
+    },
+}
+
+impl AlarmAction {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            AlarmAction::Command { program, .. } if program.trim().is_empty() => {
+                Err("program must not be empty".to_string())
+            }
+            AlarmAction::Webhook { url } if !url.starts_with("http://") => {
+                Err(format!("webhook URL must start with http://, got {}", url))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Run the action for `alarm`, blocking until it finished
+    fn run(&self, alarm: &AlarmDetail) -> std::io::Result<()> {
+        match self {
+            AlarmAction::Command { program, args } => {
+                let args: Vec<String> = args.iter().map(|a| render_arg(a, alarm)).collect();
+                let status = std::process::Command::new(program).args(&args).status()?;
+                if !status.success() {
+                    log::warn!("Alarm action {} exited with {}", program, status);
+                }
+                Ok(())
+            }
+            AlarmAction::Webhook { url } => {
+                let body = serde_json::json!({
+                    "bit": alarm.bit,
+                    "description": alarm.description,
+                    "severity": alarm.severity,
+                });
+                post_json(url, &body.to_string())
+            }
+        }
+    }
+}
+
+/// Replace the alarm placeholders in one command argument
+fn render_arg(arg: &str, alarm: &AlarmDetail) -> String {
+    arg.replace("{bit}", &alarm.bit.to_string())
+        .replace("{description}", &alarm.description)
+        .replace("{severity}", &alarm.severity.to_string())
+}
+
+/// POST `body` as JSON to an `http://host[:port]/path` URL
+fn post_json(url: &str, body: &str) -> std::io::Result<()> {
+    use std::io::{Error, ErrorKind};
+    use std::net::{TcpStream, ToSocketAddrs};
+
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "only http:// is supported"))?;
+    let (host, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let addr = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+    let addr = addr
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "host did not resolve"))?;
+
+    let mut stream = TcpStream::connect_timeout(&addr, WEBHOOK_TIMEOUT)?;
+    stream.set_read_timeout(Some(WEBHOOK_TIMEOUT))?;
+    stream.set_write_timeout(Some(WEBHOOK_TIMEOUT))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )?;
+
+    let mut status = [0u8; 12];
+    stream.read_exact(&mut status)?;
+    // "HTTP/1.1 2xx"
+    if status[9] != b'2' {
+        return Err(Error::other(format!(
+            "webhook answered {}",
+            String::from_utf8_lossy(&status)
+        )));
+    }
+    Ok(())
+}
+
+/// Runs the configured `AlarmAction` for raised critical alarms
+///
+/// `CriticalAlarmWatch` already reports each alarm once per activation; the
+/// debounce additionally limits an alarm that keeps flapping to one run per
+/// `debounce`.
+#[derive(Debug)]
+pub struct AlarmActionHook {
+    action: AlarmAction,
+    debounce: Duration,
+    last_run: HashMap<u8, Instant>,
+}
+
+impl AlarmActionHook {
+    pub fn new(action: AlarmAction, debounce: Duration) -> Result<Self, String> {
+        action.validate()?;
+        Ok(AlarmActionHook {
+            action,
+            debounce,
+            last_run: HashMap::new(),
+        })
+    }
+
+    pub fn action(&self) -> &AlarmAction {
+        &self.action
+    }
+
+    /// Start the action for `alarm` on a background thread unless the same
+    /// bit already triggered it within the debounce; returns whether it started
+    pub fn trigger(&mut self, alarm: &AlarmDetail, now: Instant) -> bool {
+        if let Some(&last) = self.last_run.get(&alarm.bit) {
+            if now.duration_since(last) < self.debounce {
+                return false;
+            }
+        }
+        self.last_run.insert(alarm.bit, now);
+
+        let action = self.action.clone();
+        let alarm = alarm.clone();
+        let spawned = std::thread::Builder::new()
+            .name("bms-alarm-action".to_string())
+            .spawn(move || {
+                if let Err(e) = action.run(&alarm) {
+                    log::warn!("Alarm action for bit {} failed: {}", alarm.bit, e);
+                }
+            });
+        if let Err(e) = spawned {
+            log::warn!("Could not start alarm action: {}", e);
+            return false;
+        }
+        true
+    }
+}
+
+/// Alarms the operator has acknowledged
+///
+/// An acknowledgement only lasts while its bit stays active; once the alarm
+/// falls, a later occurrence shows up as new again.
+#[derive(Debug, Default)]
+pub struct AlarmAcknowledgements {
+    acknowledged: u64,
+}
+
+impl AlarmAcknowledgements {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Acknowledge `bit` if it is currently active; returns false otherwise
+    pub fn acknowledge(&mut self, bit: u8, status: Option<&AlarmStatus>) -> bool {
+        let active = status.map_or(0, |s| s.raw_status);
+        if bit >= 64 || active & (1u64 << bit) == 0 {
+            return false;
+        }
+        self.acknowledged |= 1u64 << bit;
+        true
+    }
+
+    /// Drop acknowledgements of alarms that are no longer active
+    pub fn update(&mut self, status: Option<&AlarmStatus>) {
+        self.acknowledged &= status.map_or(0, |s| s.raw_status);
+    }
+
+    pub fn is_acknowledged(&self, bit: u8) -> bool {
+        bit < 64 && self.acknowledged & (1u64 << bit) != 0
+    }
+
+    pub fn clear(&mut self) {
+        self.acknowledged = 0;
+    }
+}
+
+/// 0xC0 bits reporting a communication fault inside the battery system
+pub const BMS_COMM_ALARM_BITS: [AlarmBit; 3] = [
+    AlarmBit::BmuCommunicationInterruption,
+    AlarmBit::ParallelCommunicationAbnormality,
+    AlarmBit::CanHallCommunicationInterruption,
+];
+
+/// Data older than this in ms counts as a link loss by default
+pub const DEFAULT_STALE_AFTER_MS: i64 = 10_000;
+
+/// Why the app-side link to the BMS is considered down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkFault {
+    /// Adapter not connected, or the connection was dropped
+    Disconnected,
+    /// Connected, but nothing has been decoded yet
+    NoData,
+    /// Connected, but no frame decoded within the stale limit
+    Stale,
+}
+
+/// App-side link state next to BMS-reported internal comms faults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommunicationStatus {
+    /// Adapter/serial/VCI link fault, `None` while healthy
+    pub link_fault: Option<LinkFault>,
+    /// Time since the last decoded frame in ms
+    pub data_age_ms: Option<i64>,
+    /// True if the last 0xC0 frame carried a communication fault bit
+    pub bms_comm_fault: bool,
+    /// Active `BMS_COMM_ALARM_BITS`; from the last known alarm frame, even if stale
+    pub bms_comm_alarms: Vec<u8>,
+}
+
+/// Classify link health (ours) and internal comms faults (reported by the BMS)
+pub fn communication_status(
+    data: &BmsData,
+    link_connected: bool,
+    now_ms: i64,
+    stale_after_ms: i64,
+) -> CommunicationStatus {
+    let data_age_ms = data.last_received_ms().map(|received| now_ms - received);
+    let link_fault = if !link_connected {
+        Some(LinkFault::Disconnected)
+    } else {
+        match data_age_ms {
+            None => Some(LinkFault::NoData),
+            Some(age) if age > stale_after_ms => Some(LinkFault::Stale),
+            Some(_) => None,
+        }
+    };
+
+    let bms_comm_alarms: Vec<u8> = data
+        .alarm_status
+        .as_ref()
+        .map(|status| {
+            BMS_COMM_ALARM_BITS
+                .iter()
+                .map(|&bit| bit as u8)
+                .filter(|bit| status.active_alarms.contains(bit))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    CommunicationStatus {
+        link_fault,
+        data_age_ms,
+        bms_comm_fault: !bms_comm_alarms.is_empty(),
+        bms_comm_alarms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bms_parser::parse_alarm_status;
+
+    #[test]
+    fn test_active_alarm_details() {
+        let mut data = BmsData::default();
+        assert!(active_alarm_details(&data).is_empty());
+
+        data.alarm_status = Some(AlarmStatus {
+            raw_status: (1 << 2) | (1 << 14) | (1 << 50),
+            active_alarms: vec![2, 14],
+            unknown_alarms: vec![50],
+            max_severity: 3,
+        });
+        let details = active_alarm_details(&data);
+
+        assert_eq!(details.len(), 3);
+        assert_eq!(details[2].description, "Unknown alarm bit 50");
+        assert_eq!(details[0].bit, 2);
+        assert_eq!(details[0].description, "Charging over temperature alarm");
+        assert_eq!(details[0].severity, 2);
+        assert_eq!(details[1].description, "BMU communication interruption");
+        assert_eq!(details[1].severity, 3);
+    }
+
+    #[test]
+    fn test_critical_alarm_fires_once() {
+        let mut watch = CriticalAlarmWatch::new();
+
+        // Fire protection (32, severity 3) + charging over temp alarm (2, severity 2)
+        let frame = ((1u64 << 32) | (1 << 2)).to_le_bytes();
+        let status = parse_alarm_status(&frame, Endianness::Little).unwrap();
+
+        let events = watch.update(Some(&status));
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].bit, 32);
+        assert_eq!(events[0].description, "Fire protection");
+
+        // Same alarm still active on the next poll
+        assert!(watch.update(Some(&status)).is_empty());
+
+        // Cleared and raised again
+        let clear = parse_alarm_status(&0u64.to_le_bytes(), Endianness::Little).unwrap();
+        assert!(watch.update(Some(&clear)).is_empty());
+        assert_eq!(watch.update(Some(&status)).len(), 1);
+    }
+
+    #[test]
+    fn test_alarm_action_debounce_and_arguments() {
+        let alarm = AlarmDetail {
+            bit: 31,
+            description: "EPO shut down; rm -rf /".to_string(),
+            severity: 3,
+            acknowledged: false,
+        };
+        assert_eq!(
+            render_arg("--alarm={bit}:{severity}:{description}", &alarm),
+            "--alarm=31:3:EPO shut down; rm -rf /"
+        );
+
+        assert!(AlarmAction::Webhook {
+            url: "https://example.com".to_string()
+        }
+        .validate()
+        .is_err());
+        assert!(AlarmActionHook::new(
+            AlarmAction::Command {
+                program: " ".to_string(),
+                args: vec![]
+            },
+            DEFAULT_ALARM_ACTION_DEBOUNCE
+        )
+        .is_err());
+
+        // The command runs on a background thread; only dispatch is checked here
+        let mut hook = AlarmActionHook::new(
+            AlarmAction::Command {
+                program: "true".to_string(),
+                args: vec!["{bit}".to_string()],
+            },
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        let start = Instant::now();
+        assert!(hook.trigger(&alarm, start));
+        assert!(!hook.trigger(&alarm, start + Duration::from_secs(30)));
+        assert!(hook.trigger(&alarm, start + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn test_acknowledgement_resets_when_alarm_clears() {
+        let mut acks = AlarmAcknowledgements::new();
+        let raised = parse_alarm_status(&(1u64 << 9).to_le_bytes(), Endianness::Little).unwrap();
+        let clear = parse_alarm_status(&0u64.to_le_bytes(), Endianness::Little).unwrap();
+
+        // Only active alarms can be acknowledged
+        assert!(!acks.acknowledge(10, Some(&raised)));
+        assert!(acks.acknowledge(9, Some(&raised)));
+        acks.update(Some(&raised));
+        assert!(acks.is_acknowledged(9));
+
+        acks.update(Some(&clear));
+        acks.update(Some(&raised));
+        assert!(!acks.is_acknowledged(9));
+    }
+
+    #[test]
+    fn test_bmu_comm_fault_with_healthy_link() {
+        let frame = (1u64 << AlarmBit::BmuCommunicationInterruption as u8).to_le_bytes();
+        let data = BmsData {
+            alarm_status: parse_alarm_status(&frame, Endianness::Little),
+            timestamp: 1_000,
+            updated_at: HashMap::from([(BmsCommand::AlarmStatus, 1_000)]),
+            ..Default::default()
+        };
+
+        let status = communication_status(&data, true, 1_500, DEFAULT_STALE_AFTER_MS);
+        assert_eq!(status.link_fault, None);
+        assert!(status.bms_comm_fault);
+        assert_eq!(status.bms_comm_alarms, vec![14]);
+
+        // Same frame, but our link went away
+        let status = communication_status(&data, false, 1_500, DEFAULT_STALE_AFTER_MS);
+        assert_eq!(status.link_fault, Some(LinkFault::Disconnected));
+
+        let status = communication_status(&data, true, 20_000, DEFAULT_STALE_AFTER_MS);
+        assert_eq!(status.link_fault, Some(LinkFault::Stale));
+        assert!(status.bms_comm_fault);
+    }
+}
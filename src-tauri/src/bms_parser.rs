@@ -4,57 +4,121 @@
 use crate::bms_types::*;
 
 /// Parse Command 0x80 - Charge/Discharge Limits
-pub fn parse_charge_discharge_limits(data: &[u8]) -> Option<ChargeDischargeLimits> {
-    if data.len() < 8 {
+pub fn parse_charge_discharge_limits(
+    data: &[u8],
+    order: Endianness,
+) -> Option<ChargeDischargeLimits> {
+    if data.len() < BmsCommand::ChargeDischargeLimits.min_payload_len() {
         return None;
     }
 
     Some(ChargeDischargeLimits {
-        charge_voltage_limit: u16::from_le_bytes([data[0], data[1]]) as f32 * 0.1,
-        charge_current_limit: u16::from_le_bytes([data[2], data[3]]) as f32 * 0.1,
-        discharge_voltage_limit: u16::from_le_bytes([data[4], data[5]]) as f32 * 0.1,
-        discharge_current_limit: u16::from_le_bytes([data[6], data[7]]) as f32 * 0.1,
+        charge_voltage_limit: order.u16(&data[0..]) as f32 * 0.1,
+        charge_current_limit: order.u16(&data[2..]) as f32 * 0.1,
+        discharge_voltage_limit: order.u16(&data[4..]) as f32 * 0.1,
+        discharge_current_limit: order.u16(&data[6..]) as f32 * 0.1,
     })
 }
 
+/// Encode Command 0x80 - inverse of `parse_charge_discharge_limits`
+/// Values are rounded to the 0.1 resolution and saturate at the u16 range.
+pub fn encode_charge_discharge_limits(
+    limits: &ChargeDischargeLimits,
+    order: Endianness,
+) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u16(&mut data[0..], deci(limits.charge_voltage_limit));
+    order.put_u16(&mut data[2..], deci(limits.charge_current_limit));
+    order.put_u16(&mut data[4..], deci(limits.discharge_voltage_limit));
+    order.put_u16(&mut data[6..], deci(limits.discharge_current_limit));
+    data
+}
+
+/// Scale a value with 0.1 resolution to its raw u16
+fn deci(value: f32) -> u16 {
+    (value * 10.0).round() as u16
+}
+
+/// Scale a signed value with 0.1 resolution to its raw i16
+fn deci_signed(value: f32) -> i16 {
+    (value * 10.0).round() as i16
+}
+
+/// Scale a value with 0.001 resolution to its raw u16
+fn milli(value: f32) -> u16 {
+    (value * 1000.0).round() as u16
+}
+
 /// Parse Command 0x81 - SOC/SOH
-pub fn parse_soc_soh(data: &[u8]) -> Option<SocSohData> {
-    if data.len() < 6 {
+pub fn parse_soc_soh(data: &[u8], order: Endianness) -> Option<SocSohData> {
+    if data.len() < BmsCommand::SocSoh.min_payload_len() {
         return None;
     }
 
     Some(SocSohData {
-        soc: u16::from_le_bytes([data[0], data[1]]),
-        soh: u16::from_le_bytes([data[2], data[3]]),
-        backup_time_minutes: u16::from_le_bytes([data[4], data[5]]),
+        soc: order.u16(&data[0..]),
+        soh: order.u16(&data[2..]),
+        backup_time_minutes: order.u16(&data[4..]),
     })
 }
 
+/// Encode Command 0x81 - inverse of `parse_soc_soh`
+pub fn encode_soc_soh(soc_soh: &SocSohData, order: Endianness) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u16(&mut data[0..], soc_soh.soc);
+    order.put_u16(&mut data[2..], soc_soh.soh);
+    order.put_u16(&mut data[4..], soc_soh.backup_time_minutes);
+    data
+}
+
 /// Parse Command 0x82 - Voltage/Current
-pub fn parse_voltage_current(data: &[u8]) -> Option<VoltageCurrentData> {
-    if data.len() < 4 {
+/// `current` follows the protocol convention; see `CurrentConvention::apply`.
+pub fn parse_voltage_current(data: &[u8], order: Endianness) -> Option<VoltageCurrentData> {
+    if data.len() < BmsCommand::VoltageCurrent.min_payload_len() {
         return None;
     }
 
-    let voltage = u16::from_le_bytes([data[0], data[1]]) as f32 * 0.1;
-    let current = i16::from_le_bytes([data[2], data[3]]) as f32 * 0.1;
-    let power = voltage * current.abs() / 1000.0; // Convert to kW
+    let raw_voltage = order.u16(&data[0..]);
+    let raw_current = order.i16(&data[2..]);
+    let voltage = raw_voltage as f32 * 0.1;
+    let current = raw_current as f32 * 0.1;
+    let power_w = dc_power_w(raw_voltage as f64 * 0.1, raw_current as f64 * 0.1);
 
     Some(VoltageCurrentData {
         voltage,
         current,
-        power,
+        power: (power_w / 1000.0) as f32,
+        power_w,
+        raw_current: current,
     })
 }
 
+/// DC power in W from pack voltage (V) and current (A)
+///
+/// The battery is a DC source, so this is plain V × I: no power factor or
+/// three-phase √3 term applies. Returns the magnitude; the direction is the
+/// sign of the current.
+pub fn dc_power_w(voltage: f64, current: f64) -> f64 {
+    voltage * current.abs()
+}
+
+/// Encode Command 0x82 - inverse of `parse_voltage_current`
+/// Power is derived on parse and not encoded.
+pub fn encode_voltage_current(vc: &VoltageCurrentData, order: Endianness) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u16(&mut data[0..], deci(vc.voltage));
+    order.put_u16(&mut data[2..], deci_signed(vc.current) as u16);
+    data
+}
+
 /// Parse Command 0x83 - Cell Voltage
-pub fn parse_cell_voltage(data: &[u8]) -> Option<CellVoltageData> {
-    if data.len() < 8 {
+pub fn parse_cell_voltage(data: &[u8], order: Endianness) -> Option<CellVoltageData> {
+    if data.len() < BmsCommand::CellVoltage.min_payload_len() {
         return None;
     }
 
-    let max_voltage = u16::from_le_bytes([data[0], data[1]]) as f32 * 0.001;
-    let min_voltage = u16::from_le_bytes([data[4], data[5]]) as f32 * 0.001;
+    let max_voltage = order.u16(&data[0..]) as f32 * 0.001;
+    let min_voltage = order.u16(&data[4..]) as f32 * 0.001;
 
     Some(CellVoltageData {
         max_voltage,
@@ -67,14 +131,185 @@ pub fn parse_cell_voltage(data: &[u8]) -> Option<CellVoltageData> {
     })
 }
 
+/// Encode Command 0x83 - inverse of `parse_cell_voltage`
+/// The voltage delta is derived on parse and not encoded.
+pub fn encode_cell_voltage(cells: &CellVoltageData, order: Endianness) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u16(&mut data[0..], milli(cells.max_voltage));
+    data[2] = cells.max_voltage_pack_no;
+    data[3] = cells.max_voltage_cell_no;
+    order.put_u16(&mut data[4..], milli(cells.min_voltage));
+    data[6] = cells.min_voltage_pack_no;
+    data[7] = cells.min_voltage_cell_no;
+    data
+}
+
+/// One continuation chunk of per-pack cell voltages
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackCellChunk {
+    /// PACK number
+    pub pack_no: u8,
+    /// Cell index (0-based) of the first voltage in this chunk
+    pub first_cell: u8,
+    /// Cell voltages in V
+    pub voltages: Vec<f32>,
+}
+
+/// Parse a Command 0x83 continuation frame (CNT bit set)
+///
+/// Assumed layout: byte 0 = PACK number, byte 1 = index of the first cell,
+/// bytes 2-7 = up to three cell voltages (u16, 1mV). A voltage of 0xFFFF marks
+/// an unused slot in the last chunk of a pack.
+pub fn parse_pack_cell_chunk(data: &[u8], order: Endianness) -> Option<PackCellChunk> {
+    if data.len() < 4 {
+        return None;
+    }
+
+    let voltages = data[2..]
+        .chunks_exact(2)
+        .map(|b| order.u16(b))
+        .take_while(|&mv| mv != 0xFFFF)
+        .map(|mv| mv as f32 * 0.001)
+        .collect();
+
+    Some(PackCellChunk {
+        pack_no: data[0],
+        first_cell: data[1],
+        voltages,
+    })
+}
+
+/// Encode a Command 0x83 continuation frame - inverse of `parse_pack_cell_chunk`
+/// Only the first three voltages fit; unused slots are filled with 0xFFFF.
+pub fn encode_pack_cell_chunk(chunk: &PackCellChunk, order: Endianness) -> [u8; 8] {
+    let mut data = [0xFFu8; 8];
+    data[0] = chunk.pack_no;
+    data[1] = chunk.first_cell;
+    for (slot, &voltage) in data[2..].chunks_exact_mut(2).zip(&chunk.voltages) {
+        order.put_u16(slot, milli(voltage));
+    }
+    data
+}
+
+/// Merge a continuation chunk into the per-pack list, keeping packs sorted
+pub fn merge_pack_chunk(packs: &mut Vec<PackVoltage>, chunk: PackCellChunk) {
+    let index = match packs.binary_search_by_key(&chunk.pack_no, |p| p.pack_no) {
+        Ok(index) => index,
+        Err(index) => {
+            packs.insert(
+                index,
+                PackVoltage {
+                    pack_no: chunk.pack_no,
+                    cell_voltages: Vec::new(),
+                },
+            );
+            index
+        }
+    };
+
+    let cells = &mut packs[index].cell_voltages;
+    let first = chunk.first_cell as usize;
+    if cells.len() < first + chunk.voltages.len() {
+        cells.resize(first + chunk.voltages.len(), 0.0);
+    }
+    cells[first..first + chunk.voltages.len()].copy_from_slice(&chunk.voltages);
+}
+
+/// One 0x85 continuation chunk of a pack's balancing map
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalancingChunk {
+    /// PACK number
+    pub pack_no: u8,
+    /// Cell index (0-based) of the first bit in this chunk
+    pub first_cell: u8,
+    /// Balancing flag per cell, starting at `first_cell`
+    pub balancing: Vec<bool>,
+}
+
+/// Parse a Command 0x85 continuation frame (CNT bit set)
+///
+/// V1.20 has no balancing command; this assumes the same chunking as the 0x83
+/// continuation frames: byte 0 = PACK number, byte 1 = index of the first
+/// cell, bytes 2-7 = balancing bitmap, LSB first (bit 0 of byte 2 is
+/// `first_cell`), so one frame covers up to 48 cells.
+pub fn parse_balancing_chunk(data: &[u8]) -> Option<BalancingChunk> {
+    if data.len() < 3 {
+        return None;
+    }
+
+    let balancing = data[2..]
+        .iter()
+        .flat_map(|&byte| (0..8).map(move |bit| (byte >> bit) & 1 == 1))
+        .collect();
+
+    Some(BalancingChunk {
+        pack_no: data[0],
+        first_cell: data[1],
+        balancing,
+    })
+}
+
+/// Encode a Command 0x85 continuation frame - inverse of `parse_balancing_chunk`
+/// Only the first 48 flags fit.
+pub fn encode_balancing_chunk(chunk: &BalancingChunk) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    data[0] = chunk.pack_no;
+    data[1] = chunk.first_cell;
+    for (i, _) in chunk
+        .balancing
+        .iter()
+        .enumerate()
+        .take(48)
+        .filter(|(_, &on)| on)
+    {
+        data[2 + i / 8] |= 1 << (i % 8);
+    }
+    data
+}
+
+/// Merge a balancing chunk into the status, keeping packs sorted
+///
+/// Cells covered by the chunk are replaced; other cells of the pack keep
+/// their last reported state.
+pub fn merge_balancing_chunk(status: &mut BalancingStatus, chunk: BalancingChunk) {
+    let packs = &mut status.packs;
+    let index = match packs.binary_search_by_key(&chunk.pack_no, |p| p.pack_no) {
+        Ok(index) => index,
+        Err(index) => {
+            packs.insert(
+                index,
+                PackBalancing {
+                    pack_no: chunk.pack_no,
+                    cells: Vec::new(),
+                },
+            );
+            index
+        }
+    };
+
+    let first = chunk.first_cell as u16;
+    let covered = first..first + chunk.balancing.len() as u16;
+    let cells = &mut packs[index].cells;
+    cells.retain(|cell| !covered.contains(cell));
+    cells.extend(
+        chunk
+            .balancing
+            .iter()
+            .enumerate()
+            .filter(|(_, &on)| on)
+            .map(|(i, _)| first + i as u16),
+    );
+    cells.sort_unstable();
+}
+
 /// Parse Command 0x84 - Temperature
-pub fn parse_temperature(data: &[u8]) -> Option<TemperatureData> {
-    if data.len() < 8 {
+pub fn parse_temperature(data: &[u8], order: Endianness) -> Option<TemperatureData> {
+    if data.len() < BmsCommand::Temperature.min_payload_len() {
         return None;
     }
 
-    let max_temperature = i16::from_le_bytes([data[0], data[1]]) as f32 * 0.1;
-    let min_temperature = i16::from_le_bytes([data[4], data[5]]) as f32 * 0.1;
+    let max_temperature = order.i16(&data[0..]) as f32 * 0.1;
+    let min_temperature = order.i16(&data[4..]) as f32 * 0.1;
 
     Some(TemperatureData {
         max_temperature,
@@ -87,9 +322,106 @@ pub fn parse_temperature(data: &[u8]) -> Option<TemperatureData> {
     })
 }
 
+/// Encode Command 0x84 - inverse of `parse_temperature`
+/// The temperature delta is derived on parse and not encoded.
+pub fn encode_temperature(temp: &TemperatureData, order: Endianness) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u16(&mut data[0..], deci_signed(temp.max_temperature) as u16);
+    data[2] = temp.max_temp_pack_no;
+    data[3] = temp.max_temp_sensor_no;
+    order.put_u16(&mut data[4..], deci_signed(temp.min_temperature) as u16);
+    data[6] = temp.min_temp_pack_no;
+    data[7] = temp.min_temp_sensor_no;
+    data
+}
+
+/// One 0x84 continuation chunk of per-sensor temperatures
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorTemperatureChunk {
+    /// Sensor index (0-based, counted across all packs) of the first reading
+    pub first_sensor: u16,
+    /// Temperatures in °C
+    pub temperatures: Vec<f32>,
+}
+
+/// Raw value of an unused slot in the last 0x84 continuation chunk
+pub const UNUSED_TEMPERATURE: i16 = i16::MAX;
+
+/// Parse a Command 0x84 continuation frame (CNT bit set)
+///
+/// V1.20 only reports the hottest and coldest sensor; this assumes chunking
+/// like the 0x83 continuation frames, with a 16-bit index for large packs:
+/// bytes 0-1 = index of the first sensor, bytes 2-7 = up to three
+/// temperatures (i16, 0.1°C). `UNUSED_TEMPERATURE` marks an unused slot.
+pub fn parse_sensor_temperature_chunk(
+    data: &[u8],
+    order: Endianness,
+) -> Option<SensorTemperatureChunk> {
+    if data.len() < 4 {
+        return None;
+    }
+
+    let temperatures = data[2..]
+        .chunks_exact(2)
+        .map(|b| order.i16(b))
+        .take_while(|&raw| raw != UNUSED_TEMPERATURE)
+        .map(|raw| raw as f32 * 0.1)
+        .collect();
+
+    Some(SensorTemperatureChunk {
+        first_sensor: order.u16(&data[0..]),
+        temperatures,
+    })
+}
+
+/// Encode a Command 0x84 continuation frame - inverse of `parse_sensor_temperature_chunk`
+/// Only the first three temperatures fit; unused slots are `UNUSED_TEMPERATURE`.
+pub fn encode_sensor_temperature_chunk(
+    chunk: &SensorTemperatureChunk,
+    order: Endianness,
+) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u16(&mut data[0..], chunk.first_sensor);
+    for (i, slot) in data[2..].chunks_exact_mut(2).enumerate() {
+        let raw = chunk
+            .temperatures
+            .get(i)
+            .map_or(UNUSED_TEMPERATURE, |&t| deci_signed(t));
+        order.put_u16(slot, raw as u16);
+    }
+    data
+}
+
+/// Most sensors accepted in the 0x84 continuation chunks, across all packs
+pub const MAX_TEMPERATURE_SENSORS: usize = 1024;
+
+/// Merge a continuation chunk into the sensor list; sensors not reported yet read 0.0
+/// A chunk starting at sensor 0 begins a new round and drops the previous
+/// list, so a smaller pack doesn't keep stale readings of a larger one.
+/// Returns false, leaving the list untouched, for a chunk reaching past
+/// `MAX_TEMPERATURE_SENSORS` (e.g. a corrupted `first_sensor`).
+pub fn merge_sensor_temperatures(
+    temperatures: &mut Vec<f32>,
+    chunk: SensorTemperatureChunk,
+) -> bool {
+    let first = chunk.first_sensor as usize;
+    let end = first + chunk.temperatures.len();
+    if end > MAX_TEMPERATURE_SENSORS {
+        return false;
+    }
+    if first == 0 {
+        temperatures.clear();
+    }
+    if temperatures.len() < end {
+        temperatures.resize(end, 0.0);
+    }
+    temperatures[first..end].copy_from_slice(&chunk.temperatures);
+    true
+}
+
 /// Parse Command 0x85 - Operation Status
 pub fn parse_operation_status(data: &[u8]) -> Option<OperationStatusData> {
-    if data.len() < 4 {
+    if data.len() < BmsCommand::OperationStatus.min_payload_len() {
         return None;
     }
 
@@ -99,44 +431,80 @@ pub fn parse_operation_status(data: &[u8]) -> Option<OperationStatusData> {
         system_status: SystemStatus::from(data[0]),
         work_status: WorkStatus::from(data[1]),
         operation_status: OperationStatusCode::from(data[2]),
-        discharge_prohibited: (prohibition_flags & 0x01) != 0,
-        charge_prohibited: (prohibition_flags & 0x02) != 0,
-        discharge_prohibited_hard: (prohibition_flags & 0x04) != 0,
+        discharge_prohibited: (prohibition_flags & Prohibition::Discharge.mask()) != 0,
+        charge_prohibited: (prohibition_flags & Prohibition::Charge.mask()) != 0,
+        discharge_prohibited_hard: (prohibition_flags & Prohibition::DischargeHard.mask()) != 0,
+        prohibition_flags,
     })
 }
 
+/// Encode Command 0x85 - inverse of `parse_operation_status`
+pub fn encode_operation_status(status: &OperationStatusData) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    data[0] = status.system_status.into();
+    data[1] = status.work_status.into();
+    data[2] = status.operation_status.into();
+    // The bools win over their bits in the raw byte; undocumented bits are kept
+    data[3] = status.undocumented_prohibition_bits()
+        | status.discharge_prohibited as u8
+        | (status.charge_prohibited as u8) << 1
+        | (status.discharge_prohibited_hard as u8) << 2;
+    data
+}
+
 /// Parse Command 0x86 - Accumulated Times
-pub fn parse_accumulated_times(data: &[u8]) -> Option<AccumulatedTimesData> {
-    if data.len() < 4 {
+pub fn parse_accumulated_times(data: &[u8], order: Endianness) -> Option<AccumulatedTimesData> {
+    if data.len() < BmsCommand::AccumulatedTimes.min_payload_len() {
         return None;
     }
 
     Some(AccumulatedTimesData {
-        charge_times: u16::from_le_bytes([data[0], data[1]]),
-        discharge_times: u16::from_le_bytes([data[2], data[3]]),
+        charge_times: order.u16(&data[0..]),
+        discharge_times: order.u16(&data[2..]),
     })
 }
 
+/// Encode Command 0x86 - inverse of `parse_accumulated_times`
+pub fn encode_accumulated_times(times: &AccumulatedTimesData, order: Endianness) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u16(&mut data[0..], times.charge_times);
+    order.put_u16(&mut data[2..], times.discharge_times);
+    data
+}
+
 /// Parse Command 0x87 - Accumulated Power
-pub fn parse_accumulated_power(data: &[u8]) -> Option<AccumulatedPowerData> {
-    if data.len() < 8 {
+pub fn parse_accumulated_power(data: &[u8], order: Endianness) -> Option<AccumulatedPowerData> {
+    if data.len() < BmsCommand::AccumulatedPower.min_payload_len() {
         return None;
     }
 
-    Some(AccumulatedPowerData {
-        charge_energy: u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as f32 * 0.1,
-        discharge_energy: u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as f32 * 0.1,
-    })
+    Some(AccumulatedPowerData::from_raw(
+        order.u32(&data[0..]),
+        order.u32(&data[4..]),
+    ))
+}
+
+/// Encode Command 0x87 - inverse of `parse_accumulated_power`
+/// Only the raw counters are encoded.
+pub fn encode_accumulated_power(power: &AccumulatedPowerData, order: Endianness) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u32(&mut data[0..], power.charge_energy_raw);
+    order.put_u32(&mut data[4..], power.discharge_energy_raw);
+    data
 }
 
 /// Parse Command 0x8F - Software Version
+///
+/// Reads printable ASCII up to the first other byte (NUL padding, control
+/// characters or bytes >= 0x80), so garbage never ends up in the version.
 pub fn parse_software_version(data: &[u8]) -> Option<String> {
     let version: String = data
         .iter()
         .take(8)
-        .filter(|&&b| b != 0)
+        .take_while(|b| b.is_ascii_graphic() || **b == b' ')
         .map(|&b| b as char)
         .collect();
+    let version = version.trim_end().to_string();
 
     if version.is_empty() {
         None
@@ -145,59 +513,78 @@ pub fn parse_software_version(data: &[u8]) -> Option<String> {
     }
 }
 
+/// Encode Command 0x8F - inverse of `parse_software_version`
+/// The version is truncated to 8 bytes and NUL padded.
+pub fn encode_software_version(version: &str) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    for (byte, &b) in data.iter_mut().zip(version.as_bytes()) {
+        *byte = b;
+    }
+    data
+}
+
+/// Best-effort split of a version string like "V2.19S" into major/minor/suffix
+pub fn parse_version_string(raw: &str) -> SoftwareVersion {
+    fn take_number(s: &str) -> (Option<u16>, &str) {
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        (s[..end].parse().ok(), &s[end..])
+    }
+
+    let mut version = SoftwareVersion {
+        raw: raw.to_string(),
+        major: None,
+        minor: None,
+        suffix: None,
+    };
+
+    // Skip a prefix such as "V" or "ver"
+    let Some(start) = raw.find(|c: char| c.is_ascii_digit()) else {
+        return version;
+    };
+
+    let (major, rest) = take_number(&raw[start..]);
+    version.major = major;
+
+    let rest = match rest.strip_prefix('.') {
+        Some(after_dot) => {
+            let (minor, rest) = take_number(after_dot);
+            version.minor = minor;
+            rest
+        }
+        None => rest,
+    };
+
+    let suffix = rest.trim();
+    if !suffix.is_empty() {
+        version.suffix = Some(suffix.to_string());
+    }
+
+    version
+}
+
 /// Parse Command 0xC0 - Alarm Status
-pub fn parse_alarm_status(data: &[u8]) -> Option<AlarmStatus> {
-    if data.len() < 8 {
+/// Payloads shorter than 8 bytes carry the low-order bytes of the bitmap.
+pub fn parse_alarm_status(data: &[u8], order: Endianness) -> Option<AlarmStatus> {
+    if data.len() < BmsCommand::AlarmStatus.min_payload_len() {
         return None;
     }
+    let data = &data[..data.len().min(8)];
 
-    let raw_status = u64::from_le_bytes([
-        data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
-    ]);
+    let raw_status = order.u64(&zero_extend(data, order));
 
     let mut active_alarms = Vec::new();
+    let mut unknown_alarms = Vec::new();
     let mut max_severity = 0u8;
 
-    for bit in 0..64 {
+    for bit in 0..64u8 {
         if (raw_status >> bit) & 1 == 1 {
-            active_alarms.push(bit as u8);
-
-            // Get severity for known alarm bits
-            if bit <= 40 {
-                if let Some(alarm) = match bit {
-                    0 => Some(AlarmBit::CellOverVoltage),
-                    1 => Some(AlarmBit::CellUnderVoltage),
-                    2 => Some(AlarmBit::ChargingOverTempAlarm),
-                    3 => Some(AlarmBit::ChargingLowTempAlarm),
-                    4 => Some(AlarmBit::DischargingOverTempPrealarm),
-                    5 => Some(AlarmBit::DischargingLowTempPrealarm),
-                    6 => Some(AlarmBit::DischargingOverCurrentPrealarm),
-                    7 => Some(AlarmBit::ChargingOverCurrentPrealarm),
-                    8 => Some(AlarmBit::TotalOverVoltagePrealarm),
-                    9 => Some(AlarmBit::TotalUnderVoltageWarning),
-                    14 => Some(AlarmBit::BmuCommunicationInterruption),
-                    18 => Some(AlarmBit::ChargingOverTempProtection),
-                    19 => Some(AlarmBit::ChargingLowTempProtection),
-                    20 => Some(AlarmBit::DischargingOverTempProtection),
-                    21 => Some(AlarmBit::DischargingLowTempProtection),
-                    22 => Some(AlarmBit::DischargingOverCurrentProtectionL1),
-                    23 => Some(AlarmBit::DischargingOverCurrentProtectionL2),
-                    24 => Some(AlarmBit::ChargingOverCurrentProtectionL1),
-                    25 => Some(AlarmBit::ChargingOverCurrentProtectionL2),
-                    26 => Some(AlarmBit::ChargingOverCurrentProtectionL3),
-                    27 => Some(AlarmBit::TotalChargingOverVoltageProtection),
-                    28 => Some(AlarmBit::TotalChargingUnderVoltageProtection),
-                    29 => Some(AlarmBit::ChargingDcContactorFailure),
-                    30 => Some(AlarmBit::DischargingDcContactorFailure),
-                    31 => Some(AlarmBit::EpoShutdown),
-                    32 => Some(AlarmBit::FireProtection),
-                    _ => None,
-                } {
-                    let severity = get_alarm_severity(alarm);
-                    if severity > max_severity {
-                        max_severity = severity;
-                    }
+            // Reserved bits are kept apart so they aren't shown as alarms
+            match alarm_info(bit) {
+                Some(info) => {
+                    active_alarms.push(bit);
+                    max_severity = max_severity.max(info.severity);
                 }
+                None => unknown_alarms.push(bit),
             }
         }
     }
@@ -205,10 +592,29 @@ pub fn parse_alarm_status(data: &[u8]) -> Option<AlarmStatus> {
     Some(AlarmStatus {
         raw_status,
         active_alarms,
+        unknown_alarms,
         max_severity,
     })
 }
 
+/// Pad a short little/big-endian integer to 8 bytes without changing its value
+fn zero_extend(data: &[u8], order: Endianness) -> [u8; 8] {
+    let mut padded = [0u8; 8];
+    match order {
+        Endianness::Little => padded[..data.len()].copy_from_slice(data),
+        Endianness::Big => padded[8 - data.len()..].copy_from_slice(data),
+    }
+    padded
+}
+
+/// Encode Command 0xC0 - inverse of `parse_alarm_status`
+/// Only the raw bitmap is encoded; active alarms and severity are derived on parse.
+pub fn encode_alarm_status(status: &AlarmStatus, order: Endianness) -> [u8; 8] {
+    let mut data = [0u8; 8];
+    order.put_u64(&mut data, status.raw_status);
+    data
+}
+
 /// Build a query frame for a specific command
 pub fn build_query_frame(
     command: BmsCommand,
@@ -225,79 +631,201 @@ pub fn build_query_frame(
 
     CanFrame {
         id: frame_id.to_id(),
+        format: FrameFormat::Extended,
         data: vec![0; 8], // Query frames have empty data
         timestamp: chrono::Utc::now().timestamp_millis(),
+        hw_timestamp: None,
     }
 }
 
-/// Parse a CAN frame and update BMS data
-pub fn parse_can_frame(frame: &CanFrame, bms_data: &mut BmsData) {
-    let parsed_id = ParsedFrameId::from_id(frame.id);
-
-    if let Ok(command) = BmsCommand::try_from(parsed_id.command) {
-        match command {
-            BmsCommand::ChargeDischargeLimits => {
-                if let Some(limits) = parse_charge_discharge_limits(&frame.data) {
-                    bms_data.limits = Some(limits);
-                }
-            }
-            BmsCommand::SocSoh => {
-                if let Some(soc_soh) = parse_soc_soh(&frame.data) {
-                    bms_data.soc_soh = Some(soc_soh);
-                }
-            }
-            BmsCommand::VoltageCurrent => {
-                if let Some(vc) = parse_voltage_current(&frame.data) {
-                    bms_data.voltage_current = Some(vc);
-                }
-            }
-            BmsCommand::CellVoltage => {
-                if let Some(cv) = parse_cell_voltage(&frame.data) {
-                    bms_data.cell_voltage = Some(cv);
-                }
-            }
-            BmsCommand::Temperature => {
-                if let Some(temp) = parse_temperature(&frame.data) {
-                    bms_data.temperature = Some(temp);
-                }
-            }
-            BmsCommand::OperationStatus => {
-                if let Some(status) = parse_operation_status(&frame.data) {
-                    bms_data.operation_status = Some(status);
-                }
-            }
-            BmsCommand::AccumulatedTimes => {
-                if let Some(times) = parse_accumulated_times(&frame.data) {
-                    bms_data.accumulated_times = Some(times);
-                }
-            }
-            BmsCommand::AccumulatedPower => {
-                if let Some(power) = parse_accumulated_power(&frame.data) {
-                    bms_data.accumulated_power = Some(power);
-                }
-            }
-            BmsCommand::SoftwareVersion => {
-                if let Some(version) = parse_software_version(&frame.data) {
-                    bms_data.software_version = Some(version);
-                }
+/// Build a write frame for `command` carrying `data` (host -> BMS)
+pub fn build_write_frame(
+    command: BmsCommand,
+    data: &[u8],
+    source_address: u8,
+    destination_address: u8,
+) -> CanFrame {
+    let mut frame = build_query_frame(command, source_address, destination_address);
+    frame.data = data.to_vec();
+    frame
+}
+
+/// Default CAN address of the host (PCS) side
+pub const DEFAULT_HOST_ADDRESS: u8 = 0x80;
+
+/// Whether `frame` was sent from `host_address`, i.e. it's our own
+/// transmission looped back by the adapter rather than a BMS reply
+pub fn is_own_frame(frame: &CanFrame, host_address: u8) -> bool {
+    ParsedFrameId::from_frame(frame).source_address == host_address
+}
+
+/// Whether `frame` is one of our own queries to `bms_address` (e.g. echoed by the adapter)
+pub fn is_query_echo(frame: &CanFrame, bms_address: u8) -> bool {
+    ParsedFrameId::from_frame(frame).destination_address == bms_address
+}
+
+/// Parse Command 0xD0 - Debug Status
+pub fn parse_debug_status(data: &[u8], order: Endianness) -> Option<DebugStatusData> {
+    if data.is_empty() || data.len() > 8 {
+        return None;
+    }
+
+    let mut padded = [0u8; 8];
+    padded[..data.len()].copy_from_slice(data);
+
+    Some(DebugStatusData {
+        raw: data.to_vec(),
+        raw_status: order.u64(&padded),
+    })
+}
+
+/// Encode Command 0xD0 - inverse of `parse_debug_status`
+pub fn encode_debug_status(status: &DebugStatusData) -> Vec<u8> {
+    status.raw.clone()
+}
+
+/// Decodes one command's payload into `BmsData`
+/// Returns false if the payload couldn't be decoded
+pub type FrameHandler = fn(&[u8], &ParsedFrameId, Endianness, &mut BmsData) -> bool;
+
+/// Store a decoded value, reporting whether decoding succeeded
+fn store<T>(value: Option<T>, field: &mut Option<T>) -> bool {
+    let decoded = value.is_some();
+    if decoded {
+        *field = value;
+    }
+    decoded
+}
+
+/// Parser registry used by `parse_can_frame`; commands without an entry are ignored
+pub const FRAME_HANDLERS: &[(BmsCommand, FrameHandler)] = &[
+    (BmsCommand::ChargeDischargeLimits, |data, _, order, bms| {
+        store(parse_charge_discharge_limits(data, order), &mut bms.limits)
+    }),
+    (BmsCommand::SocSoh, |data, _, order, bms| {
+        store(parse_soc_soh(data, order), &mut bms.soc_soh)
+    }),
+    (BmsCommand::VoltageCurrent, |data, _, order, bms| {
+        store(parse_voltage_current(data, order), &mut bms.voltage_current)
+    }),
+    (BmsCommand::CellVoltage, |data, id, order, bms| {
+        if !id.cnt {
+            return store(parse_cell_voltage(data, order), &mut bms.cell_voltage);
+        }
+        match parse_pack_cell_chunk(data, order) {
+            Some(chunk) => {
+                merge_pack_chunk(&mut bms.pack_voltages, chunk);
+                true
             }
-            BmsCommand::AlarmStatus => {
-                if let Some(alarm) = parse_alarm_status(&frame.data) {
-                    bms_data.alarm_status = Some(alarm);
-                }
+            None => false,
+        }
+    }),
+    (BmsCommand::Temperature, |data, id, order, bms| {
+        if !id.cnt {
+            return store(parse_temperature(data, order), &mut bms.temperature);
+        }
+        match parse_sensor_temperature_chunk(data, order) {
+            Some(chunk) => merge_sensor_temperatures(&mut bms.sensor_temperatures, chunk),
+            None => false,
+        }
+    }),
+    (BmsCommand::OperationStatus, |data, id, _, bms| {
+        if !id.cnt {
+            return store(parse_operation_status(data), &mut bms.operation_status);
+        }
+        match parse_balancing_chunk(data) {
+            Some(chunk) => {
+                merge_balancing_chunk(bms.balancing.get_or_insert_with(Default::default), chunk);
+                true
             }
-            _ => {}
+            None => false,
         }
+    }),
+    (BmsCommand::AccumulatedTimes, |data, _, order, bms| {
+        store(
+            parse_accumulated_times(data, order),
+            &mut bms.accumulated_times,
+        )
+    }),
+    (BmsCommand::AccumulatedPower, |data, _, order, bms| {
+        store(
+            parse_accumulated_power(data, order),
+            &mut bms.accumulated_power,
+        )
+    }),
+    (BmsCommand::SoftwareVersion, |data, _, _, bms| {
+        let Some(version) = parse_software_version(data) else {
+            return false;
+        };
+        bms.software_version_info = Some(parse_version_string(&version));
+        bms.software_version = Some(version);
+        true
+    }),
+    (BmsCommand::AlarmStatus, |data, _, order, bms| {
+        store(parse_alarm_status(data, order), &mut bms.alarm_status)
+    }),
+    (BmsCommand::DebugStatus, |data, _, order, bms| {
+        store(parse_debug_status(data, order), &mut bms.debug_status)
+    }),
+];
 
-        bms_data.timestamp = chrono::Utc::now().timestamp_millis();
-        bms_data.connected = true;
+/// Registered handler for `command`
+pub fn frame_handler(command: BmsCommand) -> Option<FrameHandler> {
+    FRAME_HANDLERS
+        .iter()
+        .find(|(registered, _)| *registered == command)
+        .map(|&(_, handler)| handler)
+}
+
+/// Parse a CAN frame and update BMS data
+/// Multi-byte fields are read with `order`.
+/// Returns false if the data is empty or longer than 8 bytes, the command is
+/// unknown, or its payload couldn't be decoded. Known commands without a
+/// registered handler leave the data untouched and return true.
+/// Echoed queries aren't detected here, as that needs the configured host
+/// and BMS addresses; callers filter them with `is_own_frame` and
+/// `is_query_echo` first.
+pub fn parse_can_frame(frame: &CanFrame, bms_data: &mut BmsData, order: Endianness) -> bool {
+    if frame.data.is_empty() || frame.data.len() > 8 {
+        return false;
     }
+
+    let parsed_id = ParsedFrameId::from_frame(frame);
+
+    let Ok(command) = BmsCommand::try_from(parsed_id.command) else {
+        return false;
+    };
+
+    let handler = frame_handler(command);
+    let decoded = match handler {
+        Some(handler) => handler(&frame.data, &parsed_id, order, bms_data),
+        None => true,
+    };
+
+    bms_data.timestamp = chrono::Utc::now().timestamp_millis();
+    bms_data.connected = true;
+    if decoded && handler.is_some() {
+        bms_data.updated_at.insert(command, bms_data.timestamp);
+    }
+
+    decoded
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Reply of BMS 0x01 to the host, a continuation frame if `cnt`
+    fn bms_frame(command: BmsCommand, cnt: bool, data: &[u8]) -> CanFrame {
+        let mut frame = build_write_frame(command, data, 0x01, DEFAULT_HOST_ADDRESS);
+        let id = ParsedFrameId {
+            cnt,
+            ..ParsedFrameId::from_frame(&frame)
+        };
+        frame.id = id.to_id();
+        frame
+    }
+
     #[test]
     fn test_parse_charge_discharge_limits() {
         // Example from protocol: 90 21 E8 03 40 1A E8 03
@@ -306,7 +834,7 @@ mod tests {
         // Discharge voltage limit: 672V
         // Discharge current limit: 100A
         let data = [0x90, 0x21, 0xE8, 0x03, 0x40, 0x1A, 0xE8, 0x03];
-        let result = parse_charge_discharge_limits(&data).unwrap();
+        let result = parse_charge_discharge_limits(&data, Endianness::Little).unwrap();
 
         assert!((result.charge_voltage_limit - 859.2).abs() < 0.1);
         assert!((result.charge_current_limit - 100.0).abs() < 0.1);
@@ -318,7 +846,7 @@ mod tests {
     fn test_parse_soc_soh() {
         // Example: SOC: 34%, SOH: 100%, Backup time: 30 minutes
         let data = [0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00];
-        let result = parse_soc_soh(&data).unwrap();
+        let result = parse_soc_soh(&data, Endianness::Little).unwrap();
 
         assert_eq!(result.soc, 34);
         assert_eq!(result.soh, 100);
@@ -329,17 +857,75 @@ mod tests {
     fn test_parse_voltage_current() {
         // Example: 812.1V, -120A (charging)
         let data = [0xB9, 0x1F, 0x50, 0xFB, 0x00, 0x00, 0x00, 0x00];
-        let result = parse_voltage_current(&data).unwrap();
+        let result = parse_voltage_current(&data, Endianness::Little).unwrap();
 
         assert!((result.voltage - 812.1).abs() < 0.1);
         assert!((result.current - (-120.0)).abs() < 0.1);
     }
 
+    #[test]
+    fn test_dc_power_in_watts_and_kilowatts() {
+        // 812.1V x 120A = 97452W, direction comes from the current alone
+        let data = [0xB9, 0x1F, 0xB0, 0x04, 0x00, 0x00, 0x00, 0x00];
+        let discharging = parse_voltage_current(&data, Endianness::Little).unwrap();
+        assert!((discharging.power_w - 97_452.0).abs() < 1e-6);
+        assert!((discharging.power - 97.45).abs() < 0.01);
+
+        let data = [0xB9, 0x1F, 0x50, 0xFB, 0x00, 0x00, 0x00, 0x00];
+        let charging = parse_voltage_current(&data, Endianness::Little).unwrap();
+        assert_eq!(charging.power_w, discharging.power_w);
+        assert!(charging.current < 0.0);
+    }
+
+    #[test]
+    fn test_minimum_payload_lengths() {
+        let payload = *b"V2.19S10";
+        let decodes = |command: BmsCommand, len: usize| {
+            let frame = build_write_frame(command, &payload[..len], 0x01, 0x80);
+            parse_can_frame(&frame, &mut BmsData::default(), Endianness::Little)
+        };
+
+        for &(command, _) in FRAME_HANDLERS {
+            let len = command.min_payload_len();
+            assert!(decodes(command, len), "{:?} with {} bytes", command, len);
+            assert!(
+                !decodes(command, len - 1),
+                "{:?} with {} bytes",
+                command,
+                len - 1
+            );
+        }
+
+        // Bit 40 (the highest defined alarm) in a 6-byte 0xC0 payload
+        let little = parse_alarm_status(&[0, 0, 0, 0, 0, 0x01], Endianness::Little).unwrap();
+        let big = parse_alarm_status(&[0x01, 0, 0, 0, 0, 0], Endianness::Big).unwrap();
+        assert_eq!(little.active_alarms, vec![40]);
+        assert_eq!(big.active_alarms, vec![40]);
+    }
+
+    #[test]
+    fn test_current_conventions() {
+        // Raw -120A: charging per protocol, discharging for PositiveIsCharge firmware
+        let data = [0xB9, 0x1F, 0x50, 0xFB, 0x00, 0x00, 0x00, 0x00];
+
+        let mut protocol = parse_voltage_current(&data, Endianness::Little).unwrap();
+        CurrentConvention::PositiveIsDischarge.apply(&mut protocol);
+        assert!((protocol.current + 120.0).abs() < 0.1);
+        assert!((protocol.raw_current + 120.0).abs() < 0.1);
+
+        let mut flipped = parse_voltage_current(&data, Endianness::Little).unwrap();
+        CurrentConvention::PositiveIsCharge.apply(&mut flipped);
+        CurrentConvention::PositiveIsCharge.apply(&mut flipped);
+        assert!((flipped.current - 120.0).abs() < 0.1);
+        assert!((flipped.raw_current + 120.0).abs() < 0.1);
+        assert!((flipped.power - protocol.power).abs() < 0.001);
+    }
+
     #[test]
     fn test_parse_cell_voltage() {
         // Example: Max 3.394V (PACK 8, Cell 5), Min 3.372V (PACK 11, Cell 2)
         let data = [0x42, 0x0D, 0x08, 0x05, 0x2C, 0x0D, 0x0B, 0x02];
-        let result = parse_cell_voltage(&data).unwrap();
+        let result = parse_cell_voltage(&data, Endianness::Little).unwrap();
 
         assert!((result.max_voltage - 3.394).abs() < 0.001);
         assert_eq!(result.max_voltage_pack_no, 8);
@@ -349,6 +935,435 @@ mod tests {
         assert_eq!(result.min_voltage_cell_no, 2);
     }
 
+    #[test]
+    fn test_big_endian_fields() {
+        // SOC 34 (0x0022) little-endian reads as 0x2200 big-endian
+        let data = [0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00];
+        let little = parse_soc_soh(&data, Endianness::Little).unwrap();
+        let big = parse_soc_soh(&data, Endianness::Big).unwrap();
+        assert_eq!(little.soc, 34);
+        assert_eq!(big.soc, 0x2200);
+
+        // 812.1V / -5.6A sent big-endian
+        let data = [0x1F, 0xB9, 0xFF, 0xC8, 0x00, 0x00, 0x00, 0x00];
+        let vc = parse_voltage_current(&data, Endianness::Big).unwrap();
+        assert!((vc.voltage - 812.1).abs() < 0.01);
+        assert!((vc.current + 5.6).abs() < 0.01);
+
+        let energy = 172_000u32.to_be_bytes();
+        let data = [energy, energy].concat();
+        let power = parse_accumulated_power(&data, Endianness::Big).unwrap();
+        assert!((power.charge_energy - 17200.0).abs() < 0.1);
+        assert_ne!(
+            parse_accumulated_power(&data, Endianness::Little)
+                .unwrap()
+                .charge_energy,
+            power.charge_energy
+        );
+
+        let alarms = (1u64 << 32).to_be_bytes();
+        let status = parse_alarm_status(&alarms, Endianness::Big).unwrap();
+        assert_eq!(status.active_alarms, vec![32]);
+    }
+
+    #[test]
+    fn test_debug_status_round_trip() {
+        let raw = vec![0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x10, 0x00];
+        let frame = bms_frame(BmsCommand::DebugStatus, false, &raw);
+
+        let mut data = BmsData::default();
+        assert!(parse_can_frame(&frame, &mut data, Endianness::Little));
+
+        let debug = data.debug_status.unwrap();
+        assert_eq!(debug.raw, raw);
+        assert_eq!(debug.raw_status, 0x0010_0000_0080_0001);
+    }
+
+    #[test]
+    fn test_operation_status_keeps_unknown_values() {
+        let status = parse_operation_status(&[9, 7, 4, 0]).unwrap();
+
+        assert_eq!(status.system_status, SystemStatus::Unknown(9));
+        assert_eq!(status.work_status, WorkStatus::Unknown(7));
+        assert_eq!(status.operation_status, OperationStatusCode::Unknown(4));
+        assert_eq!(u8::from(status.system_status), 9);
+
+        let known = parse_operation_status(&[4, 1, 1, 0]).unwrap();
+        assert_eq!(known.system_status, SystemStatus::Discharge);
+        assert_eq!(u8::from(known.system_status), 4);
+    }
+
+    #[test]
+    fn test_software_version_rejects_non_printable() {
+        assert_eq!(
+            parse_software_version(b"V2.19S\0\0").as_deref(),
+            Some("V2.19S")
+        );
+        // Stops at 0xFF and at control characters, keeping the valid prefix
+        assert_eq!(
+            parse_software_version(&[b'V', b'2', 0xFF, b'1', 0, 0, 0, 0]).as_deref(),
+            Some("V2")
+        );
+        assert_eq!(
+            parse_software_version(b"V3.0\x07\x1bX").as_deref(),
+            Some("V3.0")
+        );
+        assert_eq!(parse_software_version(&[0xFF; 8]), None);
+        assert_eq!(parse_software_version(&[0x01, b'V', b'2']), None);
+    }
+
+    #[test]
+    fn test_encode_charge_discharge_limits_round_trip() {
+        let data = [0x90, 0x21, 0xE8, 0x03, 0x40, 0x1A, 0xE8, 0x03];
+        for order in [Endianness::Little, Endianness::Big] {
+            let limits = ChargeDischargeLimits {
+                charge_voltage_limit: 859.2,
+                charge_current_limit: 100.0,
+                discharge_voltage_limit: 672.0,
+                discharge_current_limit: 100.0,
+            };
+            let encoded = encode_charge_discharge_limits(&limits, order);
+            let parsed = parse_charge_discharge_limits(&encoded, order).unwrap();
+            assert!((parsed.charge_voltage_limit - 859.2).abs() < 0.01);
+            assert!((parsed.discharge_voltage_limit - 672.0).abs() < 0.01);
+            if order == Endianness::Little {
+                assert_eq!(encoded, data);
+            }
+        }
+
+        let parsed = parse_charge_discharge_limits(&data, Endianness::Little).unwrap();
+        assert_eq!(
+            encode_charge_discharge_limits(&parsed, Endianness::Little),
+            data
+        );
+    }
+
+    /// Deterministic xorshift generator for the round-trip tests
+    struct Samples(u64);
+
+    impl Samples {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn u8(&mut self) -> u8 {
+            self.next() as u8
+        }
+
+        fn u16(&mut self) -> u16 {
+            self.next() as u16
+        }
+
+        /// A value on the `step` grid within `min..=max`
+        fn scaled(&mut self, min: f32, max: f32, step: f32) -> f32 {
+            let steps = ((max - min) / step) as u64;
+            min + (self.next() % (steps + 1)) as f32 * step
+        }
+    }
+
+    fn close(a: f32, b: f32, tolerance: f32) -> bool {
+        (a - b).abs() <= tolerance
+    }
+
+    const ORDERS: [Endianness; 2] = [Endianness::Little, Endianness::Big];
+
+    #[test]
+    fn test_round_trip_soc_soh_and_times() {
+        let mut rng = Samples(0x81);
+        for order in ORDERS {
+            for _ in 0..200 {
+                let x = SocSohData {
+                    soc: rng.u16(),
+                    soh: rng.u16(),
+                    backup_time_minutes: rng.u16(),
+                };
+                let y = parse_soc_soh(&encode_soc_soh(&x, order), order).unwrap();
+                assert_eq!(
+                    (y.soc, y.soh, y.backup_time_minutes),
+                    (x.soc, x.soh, x.backup_time_minutes)
+                );
+
+                let x = AccumulatedTimesData {
+                    charge_times: rng.u16(),
+                    discharge_times: rng.u16(),
+                };
+                let y =
+                    parse_accumulated_times(&encode_accumulated_times(&x, order), order).unwrap();
+                assert_eq!(
+                    (y.charge_times, y.discharge_times),
+                    (x.charge_times, x.discharge_times)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_limits_and_voltage_current() {
+        let mut rng = Samples(0x82);
+        for order in ORDERS {
+            for _ in 0..200 {
+                let x = ChargeDischargeLimits {
+                    charge_voltage_limit: rng.scaled(0.0, 1500.0, 0.1),
+                    charge_current_limit: rng.scaled(0.0, 1000.0, 0.1),
+                    discharge_voltage_limit: rng.scaled(0.0, 1500.0, 0.1),
+                    discharge_current_limit: rng.scaled(0.0, 1000.0, 0.1),
+                };
+                let y = parse_charge_discharge_limits(
+                    &encode_charge_discharge_limits(&x, order),
+                    order,
+                )
+                .unwrap();
+                assert!(close(y.charge_voltage_limit, x.charge_voltage_limit, 0.05));
+                assert!(close(y.charge_current_limit, x.charge_current_limit, 0.05));
+                assert!(close(
+                    y.discharge_voltage_limit,
+                    x.discharge_voltage_limit,
+                    0.05
+                ));
+                assert!(close(
+                    y.discharge_current_limit,
+                    x.discharge_current_limit,
+                    0.05
+                ));
+
+                let voltage = rng.scaled(0.0, 1500.0, 0.1);
+                let current = rng.scaled(-1000.0, 1000.0, 0.1);
+                let x = VoltageCurrentData {
+                    voltage,
+                    current,
+                    power: voltage * current.abs() / 1000.0,
+                    power_w: dc_power_w(voltage as f64, current as f64),
+                    raw_current: current,
+                };
+                let y = parse_voltage_current(&encode_voltage_current(&x, order), order).unwrap();
+                assert!(close(y.voltage, x.voltage, 0.05), "{:?} {:?}", x, y);
+                assert!(close(y.current, x.current, 0.05), "{:?} {:?}", x, y);
+                assert!(close(y.power, x.power, 0.2), "{:?} {:?}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_cell_voltage_and_chunks() {
+        let mut rng = Samples(0x83);
+        for order in ORDERS {
+            for _ in 0..200 {
+                let max_voltage = rng.scaled(2.5, 4.2, 0.001);
+                let min_voltage = rng.scaled(2.5, 4.2, 0.001);
+                let x = CellVoltageData {
+                    max_voltage,
+                    max_voltage_pack_no: rng.u8(),
+                    max_voltage_cell_no: rng.u8(),
+                    min_voltage,
+                    min_voltage_pack_no: rng.u8(),
+                    min_voltage_cell_no: rng.u8(),
+                    voltage_delta: max_voltage - min_voltage,
+                };
+                let y = parse_cell_voltage(&encode_cell_voltage(&x, order), order).unwrap();
+                assert!(close(y.max_voltage, x.max_voltage, 0.0005));
+                assert!(close(y.min_voltage, x.min_voltage, 0.0005));
+                assert!(close(y.voltage_delta, x.voltage_delta, 0.001));
+                assert_eq!(
+                    (
+                        y.max_voltage_pack_no,
+                        y.max_voltage_cell_no,
+                        y.min_voltage_pack_no,
+                        y.min_voltage_cell_no
+                    ),
+                    (
+                        x.max_voltage_pack_no,
+                        x.max_voltage_cell_no,
+                        x.min_voltage_pack_no,
+                        x.min_voltage_cell_no
+                    )
+                );
+
+                let count = (rng.u8() % 4) as usize;
+                let x = PackCellChunk {
+                    pack_no: rng.u8(),
+                    first_cell: rng.u8(),
+                    voltages: (0..count).map(|_| rng.scaled(2.5, 4.2, 0.001)).collect(),
+                };
+                let y = parse_pack_cell_chunk(&encode_pack_cell_chunk(&x, order), order).unwrap();
+                assert_eq!((y.pack_no, y.first_cell), (x.pack_no, x.first_cell));
+                assert_eq!(y.voltages.len(), x.voltages.len());
+                assert!(y
+                    .voltages
+                    .iter()
+                    .zip(&x.voltages)
+                    .all(|(a, b)| close(*a, *b, 0.0005)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_temperature_and_power() {
+        let mut rng = Samples(0x84);
+        for order in ORDERS {
+            for _ in 0..200 {
+                let max_temperature = rng.scaled(-40.0, 120.0, 0.1);
+                let min_temperature = rng.scaled(-40.0, 120.0, 0.1);
+                let x = TemperatureData {
+                    max_temperature,
+                    max_temp_pack_no: rng.u8(),
+                    max_temp_sensor_no: rng.u8(),
+                    min_temperature,
+                    min_temp_pack_no: rng.u8(),
+                    min_temp_sensor_no: rng.u8(),
+                    temp_delta: max_temperature - min_temperature,
+                };
+                let y = parse_temperature(&encode_temperature(&x, order), order).unwrap();
+                assert!(close(y.max_temperature, x.max_temperature, 0.05));
+                assert!(close(y.min_temperature, x.min_temperature, 0.05));
+                assert!(close(y.temp_delta, x.temp_delta, 0.1));
+                assert_eq!(
+                    (
+                        y.max_temp_pack_no,
+                        y.max_temp_sensor_no,
+                        y.min_temp_pack_no,
+                        y.min_temp_sensor_no
+                    ),
+                    (
+                        x.max_temp_pack_no,
+                        x.max_temp_sensor_no,
+                        x.min_temp_pack_no,
+                        x.min_temp_sensor_no
+                    )
+                );
+
+                let x = AccumulatedPowerData::from_raw(rng.next() as u32, rng.next() as u32);
+                let y =
+                    parse_accumulated_power(&encode_accumulated_power(&x, order), order).unwrap();
+                assert_eq!(y.charge_energy_raw, x.charge_energy_raw);
+                assert_eq!(y.discharge_energy_raw, x.discharge_energy_raw);
+                assert_eq!(y.charge_energy, x.charge_energy);
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_status_frames() {
+        let mut rng = Samples(0x85);
+        for order in ORDERS {
+            for _ in 0..200 {
+                let flags = rng.u8();
+                let x = OperationStatusData {
+                    system_status: SystemStatus::from(rng.u8()),
+                    work_status: WorkStatus::from(rng.u8()),
+                    operation_status: OperationStatusCode::from(rng.u8()),
+                    discharge_prohibited: flags & 0x01 != 0,
+                    charge_prohibited: flags & 0x02 != 0,
+                    discharge_prohibited_hard: flags & 0x04 != 0,
+                    prohibition_flags: flags,
+                };
+                let y = parse_operation_status(&encode_operation_status(&x)).unwrap();
+                assert_eq!(y.system_status, x.system_status);
+                assert_eq!(y.work_status, x.work_status);
+                assert_eq!(y.operation_status, x.operation_status);
+                assert_eq!(
+                    (
+                        y.discharge_prohibited,
+                        y.charge_prohibited,
+                        y.discharge_prohibited_hard
+                    ),
+                    (
+                        x.discharge_prohibited,
+                        x.charge_prohibited,
+                        x.discharge_prohibited_hard
+                    )
+                );
+                assert_eq!(y.prohibition_flags, x.prohibition_flags);
+
+                let x = parse_alarm_status(&rng.next().to_le_bytes(), Endianness::Little).unwrap();
+                let y = parse_alarm_status(&encode_alarm_status(&x, order), order).unwrap();
+                assert_eq!(y.raw_status, x.raw_status);
+                assert_eq!(y.active_alarms, x.active_alarms);
+                assert_eq!(y.max_severity, x.max_severity);
+
+                let len = 1 + (rng.u8() % 8) as usize;
+                let x = parse_debug_status(&rng.next().to_le_bytes()[..len], order).unwrap();
+                assert_eq!(parse_debug_status(&encode_debug_status(&x), order), Some(x));
+            }
+        }
+
+        for version in ["V2.19S", "1.0", "ABCDEFGH"] {
+            let encoded = encode_software_version(version);
+            assert_eq!(parse_software_version(&encoded).as_deref(), Some(version));
+        }
+    }
+
+    #[test]
+    fn test_accumulated_power_keeps_large_raw_value() {
+        let raw = u32::MAX - 1;
+        let mut data = [0u8; 8];
+        data[0..4].copy_from_slice(&raw.to_le_bytes());
+        data[4..8].copy_from_slice(&16_777_217u32.to_le_bytes());
+
+        let power = parse_accumulated_power(&data, Endianness::Little).unwrap();
+        assert_eq!(power.charge_energy_raw, raw);
+        assert_eq!(power.discharge_energy_raw, 16_777_217);
+
+        // The float is only accurate to f32 precision: 429496729.4kWh can't be represented
+        let exact = raw as f64 * 0.1;
+        assert_ne!(power.charge_energy as f64, exact);
+        assert!((power.charge_energy as f64 - exact).abs() < 32.0);
+    }
+
+    #[test]
+    fn test_prohibition_byte_is_preserved() {
+        let status = parse_operation_status(&[0x04, 0x01, 0x01, 0x07]).unwrap();
+        assert!(status.discharge_prohibited);
+        assert!(status.charge_prohibited);
+        assert!(status.discharge_prohibited_hard);
+        assert_eq!(status.prohibition_flags, 0x07);
+        assert_eq!(status.prohibitions(), Prohibition::ALL);
+        assert_eq!(status.undocumented_prohibition_bits(), 0);
+
+        let status = parse_operation_status(&[0x04, 0x01, 0x01, 0x82]).unwrap();
+        assert_eq!(status.prohibitions(), [Prohibition::Charge]);
+        assert_eq!(status.undocumented_prohibition_bits(), 0x80);
+    }
+
+    #[test]
+    fn test_unregistered_command_is_noop() {
+        assert!(frame_handler(BmsCommand::Reset).is_none());
+
+        let frame = bms_frame(
+            BmsCommand::Reset,
+            false,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+        );
+        let mut data = BmsData::default();
+        assert!(parse_can_frame(&frame, &mut data, Endianness::Little));
+
+        assert!(data.soc_soh.is_none());
+        assert!(data.updated_at.is_empty());
+        assert!(data.connected);
+    }
+
+    #[test]
+    fn test_parse_version_string() {
+        let version = parse_version_string("V2.19S");
+        assert_eq!(version.raw, "V2.19S");
+        assert_eq!(version.major, Some(2));
+        assert_eq!(version.minor, Some(19));
+        assert_eq!(version.suffix.as_deref(), Some("S"));
+
+        let version = parse_version_string("1.0");
+        assert_eq!(version.major, Some(1));
+        assert_eq!(version.minor, Some(0));
+        assert_eq!(version.suffix, None);
+
+        let version = parse_version_string("#?!");
+        assert_eq!(version.raw, "#?!");
+        assert_eq!(version.major, None);
+        assert_eq!(version.minor, None);
+        assert_eq!(version.suffix, None);
+    }
+
     #[test]
     fn test_parse_frame_id() {
         // Example: Frame header: 18080010
@@ -361,4 +1376,244 @@ mod tests {
         assert_eq!(parsed.source_address, 0x01);
         assert!(!parsed.cnt);
     }
+
+    #[test]
+    fn test_frame_id_round_trip() {
+        // Deterministic xorshift so failures are reproducible
+        let mut state = 0x2545_F491u32;
+        for _ in 0..10_000 {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+
+            let parsed = ParsedFrameId::from_id(state);
+            assert_eq!(
+                parsed.to_id(),
+                state & FRAME_ID_FIELD_MASK,
+                "id {:#010X}",
+                state
+            );
+            assert_eq!(ParsedFrameId::from_id(parsed.to_id()), parsed);
+        }
+
+        // Bits above 28 are ignored
+        assert_eq!(
+            ParsedFrameId::from_id(0xF808_0010),
+            ParsedFrameId::from_id(0x1808_0010)
+        );
+    }
+
+    #[test]
+    fn test_parse_alarm_status_severity() {
+        // Bit 10 (circuit breaker, mild) alone must still report a severity
+        let data = (1u64 << 10).to_le_bytes();
+        let alarm = parse_alarm_status(&data, Endianness::Little).unwrap();
+        assert_eq!(alarm.active_alarms, vec![10]);
+        assert_eq!(alarm.max_severity, 1);
+
+        let data = ((1u64 << 10) | (1 << 31)).to_le_bytes();
+        assert_eq!(
+            parse_alarm_status(&data, Endianness::Little)
+                .unwrap()
+                .max_severity,
+            3
+        );
+    }
+
+    #[test]
+    fn test_reserved_alarm_bits_are_unknown() {
+        let data = ((1u64 << 50) | (1 << 17) | (1 << 2)).to_le_bytes();
+        let alarm = parse_alarm_status(&data, Endianness::Little).unwrap();
+        assert_eq!(alarm.active_alarms, vec![2]);
+        assert_eq!(alarm.unknown_alarms, vec![17, 50]);
+        assert_eq!(alarm.max_severity, 2);
+
+        let reserved_only = parse_alarm_status(&(1u64 << 50).to_le_bytes(), Endianness::Little);
+        let reserved_only = reserved_only.unwrap();
+        assert!(reserved_only.active_alarms.is_empty());
+        assert_eq!(reserved_only.unknown_alarms, vec![50]);
+        assert_eq!(reserved_only.max_severity, 0);
+    }
+
+    #[test]
+    fn test_two_pack_cell_voltages() {
+        let frame = |cnt, data: Vec<u8>| bms_frame(BmsCommand::CellVoltage, cnt, &data);
+
+        let mut data = BmsData::default();
+        // Standard max/min frame still parses
+        parse_can_frame(
+            &frame(false, vec![0x42, 0x0D, 0x01, 0x05, 0x38, 0x0D, 0x02, 0x08]),
+            &mut data,
+            Endianness::Little,
+        );
+        // Pack 2 cells 0-1, then pack 1 cells 0-2 and 3 (short last chunks)
+        let chunks = [
+            vec![2, 0, 0x38, 0x0D, 0x3A, 0x0D, 0xFF, 0xFF],
+            vec![1, 0, 0x40, 0x0D, 0x41, 0x0D, 0x42, 0x0D],
+            vec![1, 3, 0x3F, 0x0D, 0xFF, 0xFF, 0xFF, 0xFF],
+        ];
+        for chunk in chunks {
+            assert!(parse_can_frame(
+                &frame(true, chunk),
+                &mut data,
+                Endianness::Little
+            ));
+        }
+
+        assert!((data.cell_voltage.unwrap().max_voltage - 3.394).abs() < 0.001);
+        assert_eq!(data.pack_voltages.len(), 2);
+        assert_eq!(data.pack_voltages[0].pack_no, 1);
+        assert_eq!(data.pack_voltages[0].cell_voltages.len(), 4);
+        assert!((data.pack_voltages[0].cell_voltages[3] - 3.391).abs() < 0.001);
+        assert_eq!(data.pack_voltages[1].pack_no, 2);
+        assert_eq!(data.pack_voltages[1].cell_voltages.len(), 2);
+        assert!((data.pack_voltages[1].cell_voltages[0] - 3.384).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_sensor_temperatures_from_continuation_frames() {
+        let frame = |cnt, data: Vec<u8>| bms_frame(BmsCommand::Temperature, cnt, &data);
+
+        let mut data = BmsData::default();
+        // Max 27.5 °C (pack 2, sensor 4), min -2.0 °C (pack 1, sensor 1)
+        assert!(parse_can_frame(
+            &frame(false, vec![0x13, 0x01, 0x02, 0x04, 0xEC, 0xFF, 0x01, 0x01]),
+            &mut data,
+            Endianness::Little
+        ));
+        // Sensors 0-2, then 3-4 with an unused last slot
+        let chunks = [
+            vec![0x00, 0x00, 0xFA, 0x00, 0x04, 0x01, 0xEC, 0xFF],
+            vec![0x03, 0x00, 0x13, 0x01, 0x00, 0x01, 0xFF, 0x7F],
+        ];
+        for chunk in chunks {
+            assert!(parse_can_frame(
+                &frame(true, chunk),
+                &mut data,
+                Endianness::Little
+            ));
+        }
+
+        // A corrupted sensor index doesn't grow the list
+        assert!(!parse_can_frame(
+            &frame(true, vec![0xFF, 0xFF, 0xFA, 0x00, 0x04, 0x01, 0xEC, 0xFF]),
+            &mut data,
+            Endianness::Little
+        ));
+
+        let expected = [25.0, 26.0, -2.0, 27.5, 25.6];
+        assert_eq!(data.sensor_temperatures.len(), expected.len());
+        for (got, want) in data.sensor_temperatures.iter().zip(expected) {
+            assert!((got - want).abs() < 0.01, "{} != {}", got, want);
+        }
+        let temperature = data.temperature.as_ref().unwrap();
+        assert!((temperature.max_temperature - 27.5).abs() < 0.01);
+        assert!((temperature.min_temperature + 2.0).abs() < 0.01);
+
+        // A new round from sensor 0 drops readings of a previous, larger pack
+        assert!(parse_can_frame(
+            &frame(true, vec![0x00, 0x00, 0xFA, 0x00, 0x04, 0x01, 0xFF, 0x7F]),
+            &mut data,
+            Endianness::Little
+        ));
+        assert_eq!(data.sensor_temperatures, vec![25.0, 26.0]);
+
+        let chunk = SensorTemperatureChunk {
+            first_sensor: 300,
+            temperatures: vec![-12.5, 41.0],
+        };
+        let encoded = encode_sensor_temperature_chunk(&chunk, Endianness::Big);
+        assert_eq!(
+            parse_sensor_temperature_chunk(&encoded, Endianness::Big),
+            Some(chunk)
+        );
+    }
+
+    #[test]
+    fn test_two_cells_balancing() {
+        let frame = |data: Vec<u8>| bms_frame(BmsCommand::OperationStatus, true, &data);
+
+        // Pack 1: cells 0 and 9 (bit 0 of byte 2, bit 1 of byte 3)
+        let mut data = BmsData::default();
+        assert!(parse_can_frame(
+            &frame(vec![1, 0, 0x01, 0x02, 0, 0, 0, 0]),
+            &mut data,
+            Endianness::Little
+        ));
+        let balancing = data.balancing.clone().unwrap();
+        assert_eq!(balancing.packs.len(), 1);
+        assert_eq!(balancing.packs[0].pack_no, 1);
+        assert_eq!(balancing.packs[0].cells, vec![0, 9]);
+        assert_eq!(balancing.active_cells(), 2);
+        assert!(data.operation_status.is_none());
+
+        // A later chunk for cells 48+ keeps cells 0 and 9
+        parse_can_frame(
+            &frame(vec![1, 48, 0x04, 0, 0, 0, 0, 0]),
+            &mut data,
+            Endianness::Little,
+        );
+        assert_eq!(
+            data.balancing.as_ref().unwrap().packs[0].cells,
+            vec![0, 9, 50]
+        );
+
+        // Cell 9 stops balancing
+        parse_can_frame(
+            &frame(vec![1, 0, 0x01, 0, 0, 0, 0, 0]),
+            &mut data,
+            Endianness::Little,
+        );
+        assert_eq!(data.balancing.as_ref().unwrap().packs[0].cells, vec![0, 50]);
+
+        let chunk = parse_balancing_chunk(&[2, 0, 0x01, 0x02, 0, 0, 0, 0]).unwrap();
+        assert_eq!(
+            encode_balancing_chunk(&chunk),
+            [2, 0, 0x01, 0x02, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_rejects_empty_and_oversized_data() {
+        let mut data = BmsData::default();
+        let mut frame = build_query_frame(BmsCommand::SocSoh, 0x01, 0x02);
+        let id = ParsedFrameId {
+            source_address: 0x02,
+            destination_address: 0x01,
+            ..ParsedFrameId::from_id(frame.id)
+        };
+        frame.id = id.to_id();
+
+        frame.data = Vec::new();
+        assert!(!parse_can_frame(&frame, &mut data, Endianness::Little));
+        frame.data = vec![0x22; 9];
+        assert!(!parse_can_frame(&frame, &mut data, Endianness::Little));
+        assert!(data.soc_soh.is_none());
+    }
+
+    #[test]
+    fn test_own_query_echo_is_detected() {
+        let reply = build_write_frame(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+            0x01,
+            DEFAULT_HOST_ADDRESS,
+        );
+        let echo = build_query_frame(BmsCommand::SocSoh, DEFAULT_HOST_ADDRESS, 0x01);
+        assert!(is_own_frame(&echo, DEFAULT_HOST_ADDRESS));
+        assert!(is_query_echo(&echo, 0x01));
+        assert!(!is_own_frame(&reply, DEFAULT_HOST_ADDRESS));
+        assert!(!is_query_echo(&reply, 0x01));
+
+        // A BMS may sit at the default host address when the host is moved
+        let mut data = BmsData::default();
+        let from_0x80 = build_write_frame(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+            DEFAULT_HOST_ADDRESS,
+            0x90,
+        );
+        assert!(parse_can_frame(&from_0x80, &mut data, Endianness::Little));
+        assert_eq!(data.soc_soh.as_ref().unwrap().soc, 34);
+    }
 }
@@ -2,12 +2,13 @@
 //! Based on Ecube BMS-PCS Communication Protocol V1.20
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// CAN baud rate for BMS communication (125Kbps)
 pub const CAN_BAUD_RATE: u32 = 125_000;
 
 /// BMS Command codes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum BmsCommand {
     /// 0x80 - Charge/discharge voltage and current limits
@@ -64,6 +65,56 @@ impl TryFrom<u8> for BmsCommand {
     }
 }
 
+impl BmsCommand {
+    /// Every variant, in code order of the protocol document
+    pub const ALL: [BmsCommand; 14] = [
+        BmsCommand::ChargeDischargeLimits,
+        BmsCommand::SocSoh,
+        BmsCommand::VoltageCurrent,
+        BmsCommand::CellVoltage,
+        BmsCommand::Temperature,
+        BmsCommand::OperationStatus,
+        BmsCommand::AccumulatedTimes,
+        BmsCommand::AccumulatedPower,
+        BmsCommand::SoftwareVersion,
+        BmsCommand::Shutdown,
+        BmsCommand::ForceOutput,
+        BmsCommand::Reset,
+        BmsCommand::AlarmStatus,
+        BmsCommand::DebugStatus,
+    ];
+
+    /// Variant name, as used in the serialized form
+    pub fn name(self) -> String {
+        format!("{:?}", self)
+    }
+
+    /// Shortest payload that carries every field the protocol defines
+    ///
+    /// Frames are usually padded to 8 bytes, but only these bytes are needed.
+    /// 0xC0 needs 6 bytes to reach the highest defined alarm bit (40);
+    /// commands without payload fields need 1 byte (empty frames are always
+    /// rejected).
+    pub fn min_payload_len(self) -> usize {
+        match self {
+            BmsCommand::ChargeDischargeLimits => 8,
+            BmsCommand::SocSoh => 6,
+            BmsCommand::VoltageCurrent => 4,
+            BmsCommand::CellVoltage => 8,
+            BmsCommand::Temperature => 8,
+            BmsCommand::OperationStatus => 4,
+            BmsCommand::AccumulatedTimes => 4,
+            BmsCommand::AccumulatedPower => 8,
+            BmsCommand::AlarmStatus => 6,
+            BmsCommand::SoftwareVersion
+            | BmsCommand::Shutdown
+            | BmsCommand::ForceOutput
+            | BmsCommand::Reset
+            | BmsCommand::DebugStatus => 1,
+        }
+    }
+}
+
 /// System status values
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -76,6 +127,8 @@ pub enum SystemStatus {
     WaitToCharge = 5,
     WaitToDischarge = 6,
     Lock = 7,
+    /// Value not defined by the protocol
+    Unknown(u8),
 }
 
 impl From<u8> for SystemStatus {
@@ -89,7 +142,23 @@ impl From<u8> for SystemStatus {
             5 => SystemStatus::WaitToCharge,
             6 => SystemStatus::WaitToDischarge,
             7 => SystemStatus::Lock,
-            _ => SystemStatus::PowerOn,
+            other => SystemStatus::Unknown(other),
+        }
+    }
+}
+
+impl From<SystemStatus> for u8 {
+    fn from(status: SystemStatus) -> u8 {
+        match status {
+            SystemStatus::PowerOn => 0,
+            SystemStatus::Start => 1,
+            SystemStatus::Alone => 2,
+            SystemStatus::Charge => 3,
+            SystemStatus::Discharge => 4,
+            SystemStatus::WaitToCharge => 5,
+            SystemStatus::WaitToDischarge => 6,
+            SystemStatus::Lock => 7,
+            SystemStatus::Unknown(raw) => raw,
         }
     }
 }
@@ -101,6 +170,8 @@ pub enum WorkStatus {
     Empty = 0,
     Boot = 1,
     ShutDown = 2,
+    /// Value not defined by the protocol
+    Unknown(u8),
 }
 
 impl From<u8> for WorkStatus {
@@ -109,7 +180,18 @@ impl From<u8> for WorkStatus {
             0 => WorkStatus::Empty,
             1 => WorkStatus::Boot,
             2 => WorkStatus::ShutDown,
-            _ => WorkStatus::Empty,
+            other => WorkStatus::Unknown(other),
+        }
+    }
+}
+
+impl From<WorkStatus> for u8 {
+    fn from(status: WorkStatus) -> u8 {
+        match status {
+            WorkStatus::Empty => 0,
+            WorkStatus::Boot => 1,
+            WorkStatus::ShutDown => 2,
+            WorkStatus::Unknown(raw) => raw,
         }
     }
 }
@@ -122,6 +204,8 @@ pub enum OperationStatusCode {
     Normal = 1,
     Alarm = 2,
     Fault = 3,
+    /// Value not defined by the protocol
+    Unknown(u8),
 }
 
 impl From<u8> for OperationStatusCode {
@@ -131,7 +215,19 @@ impl From<u8> for OperationStatusCode {
             1 => OperationStatusCode::Normal,
             2 => OperationStatusCode::Alarm,
             3 => OperationStatusCode::Fault,
-            _ => OperationStatusCode::Empty,
+            other => OperationStatusCode::Unknown(other),
+        }
+    }
+}
+
+impl From<OperationStatusCode> for u8 {
+    fn from(status: OperationStatusCode) -> u8 {
+        match status {
+            OperationStatusCode::Empty => 0,
+            OperationStatusCode::Normal => 1,
+            OperationStatusCode::Alarm => 2,
+            OperationStatusCode::Fault => 3,
+            OperationStatusCode::Unknown(raw) => raw,
         }
     }
 }
@@ -209,38 +305,337 @@ pub enum AlarmBit {
     CanHallDataFailure = 40,
 }
 
+/// Description and severity of a defined alarm bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlarmInfo {
+    pub bit: AlarmBit,
+    pub description: &'static str,
+    /// Severity level (1=mild, 2=moderate, 3=severe)
+    pub severity: u8,
+}
+
+/// Canonical alarm table, ordered by bit (bit 17 is unused)
+pub const ALARM_TABLE: [AlarmInfo; 40] = [
+    AlarmInfo {
+        bit: AlarmBit::CellOverVoltage,
+        description: "Cell over voltage",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::CellUnderVoltage,
+        description: "Cell under voltage",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingOverTempAlarm,
+        description: "Charging over temperature alarm",
+        severity: 2,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingLowTempAlarm,
+        description: "Charging low temperature alarm",
+        severity: 2,
+    },
+    AlarmInfo {
+        bit: AlarmBit::DischargingOverTempPrealarm,
+        description: "Discharging over temperature pre-alarm",
+        severity: 2,
+    },
+    AlarmInfo {
+        bit: AlarmBit::DischargingLowTempPrealarm,
+        description: "Discharging low temperature pre-alarm",
+        severity: 2,
+    },
+    AlarmInfo {
+        bit: AlarmBit::DischargingOverCurrentPrealarm,
+        description: "Discharging over current pre-alarm",
+        severity: 2,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingOverCurrentPrealarm,
+        description: "Charging over current pre-alarm",
+        severity: 2,
+    },
+    AlarmInfo {
+        bit: AlarmBit::TotalOverVoltagePrealarm,
+        description: "Total over voltage pre-alarm",
+        severity: 2,
+    },
+    AlarmInfo {
+        bit: AlarmBit::TotalUnderVoltageWarning,
+        description: "Total under voltage warning",
+        severity: 2,
+    },
+    AlarmInfo {
+        bit: AlarmBit::CircuitBreakerDisconnected,
+        description: "Circuit breaker disconnected",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::BalancedChargingFailed,
+        description: "Balanced charging failed",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::PositivePackVoltageImbalance,
+        description: "Positive battery pack voltage imbalance",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::NegativePackVoltageImbalance,
+        description: "Negative battery pack voltage imbalance",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::BmuCommunicationInterruption,
+        description: "BMU communication interruption",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::WaterFloodingDetectionAlarm,
+        description: "Water flooding detection alarm",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::WaterFloodingProtection,
+        description: "Water flooding detection and protection",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingOverTempProtection,
+        description: "Charging over temperature protection",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingLowTempProtection,
+        description: "Charging low temperature protection",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::DischargingOverTempProtection,
+        description: "Discharging over temperature protection",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::DischargingLowTempProtection,
+        description: "Discharging low temperature protection",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::DischargingOverCurrentProtectionL1,
+        description: "Discharging over current protection level 1",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::DischargingOverCurrentProtectionL2,
+        description: "Discharging over current protection level 2",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingOverCurrentProtectionL1,
+        description: "Charging over current protection level 1",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingOverCurrentProtectionL2,
+        description: "Charging over current protection level 2",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingOverCurrentProtectionL3,
+        description: "Charging over current protection level 3",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::TotalChargingOverVoltageProtection,
+        description: "Total charging over voltage protection",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::TotalChargingUnderVoltageProtection,
+        description: "Total charging under voltage protection",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ChargingDcContactorFailure,
+        description: "Charging DC contactor failure",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::DischargingDcContactorFailure,
+        description: "Discharging DC contactor failure",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::EpoShutdown,
+        description: "EPO shut down",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::FireProtection,
+        description: "Fire protection",
+        severity: 3,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ParallelCommunicationAbnormality,
+        description: "Parallel communication abnormality",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::ParallelAddressConflict,
+        description: "Parallel address conflict",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::InsulationMonitoringAlarm,
+        description: "Insulation monitoring alarm",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::HydrogenProtection,
+        description: "Hydrogen protection",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::BatteryPackFanMalfunction,
+        description: "Battery pack fan malfunction",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::BatteryPackFuseTempHigh,
+        description: "Battery pack fuse temperature too high",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::CanHallCommunicationInterruption,
+        description: "CAN Hall communication interruption",
+        severity: 1,
+    },
+    AlarmInfo {
+        bit: AlarmBit::CanHallDataFailure,
+        description: "CAN Hall data failure",
+        severity: 1,
+    },
+];
+
+/// Look up the table entry for a raw alarm bit
+pub fn alarm_info(bit: u8) -> Option<&'static AlarmInfo> {
+    ALARM_TABLE.iter().find(|info| info.bit as u8 == bit)
+}
+
+impl AlarmBit {
+    /// Alarm for a raw bit index, if defined
+    pub fn from_bit(bit: u8) -> Option<AlarmBit> {
+        alarm_info(bit).map(|info| info.bit)
+    }
+
+    /// Table entry for this alarm
+    pub fn info(self) -> &'static AlarmInfo {
+        alarm_info(self as u8).expect("every AlarmBit has an ALARM_TABLE entry")
+    }
+}
+
 /// Get alarm severity level (1=mild, 2=moderate, 3=severe)
 pub fn get_alarm_severity(alarm: AlarmBit) -> u8 {
-    match alarm {
-        AlarmBit::CellOverVoltage
-        | AlarmBit::CellUnderVoltage
-        | AlarmBit::BmuCommunicationInterruption
-        | AlarmBit::ChargingOverTempProtection
-        | AlarmBit::ChargingLowTempProtection
-        | AlarmBit::DischargingOverTempProtection
-        | AlarmBit::DischargingLowTempProtection
-        | AlarmBit::DischargingOverCurrentProtectionL1
-        | AlarmBit::DischargingOverCurrentProtectionL2
-        | AlarmBit::ChargingOverCurrentProtectionL1
-        | AlarmBit::ChargingOverCurrentProtectionL2
-        | AlarmBit::ChargingOverCurrentProtectionL3
-        | AlarmBit::TotalChargingOverVoltageProtection
-        | AlarmBit::TotalChargingUnderVoltageProtection
-        | AlarmBit::ChargingDcContactorFailure
-        | AlarmBit::DischargingDcContactorFailure
-        | AlarmBit::EpoShutdown
-        | AlarmBit::FireProtection => 3,
-
-        AlarmBit::ChargingOverTempAlarm
-        | AlarmBit::ChargingLowTempAlarm
-        | AlarmBit::DischargingOverTempPrealarm
-        | AlarmBit::DischargingLowTempPrealarm
-        | AlarmBit::DischargingOverCurrentPrealarm
-        | AlarmBit::ChargingOverCurrentPrealarm
-        | AlarmBit::TotalOverVoltagePrealarm
-        | AlarmBit::TotalUnderVoltageWarning => 2,
-
-        _ => 1,
+    alarm.info().severity
+}
+
+/// Byte order of multi-byte payload fields
+///
+/// The protocol specifies little-endian; some firmware variants send
+/// big-endian instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    /// Read a u16 from the first 2 bytes of `b`
+    pub fn u16(self, b: &[u8]) -> u16 {
+        let b = [b[0], b[1]];
+        match self {
+            Endianness::Little => u16::from_le_bytes(b),
+            Endianness::Big => u16::from_be_bytes(b),
+        }
+    }
+
+    /// Read an i16 from the first 2 bytes of `b`
+    pub fn i16(self, b: &[u8]) -> i16 {
+        self.u16(b) as i16
+    }
+
+    /// Read a u32 from the first 4 bytes of `b`
+    pub fn u32(self, b: &[u8]) -> u32 {
+        let b = [b[0], b[1], b[2], b[3]];
+        match self {
+            Endianness::Little => u32::from_le_bytes(b),
+            Endianness::Big => u32::from_be_bytes(b),
+        }
+    }
+
+    /// Read a u64 from the first 8 bytes of `b`
+    pub fn u64(self, b: &[u8]) -> u64 {
+        let b: [u8; 8] = b[..8].try_into().unwrap();
+        match self {
+            Endianness::Little => u64::from_le_bytes(b),
+            Endianness::Big => u64::from_be_bytes(b),
+        }
+    }
+
+    /// Write `value` into the first 2 bytes of `b`
+    pub fn put_u16(self, b: &mut [u8], value: u16) {
+        let bytes = match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        };
+        b[..2].copy_from_slice(&bytes);
+    }
+
+    /// Write `value` into the first 4 bytes of `b`
+    pub fn put_u32(self, b: &mut [u8], value: u32) {
+        let bytes = match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        };
+        b[..4].copy_from_slice(&bytes);
+    }
+
+    /// Write `value` into the first 8 bytes of `b`
+    pub fn put_u64(self, b: &mut [u8], value: u64) {
+        let bytes = match self {
+            Endianness::Little => value.to_le_bytes(),
+            Endianness::Big => value.to_be_bytes(),
+        };
+        b[..8].copy_from_slice(&bytes);
+    }
+}
+
+/// Sign convention of the current field (0x82) sent by the BMS
+///
+/// The protocol specifies positive = discharge, which is also the app's
+/// internal convention for `VoltageCurrentData::current`. Some firmware
+/// variants report charging as positive instead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CurrentConvention {
+    #[default]
+    PositiveIsDischarge,
+    PositiveIsCharge,
+}
+
+impl CurrentConvention {
+    /// Convert a current as sent by the BMS to the internal convention
+    pub fn to_internal(self, raw: f32) -> f32 {
+        match self {
+            CurrentConvention::PositiveIsDischarge => raw,
+            CurrentConvention::PositiveIsCharge => -raw,
+        }
+    }
+
+    /// Recompute `current` from `raw_current`; repeated calls are harmless
+    pub fn apply(self, vc: &mut VoltageCurrentData) {
+        vc.current = self.to_internal(vc.raw_current);
     }
 }
 
@@ -278,8 +673,15 @@ pub struct VoltageCurrentData {
     pub voltage: f32,
     /// Battery output current in A (0.1A resolution, positive=discharge, negative=charge)
     pub current: f32,
-    /// Power in kW (calculated)
+    /// DC power in kW (calculated from `power_w`; magnitude, the direction is
+    /// the sign of `current`)
     pub power: f32,
+    /// DC power in W, computed in f64 from the raw fields
+    #[serde(default)]
+    pub power_w: f64,
+    /// Current as sent by the BMS, before applying `CurrentConvention`
+    #[serde(default)]
+    pub raw_current: f32,
 }
 
 /// Command 0x83 - Cell Voltage Data
@@ -302,6 +704,41 @@ pub struct CellVoltageData {
     pub voltage_delta: f32,
 }
 
+/// Per-pack cell voltages from 0x83 continuation frames
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackVoltage {
+    /// PACK number (as reported, 1-based)
+    pub pack_no: u8,
+    /// Cell voltages in V by 0-based cell index (0.0 until reported)
+    pub cell_voltages: Vec<f32>,
+}
+
+/// Cells one pack is balancing, from 0x85 continuation frames
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackBalancing {
+    /// PACK number (as reported, 1-based)
+    pub pack_no: u8,
+    /// 0-based indices of the cells being balanced, ascending
+    pub cells: Vec<u16>,
+}
+
+/// Cell balancing activity per pack
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BalancingStatus {
+    /// Packs in ascending PACK number; packs without reports are omitted
+    pub packs: Vec<PackBalancing>,
+}
+
+impl BalancingStatus {
+    /// Number of cells being balanced across all packs
+    pub fn active_cells(&self) -> usize {
+        self.packs.iter().map(|p| p.cells.len()).sum()
+    }
+}
+
 /// Command 0x84 - Temperature Data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -338,6 +775,50 @@ pub struct OperationStatusData {
     pub charge_prohibited: bool,
     /// Discharge prohibited (cannot be allowed after OC/UV cleared)
     pub discharge_prohibited_hard: bool,
+    /// Raw prohibition byte (byte 3), including bits the protocol doesn't define
+    #[serde(default)]
+    pub prohibition_flags: u8,
+}
+
+impl OperationStatusData {
+    /// Active documented prohibitions
+    pub fn prohibitions(&self) -> Vec<Prohibition> {
+        Prohibition::ALL
+            .into_iter()
+            .filter(|p| self.prohibition_flags & p.mask() != 0)
+            .collect()
+    }
+
+    /// Set prohibition bits the protocol doesn't define
+    pub fn undocumented_prohibition_bits(&self) -> u8 {
+        self.prohibition_flags & !Prohibition::DOCUMENTED_MASK
+    }
+}
+
+/// Documented bits of the 0x85 prohibition byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Prohibition {
+    /// Discharge prohibited, can be allowed after OT/UT cleared
+    Discharge = 0x01,
+    Charge = 0x02,
+    /// Discharge prohibited, cannot be allowed after OC/UV cleared
+    DischargeHard = 0x04,
+}
+
+impl Prohibition {
+    pub const ALL: [Prohibition; 3] = [
+        Prohibition::Discharge,
+        Prohibition::Charge,
+        Prohibition::DischargeHard,
+    ];
+
+    /// All documented bits
+    pub const DOCUMENTED_MASK: u8 = 0x07;
+
+    pub fn mask(self) -> u8 {
+        self as u8
+    }
 }
 
 /// Command 0x86 - Accumulated Times
@@ -351,6 +832,10 @@ pub struct AccumulatedTimesData {
 }
 
 /// Command 0x87 - Accumulated Power
+///
+/// The kWh values are f32, which holds integers exactly only up to 2^24: above
+/// a raw count of 16,777,216 (about 1.68 GWh) they are off by up to several
+/// kWh near the u32 maximum. Use the raw counters where exact values matter.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AccumulatedPowerData {
@@ -358,6 +843,171 @@ pub struct AccumulatedPowerData {
     pub charge_energy: f32,
     /// Accumulated discharging energy in kWh (0.1kWh resolution)
     pub discharge_energy: f32,
+    /// Raw charging counter in 0.1kWh
+    pub charge_energy_raw: u32,
+    /// Raw discharging counter in 0.1kWh
+    pub discharge_energy_raw: u32,
+}
+
+impl AccumulatedPowerData {
+    /// Build from the raw 0.1kWh counters
+    pub fn from_raw(charge_energy_raw: u32, discharge_energy_raw: u32) -> Self {
+        AccumulatedPowerData {
+            charge_energy: (charge_energy_raw as f64 * 0.1) as f32,
+            discharge_energy: (discharge_energy_raw as f64 * 0.1) as f32,
+            charge_energy_raw,
+            discharge_energy_raw,
+        }
+    }
+}
+
+/// Command 0x8F - Software version decoded from the raw ASCII string
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftwareVersion {
+    /// Raw version string as reported (e.g. "V2.19S")
+    pub raw: String,
+    /// Major version number
+    pub major: Option<u16>,
+    /// Minor version number
+    pub minor: Option<u16>,
+    /// Trailing suffix after the numbers (e.g. "S")
+    pub suffix: Option<String>,
+}
+
+impl SoftwareVersion {
+    /// Whether this version is `min` or newer; `None` if no major version was found
+    ///
+    /// A missing minor version counts as 0.
+    pub fn at_least(&self, min: FirmwareVersion) -> Option<bool> {
+        let major = self.major?;
+        Some((major, self.minor.unwrap_or(0)) >= (min.major, min.minor))
+    }
+}
+
+/// Oldest protocol revision whose field layout the parser assumes
+pub const MIN_SUPPORTED_FIRMWARE: FirmwareVersion = FirmwareVersion {
+    major: 1,
+    minor: 20,
+};
+
+/// Firmware version threshold, e.g. 1.20 for "V1.20"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirmwareVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl Default for FirmwareVersion {
+    fn default() -> Self {
+        MIN_SUPPORTED_FIRMWARE
+    }
+}
+
+impl FirmwareVersion {
+    /// Set `protocol_compatible` from the decoded version; returns true when
+    /// the device just turned out to be older than `self`
+    pub fn apply(self, data: &mut BmsData) -> bool {
+        let compatible = data
+            .software_version_info
+            .as_ref()
+            .and_then(|v| v.at_least(self));
+        let newly_outdated = compatible == Some(false) && data.protocol_compatible != Some(false);
+        data.protocol_compatible = compatible;
+        newly_outdated
+    }
+}
+
+impl std::fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "V{}.{:02}", self.major, self.minor)
+    }
+}
+
+/// Installed packs and cells, to sanity-check the indices the BMS reports
+///
+/// Pack and cell numbers are 1-based. A limit left unset isn't checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatteryLayout {
+    pub pack_count: Option<u8>,
+    pub cells_per_pack: Option<u8>,
+}
+
+impl BatteryLayout {
+    /// Max/min cell and temperature indices in `data` outside the layout
+    pub fn out_of_range(&self, data: &BmsData) -> Vec<String> {
+        let in_range =
+            |index: u8, count: Option<u8>| count.is_none_or(|n| (1..=n).contains(&index));
+        let mut errors = Vec::new();
+        let mut check = |what: &str, index: u8, count: Option<u8>, limit: &str| {
+            if !in_range(index, count) {
+                errors.push(format!(
+                    "{} {} is outside 1..={} {}",
+                    what,
+                    index,
+                    count.unwrap_or_default(),
+                    limit
+                ));
+            }
+        };
+
+        if let Some(ref cells) = data.cell_voltage {
+            let packs = self.pack_count;
+            check(
+                "Max cell voltage pack",
+                cells.max_voltage_pack_no,
+                packs,
+                "packs",
+            );
+            check(
+                "Max cell voltage cell",
+                cells.max_voltage_cell_no,
+                self.cells_per_pack,
+                "cells",
+            );
+            check(
+                "Min cell voltage pack",
+                cells.min_voltage_pack_no,
+                packs,
+                "packs",
+            );
+            check(
+                "Min cell voltage cell",
+                cells.min_voltage_cell_no,
+                self.cells_per_pack,
+                "cells",
+            );
+        }
+        if let Some(ref temps) = data.temperature {
+            check(
+                "Max temperature pack",
+                temps.max_temp_pack_no,
+                self.pack_count,
+                "packs",
+            );
+            check(
+                "Min temperature pack",
+                temps.min_temp_pack_no,
+                self.pack_count,
+                "packs",
+            );
+        }
+        errors
+    }
+
+    /// Set `index_errors`; returns the errors that weren't flagged before
+    pub fn apply(self, data: &mut BmsData) -> Vec<String> {
+        let errors = self.out_of_range(data);
+        let new = errors
+            .iter()
+            .filter(|e| !data.index_errors.contains(e))
+            .cloned()
+            .collect();
+        data.index_errors = errors;
+        new
+    }
 }
 
 /// Command 0xC0 - Alarm Status
@@ -368,10 +1018,26 @@ pub struct AlarmStatus {
     pub raw_status: u64,
     /// Active alarm indices
     pub active_alarms: Vec<u8>,
+    /// Active bits without a defined alarm (reserved 17 and 41-63)
+    #[serde(default)]
+    pub unknown_alarms: Vec<u8>,
     /// Severity level (1=mild, 2=moderate, 3=severe)
     pub max_severity: u8,
 }
 
+/// Command 0xD0 - Debug Status
+///
+/// The protocol doesn't document the bit meanings, so the payload is kept
+/// as-is for diagnostics.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugStatusData {
+    /// Raw payload bytes
+    pub raw: Vec<u8>,
+    /// Payload as a 64-bit status word (zero-padded if shorter than 8 bytes)
+    pub raw_status: u64,
+}
+
 /// Complete BMS Data
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -388,33 +1054,170 @@ pub struct BmsData {
     pub voltage_current: Option<VoltageCurrentData>,
     /// Cell voltage data
     pub cell_voltage: Option<CellVoltageData>,
+    /// Per-pack cell voltages, empty unless the BMS sends continuation frames
+    #[serde(default)]
+    pub pack_voltages: Vec<PackVoltage>,
     /// Temperature data
     pub temperature: Option<TemperatureData>,
+    /// Temperature per sensor in °C, empty unless the BMS sends 0x84
+    /// continuation frames
+    #[serde(default)]
+    pub sensor_temperatures: Vec<f32>,
     /// Operation status
     pub operation_status: Option<OperationStatusData>,
+    /// Cell balancing, `None` unless the BMS sends 0x85 continuation frames
+    #[serde(default)]
+    pub balancing: Option<BalancingStatus>,
     /// Accumulated times
     pub accumulated_times: Option<AccumulatedTimesData>,
     /// Accumulated power
     pub accumulated_power: Option<AccumulatedPowerData>,
     /// BMS software version
     pub software_version: Option<String>,
+    /// BMS software version with major/minor parsed out
+    pub software_version_info: Option<SoftwareVersion>,
+    /// Whether the firmware is at least the configured minimum version,
+    /// `None` until a parseable version was received
+    #[serde(default)]
+    pub protocol_compatible: Option<bool>,
+    /// Reported pack/cell indices outside the configured `BatteryLayout`,
+    /// e.g. from a parse error or bus crosstalk; the values are still kept
+    #[serde(default)]
+    pub index_errors: Vec<String>,
+    /// SOC, voltage and current haven't changed at all for a while, see
+    /// `FreezeConfig`; a firmware fault or a battery at rest
+    #[serde(default)]
+    pub possibly_frozen: bool,
     /// Alarm status
     pub alarm_status: Option<AlarmStatus>,
+    /// Debug status
+    #[serde(default)]
+    pub debug_status: Option<DebugStatusData>,
+    /// Time in ms each command's data was last decoded, so fields carried
+    /// over from before a gap or reconnect can be told apart from fresh ones
+    #[serde(default)]
+    pub updated_at: HashMap<BmsCommand, i64>,
+}
+
+/// Decoded data of a single query command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", content = "data")]
+pub enum CommandData {
+    ChargeDischargeLimits(ChargeDischargeLimits),
+    SocSoh(SocSohData),
+    VoltageCurrent(VoltageCurrentData),
+    CellVoltage(CellVoltageData),
+    Temperature(TemperatureData),
+    OperationStatus(OperationStatusData),
+    AccumulatedTimes(AccumulatedTimesData),
+    AccumulatedPower(AccumulatedPowerData),
+    SoftwareVersion(String),
+    AlarmStatus(AlarmStatus),
+}
+
+impl BmsData {
+    /// The part of the data filled in by `command`, if it has been received
+    pub fn command_data(&self, command: BmsCommand) -> Option<CommandData> {
+        match command {
+            BmsCommand::ChargeDischargeLimits => {
+                self.limits.clone().map(CommandData::ChargeDischargeLimits)
+            }
+            BmsCommand::SocSoh => self.soc_soh.clone().map(CommandData::SocSoh),
+            BmsCommand::VoltageCurrent => self
+                .voltage_current
+                .clone()
+                .map(CommandData::VoltageCurrent),
+            BmsCommand::CellVoltage => self.cell_voltage.clone().map(CommandData::CellVoltage),
+            BmsCommand::Temperature => self.temperature.clone().map(CommandData::Temperature),
+            BmsCommand::OperationStatus => self
+                .operation_status
+                .clone()
+                .map(CommandData::OperationStatus),
+            BmsCommand::AccumulatedTimes => self
+                .accumulated_times
+                .clone()
+                .map(CommandData::AccumulatedTimes),
+            BmsCommand::AccumulatedPower => self
+                .accumulated_power
+                .clone()
+                .map(CommandData::AccumulatedPower),
+            BmsCommand::SoftwareVersion => self
+                .software_version
+                .clone()
+                .map(CommandData::SoftwareVersion),
+            BmsCommand::AlarmStatus => self.alarm_status.clone().map(CommandData::AlarmStatus),
+            _ => None,
+        }
+    }
+
+    /// Time in ms the newest frame was decoded, `None` before the first one
+    ///
+    /// Unlike `timestamp`, which every query cycle bumps when it sends, this
+    /// only moves when the BMS actually answered.
+    pub fn last_received_ms(&self) -> Option<i64> {
+        self.updated_at.values().copied().max()
+    }
+}
+
+/// CAN identifier format
+///
+/// The protocol uses 29-bit extended ids; some gateways bridge it to 11-bit
+/// standard frames, see `ParsedFrameId::to_standard_id` for what that loses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameFormat {
+    /// 11-bit identifier
+    Standard,
+    /// 29-bit identifier
+    #[default]
+    Extended,
 }
 
 /// CAN Frame structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CanFrame {
-    /// 29-bit extended identifier
+    /// 29-bit extended or 11-bit standard identifier, see `format`
     pub id: u32,
+    #[serde(default)]
+    pub format: FrameFormat,
     /// Data bytes (1-8 bytes)
     pub data: Vec<u8>,
     /// Timestamp
     pub timestamp: i64,
+    /// Adapter arrival time in 0.1ms ticks, if the adapter provides one
+    ///
+    /// Only the iTEKON/VCI path fills this; the counter is free-running and
+    /// wraps, so use it for differences between frames, not absolute time.
+    #[serde(default)]
+    pub hw_timestamp: Option<u32>,
+}
+
+impl CanFrame {
+    /// The same frame with its id rewritten to the 11-bit standard layout
+    pub fn to_standard(&self) -> CanFrame {
+        CanFrame {
+            id: ParsedFrameId::from_frame(self).to_standard_id(),
+            format: FrameFormat::Standard,
+            data: self.data.clone(),
+            timestamp: self.timestamp,
+            hw_timestamp: self.hw_timestamp,
+        }
+    }
 }
 
+/// Valid bits of a 29-bit extended CAN identifier
+pub const CAN_EXT_ID_MASK: u32 = 0x1FFF_FFFF;
+
+/// Valid bits of an 11-bit standard CAN identifier
+pub const CAN_STD_ID_MASK: u32 = 0x7FF;
+
+/// Identifier bits carried by `ParsedFrameId` (bits 0-2 are reserved)
+pub const FRAME_ID_FIELD_MASK: u32 = 0x1FFF_FFF8;
+
 /// Parsed CAN Frame ID
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Layout: ptp(28) | command(27-20) | destination(19-12) | source(11-4) | cnt(3)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ParsedFrameId {
     /// Point-to-point flag
     pub ptp: bool,
@@ -431,6 +1234,7 @@ pub struct ParsedFrameId {
 impl ParsedFrameId {
     /// Parse a 29-bit CAN frame ID
     pub fn from_id(id: u32) -> Self {
+        let id = id & CAN_EXT_ID_MASK;
         ParsedFrameId {
             ptp: (id >> 28) & 1 == 1,
             command: ((id >> 20) & 0xFF) as u8,
@@ -454,4 +1258,36 @@ impl ParsedFrameId {
         }
         id
     }
+
+    /// Parse a 11-bit standard CAN frame ID
+    ///
+    /// Layout: command(10-3) | node(2-0). The node bits hold the BMS address,
+    /// which is taken as the source; the destination is not carried and is 0.
+    pub fn from_standard_id(id: u32) -> Self {
+        let id = id & CAN_STD_ID_MASK;
+        ParsedFrameId {
+            ptp: true,
+            command: (id >> 3) as u8,
+            destination_address: 0,
+            source_address: (id & 0x07) as u8,
+            cnt: false,
+        }
+    }
+
+    /// Build a 11-bit standard CAN frame ID for a query/write to the BMS
+    ///
+    /// Standard ids can't carry the full addressing scheme: only BMS addresses
+    /// 0-7 (taken from the destination) fit, and the host address, PTP and CNT
+    /// flags are dropped. Echoed queries can't be told apart from responses.
+    pub fn to_standard_id(&self) -> u32 {
+        ((self.command as u32) << 3) | (self.destination_address as u32 & 0x07)
+    }
+
+    /// Parse the id of `frame` according to its format
+    pub fn from_frame(frame: &CanFrame) -> Self {
+        match frame.format {
+            FrameFormat::Standard => Self::from_standard_id(frame.id),
+            FrameFormat::Extended => Self::from_id(frame.id),
+        }
+    }
 }
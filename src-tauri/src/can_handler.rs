@@ -2,10 +2,14 @@
 //! Supports USB-CAN adapters via serial port and SocketCAN on Linux
 
 use crate::bms_types::*;
+use crate::freeze::{FreezeConfig, FreezeDetector};
+use crate::units::UnitSystem;
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -16,10 +20,68 @@ pub enum CanError {
     ParseError(String),
     #[error("Connection timeout")]
     Timeout,
+    /// The link works but not a single frame arrived during a query cycle
+    #[error("No response from BMS")]
+    NoResponse,
+    /// A query was aborted through `CanManager::cancel_flag`, e.g. by a disconnect
+    #[error("Query cancelled")]
+    Cancelled,
     #[error("Device not found: {0}")]
     DeviceNotFound(String),
+    /// The adapter went away (e.g. unplugged); only a reconnect can recover
+    #[error("Device disconnected: {0}")]
+    DeviceDisconnected(String),
     #[error("IO error: {0}")]
     IoError(String),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Invalid frame: {0}")]
+    InvalidFrame(String),
+}
+
+impl CanError {
+    /// Whether the link is gone for good, as opposed to a single failed read
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, CanError::DeviceDisconnected(_))
+    }
+}
+
+/// Whether a serial I/O error means the device itself is gone
+///
+/// Unplugging shows up as different errors per platform: a closed pipe or
+/// EOF, or an OS error such as ENODEV/ENXIO/EIO (Linux, macOS) or
+/// ERROR_DEVICE_NOT_CONNECTED/ERROR_BAD_COMMAND (Windows).
+pub fn is_device_gone(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    if matches!(
+        err.kind(),
+        ErrorKind::BrokenPipe
+            | ErrorKind::NotConnected
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionReset
+            | ErrorKind::UnexpectedEof
+            | ErrorKind::NotFound
+    ) {
+        return true;
+    }
+    #[cfg(unix)]
+    const DEVICE_GONE_CODES: &[i32] = &[5, 6, 19];
+    #[cfg(windows)]
+    const DEVICE_GONE_CODES: &[i32] = &[22, 1167];
+    #[cfg(not(any(unix, windows)))]
+    const DEVICE_GONE_CODES: &[i32] = &[];
+    err.raw_os_error()
+        .is_some_and(|code| DEVICE_GONE_CODES.contains(&code))
+}
+
+/// Map a serial I/O error to `DeviceDisconnected` or `SerialError`
+fn serial_error(err: std::io::Error) -> CanError {
+    if is_device_gone(&err) {
+        CanError::DeviceDisconnected(err.to_string())
+    } else {
+        CanError::SerialError(err.to_string())
+    }
 }
 
 impl From<std::io::Error> for CanError {
@@ -40,11 +102,86 @@ pub struct CanConfig {
     /// CAN bus baud rate (125K for BMS)
     pub can_baud_rate: u32,
     /// SocketCAN interface name (for Linux)
+    #[cfg_attr(not(target_os = "linux"), serde(skip_serializing))]
     pub socket_can_interface: Option<String>,
     /// BMS address
     pub bms_address: u8,
     /// Host address (PCS)
     pub host_address: u8,
+    /// Unit system used by display commands
+    #[serde(default)]
+    pub unit_system: UnitSystem,
+    /// Commands polled by `CanManager::poll`
+    #[serde(default)]
+    pub query_plan: QueryPlan,
+    /// `query_all_data` re-queries `STATIC_COMMANDS` every this many calls (0 or 1 = every call)
+    #[serde(default = "default_static_query_every")]
+    pub static_query_every: u32,
+    /// Clock used for received frame timestamps
+    #[serde(default)]
+    pub timestamp_source: TimestampSource,
+    /// Settings for the I+BT Bluetooth adapter
+    #[serde(default)]
+    pub bluetooth: BluetoothConfig,
+    /// Delay after each query frame in ms (adapter default if unset)
+    #[serde(default)]
+    pub send_delay_ms: Option<u64>,
+    /// Receive timeout per expected response in ms (adapter default if unset)
+    #[serde(default)]
+    pub receive_timeout_ms: Option<u64>,
+    /// Byte order of multi-byte payload fields
+    #[serde(default)]
+    pub endianness: Endianness,
+    /// Sign convention of the BMS's current field
+    #[serde(default)]
+    pub current_convention: CurrentConvention,
+    /// Id format of sent frames; received frames carry their own
+    #[serde(default)]
+    pub frame_format: FrameFormat,
+    /// Reset the shared `BmsData` on disconnect instead of keeping the last values
+    #[serde(default)]
+    pub clear_data_on_disconnect: bool,
+    /// Bytes requested per serial read; a burst that doesn't fit is read next time
+    #[serde(default = "default_read_buffer_size")]
+    pub read_buffer_size: usize,
+    /// Further adapter channels, each with its own BMS (iTEKON and simulation only)
+    #[serde(default)]
+    pub extra_channels: Vec<ExtraChannel>,
+    /// Oldest firmware whose field layout is supported; older devices are
+    /// still read, but flagged with `BmsData::protocol_compatible`
+    #[serde(default)]
+    pub min_firmware_version: FirmwareVersion,
+    /// Pack and cell counts reported indices are checked against, see
+    /// `BmsData::index_errors`
+    #[serde(default)]
+    pub battery_layout: BatteryLayout,
+    /// When unchanging SOC/voltage/current count as frozen, see
+    /// `BmsData::possibly_frozen`
+    #[serde(default)]
+    pub freeze_detection: FreezeConfig,
+    /// Drop a received frame identical in id and data to one received within
+    /// this many ms, for adapters that deliver frames twice (off if unset);
+    /// sending a command's query again resets the window for its replies
+    #[serde(default)]
+    pub dedup_window_ms: Option<u64>,
+}
+
+/// An adapter channel polled alongside the primary one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtraChannel {
+    /// Adapter channel index (iTEKON CAN1 = 0, CAN2 = 1)
+    pub channel: u32,
+    /// Address of the BMS on that channel, distinct from all others
+    pub bms_address: u8,
+}
+
+fn default_static_query_every() -> u32 {
+    DEFAULT_STATIC_QUERY_EVERY
+}
+
+fn default_read_buffer_size() -> usize {
+    DEFAULT_READ_BUFFER_SIZE
 }
 
 impl Default for CanConfig {
@@ -56,8 +193,336 @@ impl Default for CanConfig {
             can_baud_rate: CAN_BAUD_RATE,
             socket_can_interface: None,
             bms_address: 0x01,
-            host_address: 0x80,
+            host_address: crate::bms_parser::DEFAULT_HOST_ADDRESS,
+            unit_system: UnitSystem::Metric,
+            query_plan: QueryPlan::default(),
+            static_query_every: DEFAULT_STATIC_QUERY_EVERY,
+            timestamp_source: TimestampSource::WallClock,
+            bluetooth: BluetoothConfig::default(),
+            send_delay_ms: None,
+            receive_timeout_ms: None,
+            endianness: Endianness::Little,
+            current_convention: CurrentConvention::PositiveIsDischarge,
+            frame_format: FrameFormat::Extended,
+            clear_data_on_disconnect: false,
+            read_buffer_size: DEFAULT_READ_BUFFER_SIZE,
+            extra_channels: Vec::new(),
+            min_firmware_version: MIN_SUPPORTED_FIRMWARE,
+            battery_layout: BatteryLayout::default(),
+            freeze_detection: FreezeConfig::default(),
+            dedup_window_ms: None,
+        }
+    }
+}
+
+/// Receive attempts `CanManager::query_single` makes before timing out
+pub const SINGLE_QUERY_ATTEMPTS: usize = 5;
+/// Allowed number of tries for `CanManager::ping`
+pub const PING_COUNT_RANGE: std::ops::RangeInclusive<u32> = 1..=100;
+
+/// Allowed inter-frame send delay in ms
+pub const SEND_DELAY_RANGE_MS: std::ops::RangeInclusive<u64> = 0..=1000;
+/// Allowed per-response receive timeout in ms
+pub const RECEIVE_TIMEOUT_RANGE_MS: std::ops::RangeInclusive<u64> = 5..=5000;
+/// Allowed duplicate-frame window in ms
+pub const DEDUP_WINDOW_RANGE_MS: std::ops::RangeInclusive<u64> = 1..=1000;
+
+/// Commands whose data rarely changes (software version, accumulated times)
+pub const STATIC_COMMANDS: [BmsCommand; 2] =
+    [BmsCommand::AccumulatedTimes, BmsCommand::SoftwareVersion];
+/// Default `static_query_every`: static data is refreshed every 10th full query
+pub const DEFAULT_STATIC_QUERY_EVERY: u32 = 10;
+
+/// Default serial read size in bytes, room for 16 full I+ frames
+pub const DEFAULT_READ_BUFFER_SIZE: usize = 256;
+/// Allowed serial read size in bytes
+pub const READ_BUFFER_SIZE_RANGE: std::ops::RangeInclusive<usize> = 16..=65536;
+
+/// Node addresses usable for the BMS and the host
+pub const NODE_ADDRESS_RANGE: std::ops::RangeInclusive<u8> = 0x01..=0xFE;
+
+/// Upper bound for voltage limits written with `set_charge_discharge_limits`, in V
+pub const MAX_VOLTAGE_LIMIT: f32 = 1500.0;
+/// Upper bound for current limits written with `set_charge_discharge_limits`, in A
+pub const MAX_CURRENT_LIMIT: f32 = 1000.0;
+
+impl CanConfig {
+    /// Check that configured timing and buffer sizes are within a safe range
+    /// and that extra channels are usable
+    pub fn validate(&self) -> Result<(), CanError> {
+        if !self.extra_channels.is_empty()
+            && !matches!(
+                self.adapter_type,
+                AdapterType::ItekonCan | AdapterType::Simulation
+            )
+        {
+            return Err(CanError::InvalidConfig(
+                "extra_channels need an iTEKON or simulation adapter".to_string(),
+            ));
+        }
+        let nodes = std::iter::once(("host_address", self.host_address))
+            .chain(std::iter::once(("bms_address", self.bms_address)))
+            .chain(
+                self.extra_channels
+                    .iter()
+                    .map(|extra| ("extra_channels.bms_address", extra.bms_address)),
+            );
+        for (name, address) in nodes {
+            if !NODE_ADDRESS_RANGE.contains(&address) {
+                return Err(CanError::InvalidConfig(format!(
+                    "{} must be between 0x{:02X} and 0x{:02X}, got 0x{:02X}",
+                    name,
+                    NODE_ADDRESS_RANGE.start(),
+                    NODE_ADDRESS_RANGE.end(),
+                    address
+                )));
+            }
+        }
+
+        // The primary adapter channel is 0
+        let mut channels = BTreeSet::from([0]);
+        for extra in &self.extra_channels {
+            if !channels.insert(extra.channel) {
+                return Err(CanError::InvalidConfig(format!(
+                    "CAN channel {} is used by more than one channel (0 is the primary)",
+                    extra.channel
+                )));
+            }
+        }
+
+        let mut addresses = BTreeSet::from([self.bms_address]);
+        for extra in &self.extra_channels {
+            if !addresses.insert(extra.bms_address) {
+                return Err(CanError::InvalidConfig(format!(
+                    "BMS address 0x{:02X} is used by more than one channel",
+                    extra.bms_address
+                )));
+            }
+        }
+        if addresses.contains(&self.host_address) {
+            return Err(CanError::InvalidConfig(format!(
+                "host address 0x{:02X} is also a BMS address",
+                self.host_address
+            )));
+        }
+
+        let layout = [
+            ("battery_layout.pack_count", self.battery_layout.pack_count),
+            (
+                "battery_layout.cells_per_pack",
+                self.battery_layout.cells_per_pack,
+            ),
+        ];
+        for (name, count) in layout {
+            if count == Some(0) {
+                return Err(CanError::InvalidConfig(format!(
+                    "{} must be at least 1",
+                    name
+                )));
+            }
+        }
+
+        self.freeze_detection
+            .validate()
+            .map_err(CanError::InvalidConfig)?;
+
+        if !READ_BUFFER_SIZE_RANGE.contains(&self.read_buffer_size) {
+            return Err(CanError::InvalidConfig(format!(
+                "read_buffer_size must be between {} and {} bytes, got {}",
+                READ_BUFFER_SIZE_RANGE.start(),
+                READ_BUFFER_SIZE_RANGE.end(),
+                self.read_buffer_size
+            )));
+        }
+
+        let checks = [
+            ("send_delay_ms", self.send_delay_ms, &SEND_DELAY_RANGE_MS),
+            (
+                "receive_timeout_ms",
+                self.receive_timeout_ms,
+                &RECEIVE_TIMEOUT_RANGE_MS,
+            ),
+            (
+                "dedup_window_ms",
+                self.dedup_window_ms,
+                &DEDUP_WINDOW_RANGE_MS,
+            ),
+            (
+                "bluetooth.send_delay_ms",
+                Some(self.bluetooth.send_delay_ms),
+                &SEND_DELAY_RANGE_MS,
+            ),
+            (
+                "bluetooth.receive_timeout_ms",
+                Some(self.bluetooth.receive_timeout_ms),
+                &RECEIVE_TIMEOUT_RANGE_MS,
+            ),
+        ];
+        for (name, value, range) in checks {
+            if let Some(value) = value {
+                if !range.contains(&value) {
+                    return Err(CanError::InvalidConfig(format!(
+                        "{} must be between {} and {} ms, got {}",
+                        name,
+                        range.start(),
+                        range.end(),
+                        value
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// I+BT Bluetooth adapter settings
+///
+/// The SPP (RFCOMM) link adds tens of milliseconds per hop, so it gets its own
+/// baud rate and longer timeouts instead of sharing the USB-CAN values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BluetoothConfig {
+    /// Serial baud rate of the SPP link
+    pub baud_rate: u32,
+    /// Port open timeout in ms (RFCOMM connection setup can take seconds)
+    pub open_timeout_ms: u64,
+    /// Delay between queries in ms
+    pub send_delay_ms: u64,
+    /// Receive timeout per response in ms
+    pub receive_timeout_ms: u64,
+}
+
+impl Default for BluetoothConfig {
+    fn default() -> Self {
+        BluetoothConfig {
+            baud_rate: 115200,
+            open_timeout_ms: 5000,
+            send_delay_ms: 60,
+            receive_timeout_ms: 200,
+        }
+    }
+}
+
+/// Timing used by `CanManager` for the active adapter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkTiming {
+    /// Serial port open timeout
+    pub open_timeout: Duration,
+    /// Delay after each query frame
+    pub send_delay: Duration,
+    /// Receive timeout per expected response
+    pub receive_timeout: Duration,
+}
+
+/// Whether a serial port name looks like a Bluetooth SPP port
+fn is_bluetooth_port_name(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    // Linux /dev/rfcommN, macOS /dev/cu.<device>-Bluetooth... or /dev/tty.Bluetooth-...
+    name.contains("rfcomm") || name.contains("bluetooth")
+}
+
+/// Clock used for received frame timestamps
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampSource {
+    /// System time when the frame was read (jumps if the clock is adjusted)
+    #[default]
+    WallClock,
+    /// Wall-clock time at connect plus monotonic time since connect
+    Monotonic,
+}
+
+/// Frame clock anchored at connect time
+///
+/// Monotonic stamps are `connected_at_ms` plus elapsed `Instant` time, so they
+/// never go backwards and still map to absolute time.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameClock {
+    source: TimestampSource,
+    started: Instant,
+    connected_at_ms: i64,
+}
+
+impl FrameClock {
+    pub fn new(source: TimestampSource) -> Self {
+        FrameClock {
+            source,
+            started: Instant::now(),
+            connected_at_ms: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Wall-clock time (ms) when the clock was started
+    pub fn connected_at_ms(&self) -> i64 {
+        self.connected_at_ms
+    }
+
+    /// Timestamp in ms for a frame read now
+    pub fn now_ms(&self) -> i64 {
+        match self.source {
+            TimestampSource::WallClock => chrono::Utc::now().timestamp_millis(),
+            TimestampSource::Monotonic => {
+                self.connected_at_ms + self.started.elapsed().as_millis() as i64
+            }
+        }
+    }
+}
+
+/// Every command answered by the BMS, in query order
+pub const ALL_QUERY_COMMANDS: [BmsCommand; 10] = [
+    BmsCommand::ChargeDischargeLimits,
+    BmsCommand::SocSoh,
+    BmsCommand::VoltageCurrent,
+    BmsCommand::CellVoltage,
+    BmsCommand::Temperature,
+    BmsCommand::OperationStatus,
+    BmsCommand::AccumulatedTimes,
+    BmsCommand::AccumulatedPower,
+    BmsCommand::SoftwareVersion,
+    BmsCommand::AlarmStatus,
+];
+
+/// Polling plan: the fast set is queried every cycle, the slow set every Nth
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QueryPlan {
+    /// Commands queried every cycle
+    pub fast: Vec<BmsCommand>,
+    /// Commands queried every `slow_every` cycles
+    pub slow: Vec<BmsCommand>,
+    /// Slow set interval in cycles (0 or 1 queries it every cycle)
+    pub slow_every: u32,
+}
+
+impl Default for QueryPlan {
+    fn default() -> Self {
+        QueryPlan {
+            fast: vec![
+                BmsCommand::SocSoh,
+                BmsCommand::VoltageCurrent,
+                BmsCommand::AlarmStatus,
+            ],
+            slow: vec![
+                BmsCommand::ChargeDischargeLimits,
+                BmsCommand::CellVoltage,
+                BmsCommand::Temperature,
+                BmsCommand::OperationStatus,
+                BmsCommand::AccumulatedTimes,
+                BmsCommand::AccumulatedPower,
+                BmsCommand::SoftwareVersion,
+            ],
+            slow_every: 10,
+        }
+    }
+}
+
+impl QueryPlan {
+    /// Commands to query in the given cycle; the slow set is included in cycle 0
+    pub fn commands_for_cycle(&self, cycle: u32) -> Vec<BmsCommand> {
+        let mut commands = self.fast.clone();
+        if cycle.is_multiple_of(self.slow_every.max(1)) {
+            commands.extend(self.slow.iter().filter(|c| !self.fast.contains(c)));
         }
+        commands
     }
 }
 
@@ -77,397 +542,3899 @@ pub enum AdapterType {
     Simulation,
 }
 
+/// Reason an I+ Series frame was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IplusFrameError {
+    /// Fewer bytes than the header and declared length require
+    TooShort,
+    /// First byte is not 0xAA
+    BadHeader,
+    /// Frame type other than standard (0x00) or extended (0x01)
+    UnsupportedType(u8),
+    /// Checksum byte doesn't match the sum of the preceding bytes
+    ChecksumMismatch,
+}
+
+/// I+ checksum: wrapping byte sum of everything before the checksum byte
+///
+/// This covers the 0xAA header, type, id, length and data. Build and parse
+/// both go through here so their ranges can't drift apart.
+fn iplus_checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |acc, &x| acc.wrapping_add(x))
+}
+
 /// Parse I+ Series frame format
 /// Frame format: 0xAA + type(1) + id(4) + len(1) + data(0-8) + checksum(1)
-fn parse_iplus_frame(buffer: &[u8]) -> Option<CanFrame> {
+fn parse_iplus_frame(buffer: &[u8]) -> Result<CanFrame, IplusFrameError> {
     if buffer.len() < 8 {
-        return None;
+        return Err(IplusFrameError::TooShort);
     }
 
     // Check header
     if buffer[0] != 0xAA {
-        return None;
+        return Err(IplusFrameError::BadHeader);
     }
 
-    let frame_type = buffer[1];
-    if frame_type != 0x01 {
-        // Extended frame
-        return None;
-    }
+    let format = match buffer[1] {
+        0x00 => FrameFormat::Standard,
+        0x01 => FrameFormat::Extended,
+        frame_type => return Err(IplusFrameError::UnsupportedType(frame_type)),
+    };
 
     let id = u32::from_le_bytes([buffer[2], buffer[3], buffer[4], buffer[5]]);
     let len = buffer[6] as usize;
 
     if buffer.len() < 7 + len + 1 {
-        return None;
+        return Err(IplusFrameError::TooShort);
     }
 
     let data = buffer[7..7 + len].to_vec();
 
-    // Verify checksum
-    let mut checksum: u8 = 0;
-    for i in 0..7 + len {
-        checksum = checksum.wrapping_add(buffer[i]);
-    }
-    if checksum != buffer[7 + len] {
-        return None;
+    if iplus_checksum(&buffer[..7 + len]) != buffer[7 + len] {
+        return Err(IplusFrameError::ChecksumMismatch);
     }
 
-    Some(CanFrame {
+    Ok(CanFrame {
         id,
+        format,
         data,
         timestamp: chrono::Utc::now().timestamp_millis(),
+        hw_timestamp: None,
     })
 }
 
-/// Build I+ Series frame format
-fn build_iplus_frame(frame: &CanFrame) -> Vec<u8> {
-    let mut buffer = Vec::with_capacity(16);
-
-    buffer.push(0xAA); // Header
-    buffer.push(0x01); // Extended frame type
-    buffer.extend_from_slice(&frame.id.to_le_bytes());
-    buffer.push(frame.data.len() as u8);
-    buffer.extend_from_slice(&frame.data);
+/// Byte transport of the serial (I+ / I+BT) adapters
+///
+/// `CanManager` only talks to the port through this trait, so the I+ read
+/// path can be driven by a scripted `MockPort` instead of hardware.
+pub trait CanPort: Send {
+    /// Write all bytes to the adapter
+    fn write_all(&mut self, data: &[u8]) -> std::io::Result<()>;
+    /// Read available bytes, waiting at most `timeout`
+    ///
+    /// `Ok(0)` or a `TimedOut` error means nothing arrived.
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> std::io::Result<usize>;
+}
 
-    // Calculate checksum
-    let checksum: u8 = buffer.iter().fold(0u8, |acc, &x| acc.wrapping_add(x));
-    buffer.push(checksum);
+impl CanPort for Box<dyn serialport::SerialPort> {
+    fn write_all(&mut self, data: &[u8]) -> std::io::Result<()> {
+        std::io::Write::write_all(self, data)
+    }
 
-    buffer
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> std::io::Result<usize> {
+        self.set_timeout(timeout)?;
+        std::io::Read::read(self, buf)
+    }
 }
 
-/// Simulation handler for testing without hardware
-pub struct SimulationHandler {
-    connected: bool,
-    frame_counter: u32,
+/// In-memory `CanPort` for tests
+///
+/// Each queued chunk is returned by one `read` call (split if the buffer is
+/// smaller), so tests can control how frames are fragmented. Clones share
+/// the same queues.
+#[derive(Debug, Clone, Default)]
+pub struct MockPort {
+    incoming: Arc<Mutex<VecDeque<std::io::Result<Vec<u8>>>>>,
+    written: Arc<Mutex<Vec<u8>>>,
 }
 
-impl SimulationHandler {
+impl MockPort {
     pub fn new() -> Self {
-        SimulationHandler {
-            connected: false,
-            frame_counter: 0,
-        }
+        Self::default()
     }
 
-    pub fn connect(&mut self) -> Result<(), CanError> {
-        self.connected = true;
-        log::info!("Simulation mode connected");
-        Ok(())
+    /// Queue bytes to be returned by a later `read`
+    pub fn push_incoming(&self, bytes: &[u8]) {
+        self.incoming.lock().push_back(Ok(bytes.to_vec()));
     }
 
-    pub fn disconnect(&mut self) -> Result<(), CanError> {
-        self.connected = false;
-        log::info!("Simulation mode disconnected");
-        Ok(())
+    /// Queue an error to be returned by a later `read`, e.g. an unplugged device
+    pub fn push_read_error(&self, kind: std::io::ErrorKind) {
+        self.incoming.lock().push_back(Err(kind.into()));
     }
 
-    pub fn is_connected(&self) -> bool {
-        self.connected
+    /// Bytes written by the manager so far
+    pub fn written(&self) -> Vec<u8> {
+        self.written.lock().clone()
     }
+}
 
-    pub fn send_frame(&mut self, _frame: &CanFrame) -> Result<(), CanError> {
-        // Simulation mode ignores sent frames
+impl CanPort for MockPort {
+    fn write_all(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.written.lock().extend_from_slice(data);
         Ok(())
     }
 
-    pub fn receive_frame(&mut self, _timeout: Duration) -> Result<Option<CanFrame>, CanError> {
-        if !self.connected {
-            return Ok(None);
+    fn read(&mut self, buf: &mut [u8], _timeout: Duration) -> std::io::Result<usize> {
+        let mut incoming = self.incoming.lock();
+        let mut chunk = match incoming.pop_front() {
+            Some(chunk) => chunk?,
+            None => return Err(std::io::ErrorKind::TimedOut.into()),
+        };
+        let n = chunk.len().min(buf.len());
+        buf[..n].copy_from_slice(&chunk[..n]);
+        if n < chunk.len() {
+            incoming.push_front(Ok(chunk.split_off(n)));
         }
-
-        // Simulation mode: return test frame immediately without sleep
-        std::thread::sleep(Duration::from_millis(10)); // Minimal delay
-        Ok(Some(self.generate_test_frame()))
+        Ok(n)
     }
+}
 
-    fn generate_test_frame(&mut self) -> CanFrame {
-        self.frame_counter += 1;
-
-        // Cycle through different commands
-        let command = match self.frame_counter % 10 {
-            0 => 0x80u8, // Limits
-            1 => 0x81,   // SOC/SOH
-            2 => 0x82,   // Voltage/Current
-            3 => 0x83,   // Cell Voltage
-            4 => 0x84,   // Temperature
-            5 => 0x85,   // Operation Status
-            6 => 0x86,   // Accumulated Times
-            7 => 0x87,   // Accumulated Power
-            8 => 0x8F,   // Version
-            _ => 0xC0,   // Alarm
-        };
+/// One CAN adapter as seen by `CanManager`
+///
+/// The manager picks a backend from `adapter_type` once and afterwards only
+/// talks to the adapter through this trait. Frames passed to `send_frame`
+/// are already in the configured id format.
+pub trait CanBackend: Send {
+    fn connect(&mut self) -> Result<(), CanError>;
+    fn disconnect(&mut self) -> Result<(), CanError>;
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<(), CanError>;
+    /// Receive one frame, waiting at most `timeout`; `Ok(None)` if none arrived
+    fn receive_frame(&mut self, timeout: Duration) -> Result<Option<CanFrame>, CanError>;
+    fn is_connected(&self) -> bool;
 
-        let frame_id = ParsedFrameId {
-            ptp: true,
-            command,
-            destination_address: 0x80, // PCS
-            source_address: 0x01,      // BMS
-            cnt: false,
-        };
+    /// The simulation behind this backend, for the simulation-only controls
+    fn as_simulation(&mut self) -> Option<&mut SimulationHandler> {
+        None
+    }
 
-        let data = match command {
-            0x80 => vec![0x90, 0x21, 0xE8, 0x03, 0x40, 0x1A, 0xE8, 0x03], // 859.2V, 100A, 672V, 100A
-            0x81 => vec![0x50, 0x00, 0x64, 0x00, 0x3C, 0x00, 0x00, 0x00], // SOC 80%, SOH 100%, 60min
-            0x82 => vec![0xB9, 0x1F, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00], // 812.1V, 5.6A discharge
-            0x83 => vec![0x42, 0x0D, 0x01, 0x05, 0x38, 0x0D, 0x02, 0x08], // Max 3.394V, Min 3.384V
-            0x84 => vec![0x0E, 0x01, 0x01, 0x03, 0xF8, 0x00, 0x02, 0x05], // Max 27°C, Min 24.8°C
-            0x85 => vec![0x04, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00], // Discharging, Boot, Normal
-            0x86 => vec![0x64, 0x00, 0x62, 0x00, 0x00, 0x00, 0x00, 0x00], // 100 charge, 98 discharge
-            0x87 => vec![0xE0, 0x9F, 0x02, 0x00, 0xDE, 0xC9, 0x02, 0x00], // 17200 kWh, 18275 kWh
-            0x8F => vec![0x56, 0x32, 0x2E, 0x31, 0x39, 0x53, 0x00, 0x00], // V2.19S
-            0xC0 => vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // No alarms
-            _ => vec![0; 8],
-        };
+    /// Board info of a connected adapter that reports one
+    fn board_info(&self) -> Result<Option<crate::itekon_handler::BoardInfo>, CanError> {
+        Ok(None)
+    }
 
-        CanFrame {
-            id: frame_id.to_id(),
-            data,
-            timestamp: chrono::Utc::now().timestamp_millis(),
-        }
+    /// Connected backend for another channel of this adapter, sharing its
+    /// open device; adapters with a single channel refuse
+    fn open_channel(&mut self, channel: u32) -> Result<Box<dyn CanBackend>, CanError> {
+        Err(CanError::InvalidConfig(format!(
+            "adapter has no CAN channel {}",
+            channel
+        )))
     }
+
+    /// Add the link counters kept by the backend itself to `stats`
+    fn add_stats(&self, _stats: &mut CanStats) {}
 }
 
-impl Default for SimulationHandler {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Where `SerialBackend::connect` opens its port
+struct SerialSettings {
+    /// `None` picks the first Bluetooth port for I+BT
+    port_name: Option<String>,
+    baud_rate: u32,
+    open_timeout: Duration,
+    bluetooth: bool,
 }
 
-/// CAN Manager for handling communication
-pub struct CanManager {
-    simulation_handler: Option<SimulationHandler>,
-    serial_port: Option<Box<dyn serialport::SerialPort + Send>>,
-    itekon_handler: Option<crate::itekon_handler::ItekonHandler>,
-    config: CanConfig,
-    bms_data: Arc<Mutex<BmsData>>,
-    running: Arc<Mutex<bool>>,
-    connected: bool,
+/// Serial (I+ / I+BT) adapter exchanging I+ frames over a `CanPort`
+pub struct SerialBackend {
+    /// `None` for a transport opened by the caller
+    settings: Option<SerialSettings>,
+    port: Option<Box<dyn CanPort>>,
+    /// Bytes read from the port that don't form a full I+ frame yet
+    rx_buffer: Vec<u8>,
+    /// Scratch buffer for one read, `read_buffer_size` bytes
+    read_buffer: Vec<u8>,
+    read_buffer_size: usize,
+    checksum_errors: u64,
+    parse_failures: u64,
+    parse_log: LogLimiter,
 }
 
-impl CanManager {
-    pub fn new_with_mutex(config: CanConfig, bms_data: Arc<Mutex<BmsData>>) -> Self {
-        CanManager {
-            simulation_handler: if config.adapter_type == AdapterType::Simulation {
-                Some(SimulationHandler::new())
+impl SerialBackend {
+    /// Backend that opens the port configured for a USB-CAN or I+BT adapter
+    pub fn new(config: &CanConfig) -> Self {
+        let bluetooth = config.adapter_type == AdapterType::BluetoothCan;
+        let settings = SerialSettings {
+            port_name: config.serial_port.clone(),
+            baud_rate: if bluetooth {
+                config.bluetooth.baud_rate
             } else {
-                None
+                config.serial_baud_rate
             },
-            serial_port: None,
-            itekon_handler: None,
-            config,
-            bms_data,
-            running: Arc::new(Mutex::new(false)),
-            connected: false,
+            open_timeout: CanManager::adapter_timing(config).open_timeout,
+            bluetooth,
+        };
+        SerialBackend {
+            settings: Some(settings),
+            ..Self::with_port_opt(None, config.read_buffer_size)
         }
     }
 
-    pub fn connect(&mut self) -> Result<(), CanError> {
-        match self.config.adapter_type {
-            AdapterType::Simulation => {
-                if let Some(ref mut handler) = self.simulation_handler {
-                    handler.connect()?;
-                    self.connected = true;
-                }
-            }
-            AdapterType::UsbCan | AdapterType::BluetoothCan => {
-                let port_name = self.config.serial_port.as_ref().ok_or_else(|| {
-                    CanError::DeviceNotFound("No serial port specified".to_string())
-                })?;
-
-                let port = serialport::new(port_name, self.config.serial_baud_rate)
-                    .timeout(Duration::from_millis(1000))
-                    .open()
-                    .map_err(|e| CanError::SerialError(e.to_string()))?;
-
-                self.serial_port = Some(port);
-                self.connected = true;
-                log::info!("Connected to USB-CAN adapter on {}", port_name);
-            }
-            AdapterType::ItekonCan => {
-                let mut handler = crate::itekon_handler::ItekonHandler::new();
-                handler
-                    .connect()
-                    .map_err(|e| CanError::DeviceNotFound(e))?;
-                self.itekon_handler = Some(handler);
-                self.connected = true;
-                log::info!("Connected to iTEKON USBCAN adapter");
-            }
-            #[cfg(target_os = "linux")]
-            AdapterType::SocketCan => {
-                // TODO: Implement SocketCAN
-                self.simulation_handler = Some(SimulationHandler::new());
-                if let Some(ref mut handler) = self.simulation_handler {
-                    handler.connect()?;
-                    self.connected = true;
-                }
-            }
-        }
-        Ok(())
+    /// Backend over an already opened transport (e.g. a `MockPort`)
+    pub fn with_port(port: Box<dyn CanPort>, read_buffer_size: usize) -> Self {
+        Self::with_port_opt(Some(port), read_buffer_size)
     }
 
-    pub fn disconnect(&mut self) -> Result<(), CanError> {
-        *self.running.lock() = false;
-
-        if let Some(ref mut handler) = self.simulation_handler {
-            handler.disconnect()?;
+    fn with_port_opt(port: Option<Box<dyn CanPort>>, read_buffer_size: usize) -> Self {
+        SerialBackend {
+            settings: None,
+            port,
+            rx_buffer: Vec::new(),
+            read_buffer: Vec::new(),
+            read_buffer_size,
+            checksum_errors: 0,
+            parse_failures: 0,
+            parse_log: LogLimiter::new(LogLimiter::INTERVAL),
         }
-
-        if let Some(ref mut handler) = self.itekon_handler {
-            handler.disconnect().map_err(|e| CanError::IoError(e))?;
-        }
-
-        self.serial_port = None;
-        self.itekon_handler = None;
-        self.connected = false;
-        log::info!("Disconnected");
-        Ok(())
     }
 
-    pub fn is_connected(&self) -> bool {
-        self.connected
-    }
+    fn open(settings: &SerialSettings) -> Result<Box<dyn CanPort>, CanError> {
+        let port_name = match settings.port_name.clone() {
+            Some(port_name) => port_name,
+            // Fall back to the first RFCOMM/Bluetooth COM port if none was chosen
+            None if settings.bluetooth => CanManager::list_bluetooth_ports()
+                .into_iter()
+                .next()
+                .ok_or_else(|| {
+                    CanError::DeviceNotFound("No Bluetooth serial port found".to_string())
+                })?,
+            None => {
+                return Err(CanError::DeviceNotFound(
+                    "No serial port specified".to_string(),
+                ))
+            }
+        };
 
-    pub fn get_bms_data(&self) -> BmsData {
-        self.bms_data.lock().clone()
+        let port = serialport::new(&port_name, settings.baud_rate)
+            .timeout(settings.open_timeout)
+            .open()
+            .map_err(|e| CanError::SerialError(e.to_string()))?;
+        if settings.bluetooth {
+            log::info!("Connected to I+BT Bluetooth adapter on {}", port_name);
+        } else {
+            log::info!("Connected to USB-CAN adapter on {}", port_name);
+        }
+        Ok(Box::new(port))
     }
 
-    fn send_frame(&mut self, frame: &CanFrame) -> Result<(), CanError> {
-        match self.config.adapter_type {
-            AdapterType::Simulation => {
-                if let Some(ref mut handler) = self.simulation_handler {
-                    handler.send_frame(frame)?;
+    /// Pop the next complete I+ frame from the receive buffer
+    ///
+    /// Bytes before a 0xAA header are discarded; a frame with a bad checksum
+    /// is dropped whole, other malformed frames are skipped one byte at a time
+    /// to resynchronize.
+    fn take_buffered_frame(&mut self) -> Option<CanFrame> {
+        loop {
+            match self.rx_buffer.iter().position(|&b| b == 0xAA) {
+                Some(start) => {
+                    self.rx_buffer.drain(..start);
                 }
-            }
-            AdapterType::ItekonCan => {
-                if let Some(ref handler) = self.itekon_handler {
-                    handler
-                        .send_frame(frame)
-                        .map_err(|e| CanError::IoError(e))?;
+                None => {
+                    self.rx_buffer.clear();
+                    return None;
                 }
             }
-            _ => {
-                if let Some(ref mut port) = self.serial_port {
-                    let data = build_iplus_frame(frame);
-                    port.write_all(&data)
-                        .map_err(|e| CanError::SerialError(e.to_string()))?;
+            if self.rx_buffer.len() < 7 {
+                return None;
+            }
+
+            let len = self.rx_buffer[6] as usize;
+            if len > 8 {
+                self.parse_log.warn(
+                    "Dropped I+ frame with bad data length",
+                    format!("Dropped I+ frame with data length {}", len),
+                );
+                self.parse_failures += 1;
+                self.rx_buffer.drain(..1);
+                continue;
+            }
+            let total = 7 + len + 1;
+            if self.rx_buffer.len() < total {
+                return None;
+            }
+
+            match parse_iplus_frame(&self.rx_buffer[..total]) {
+                Ok(frame) => {
+                    self.rx_buffer.drain(..total);
+                    return Some(frame);
+                }
+                Err(IplusFrameError::ChecksumMismatch) => {
+                    self.checksum_errors += 1;
+                    self.rx_buffer.drain(..total);
+                }
+                Err(e) => {
+                    self.parse_log.warn(
+                        "Dropped malformed I+ frame",
+                        format!("Dropped malformed I+ frame: {:?}", e),
+                    );
+                    self.parse_failures += 1;
+                    self.rx_buffer.drain(..1);
                 }
             }
         }
+    }
+}
+
+impl CanBackend for SerialBackend {
+    fn connect(&mut self) -> Result<(), CanError> {
+        if let Some(ref settings) = self.settings {
+            self.port = Some(Self::open(settings)?);
+        } else if self.port.is_none() {
+            return Err(CanError::DeviceNotFound(
+                "Serial transport was closed".to_string(),
+            ));
+        }
+        self.rx_buffer.clear();
+        self.checksum_errors = 0;
+        self.parse_failures = 0;
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<(), CanError> {
+        self.port = None;
+        self.rx_buffer.clear();
         Ok(())
     }
 
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<(), CanError> {
+        let Some(ref mut port) = self.port else {
+            return Err(CanError::SerialError("Serial port is not open".to_string()));
+        };
+        port.write_all(&build_iplus_frame(frame))
+            .map_err(serial_error)
+    }
+
+    /// Read from the port until one I+ frame is complete or `timeout` passes
+    ///
+    /// Timeouts and interrupted reads count as nothing received; a vanished
+    /// device is `CanError::DeviceDisconnected`.
     fn receive_frame(&mut self, timeout: Duration) -> Result<Option<CanFrame>, CanError> {
-        match self.config.adapter_type {
-            AdapterType::Simulation => {
-                if let Some(ref mut handler) = self.simulation_handler {
-                    return handler.receive_frame(timeout);
-                }
+        self.parse_log.warn_suppressed();
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(frame) = self.take_buffered_frame() {
+                return Ok(Some(frame));
             }
-            AdapterType::ItekonCan => {
-                if let Some(ref handler) = self.itekon_handler {
-                    return handler
-                        .receive_frame(timeout)
-                        .map_err(|e| CanError::IoError(e));
-                }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
             }
-            _ => {
-                if let Some(ref mut port) = self.serial_port {
-                    port.set_timeout(timeout)
-                        .map_err(|e| CanError::SerialError(e.to_string()))?;
-
-                    let mut buffer = [0u8; 32];
-                    match port.read(&mut buffer) {
-                        Ok(n) if n > 0 => return Ok(parse_iplus_frame(&buffer[..n])),
-                        Ok(_) => return Ok(None),
-                        Err(ref e) if e.kind() == std::io::ErrorKind::TimedOut => return Ok(None),
-                        Err(e) => return Err(CanError::SerialError(e.to_string())),
-                    }
+            let Some(ref mut port) = self.port else {
+                return Ok(None);
+            };
+
+            self.read_buffer.resize(self.read_buffer_size, 0);
+            match port.read(&mut self.read_buffer, remaining) {
+                Ok(n) if n > 0 => self.rx_buffer.extend_from_slice(&self.read_buffer[..n]),
+                Ok(_) => return Ok(None),
+                Err(ref e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::TimedOut
+                            | std::io::ErrorKind::Interrupted
+                            | std::io::ErrorKind::WouldBlock
+                    ) =>
+                {
+                    return Ok(None)
                 }
+                Err(e) => return Err(serial_error(e)),
             }
         }
-        Ok(None)
     }
 
-    /// Query all BMS data
-    pub fn query_all_data(&mut self) -> Result<(), CanError> {
-        use crate::bms_parser::build_query_frame;
+    fn is_connected(&self) -> bool {
+        self.port.is_some()
+    }
 
-        let commands = [
-            BmsCommand::ChargeDischargeLimits,
-            BmsCommand::SocSoh,
-            BmsCommand::VoltageCurrent,
-            BmsCommand::CellVoltage,
-            BmsCommand::Temperature,
-            BmsCommand::OperationStatus,
-            BmsCommand::AccumulatedTimes,
-            BmsCommand::AccumulatedPower,
-            BmsCommand::SoftwareVersion,
-            BmsCommand::AlarmStatus,
-        ];
+    fn add_stats(&self, stats: &mut CanStats) {
+        stats.checksum_errors += self.checksum_errors;
+        stats.parse_failures += self.parse_failures;
+    }
+}
 
-        let is_simulation = self.config.adapter_type == AdapterType::Simulation;
-        let send_delay = if is_simulation {
-            Duration::from_millis(5)
-        } else {
-            Duration::from_millis(30) // Reduced from 50ms
-        };
+impl CanBackend for crate::itekon_handler::ItekonHandler {
+    fn connect(&mut self) -> Result<(), CanError> {
+        use crate::itekon_handler::ItekonHandler;
 
-        for cmd in commands {
-            let frame = build_query_frame(cmd, self.config.host_address, self.config.bms_address);
-            self.send_frame(&frame)?;
-            std::thread::sleep(send_delay);
+        // Reconnecting reopens the device instead of leaking the open handle
+        if ItekonHandler::is_connected(self) {
+            ItekonHandler::disconnect(self).map_err(CanError::IoError)?;
         }
+        ItekonHandler::connect(self).map_err(CanError::DeviceNotFound)?;
+        log::info!("Connected to iTEKON USBCAN adapter");
+        Ok(())
+    }
 
-        // Receive responses with appropriate timeout
-        let receive_timeout = if is_simulation {
-            Duration::from_millis(10)
-        } else {
-            Duration::from_millis(50) // Reduced from 100ms
-        };
+    fn disconnect(&mut self) -> Result<(), CanError> {
+        crate::itekon_handler::ItekonHandler::disconnect(self).map_err(CanError::IoError)
+    }
 
-        // Update timestamp
-        {
-            let mut data = self.bms_data.lock();
-            data.timestamp = chrono::Utc::now().timestamp_millis();
-            data.connected = true;
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<(), CanError> {
+        crate::itekon_handler::ItekonHandler::send_frame(self, frame).map_err(CanError::IoError)
+    }
+
+    fn receive_frame(&mut self, timeout: Duration) -> Result<Option<CanFrame>, CanError> {
+        crate::itekon_handler::ItekonHandler::receive_frame(self, timeout)
+            .map_err(CanError::IoError)
+    }
+
+    fn is_connected(&self) -> bool {
+        crate::itekon_handler::ItekonHandler::is_connected(self)
+    }
+
+    fn board_info(&self) -> Result<Option<crate::itekon_handler::BoardInfo>, CanError> {
+        if !CanBackend::is_connected(self) {
+            return Ok(None);
         }
+        let board = self.get_board_info().map_err(CanError::DeviceNotFound)?;
+        Ok(Some(board.decode()))
+    }
 
-        for _ in 0..10 {
-            if let Ok(Some(frame)) = self.receive_frame(receive_timeout) {
-                let mut data = self.bms_data.lock();
-                crate::bms_parser::parse_can_frame(&frame, &mut data);
+    fn open_channel(&mut self, channel: u32) -> Result<Box<dyn CanBackend>, CanError> {
+        let handler = crate::itekon_handler::ItekonHandler::open_channel(self, channel)
+            .map_err(CanError::DeviceNotFound)?;
+        Ok(Box::new(handler))
+    }
+
+    fn add_stats(&self, stats: &mut CanStats) {
+        stats.dropped_frames += self.dropped_frames();
+    }
+}
+
+/// Build I+ Series frame format
+pub fn build_iplus_frame(frame: &CanFrame) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(16);
+
+    buffer.push(0xAA); // Header
+    buffer.push(match frame.format {
+        FrameFormat::Standard => 0x00,
+        FrameFormat::Extended => 0x01,
+    });
+    buffer.extend_from_slice(&frame.id.to_le_bytes());
+    buffer.push(frame.data.len() as u8);
+    buffer.extend_from_slice(&frame.data);
+
+    buffer.push(iplus_checksum(&buffer));
+
+    buffer
+}
+
+/// Scripted simulation scenario
+///
+/// One step is a full cycle of the 10 simulated commands. Values are derived
+/// from the step number, so the same scenario always replays identically.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SimulationScenario {
+    /// SOC at step 0 in %
+    pub initial_soc: f32,
+    /// SOC change per step in % (negative drains)
+    pub soc_step: f32,
+    /// Maximum temperature at step 0 in °C
+    pub initial_temperature: f32,
+    /// Temperature change per step in °C
+    pub temperature_step: f32,
+    /// Alarm bits raised from a given step onwards: (step, bit)
+    pub alarm_events: Vec<(u32, u8)>,
+}
+
+impl Default for SimulationScenario {
+    fn default() -> Self {
+        SimulationScenario {
+            initial_soc: 80.0,
+            soc_step: 0.0,
+            initial_temperature: 27.0,
+            temperature_step: 0.0,
+            alarm_events: Vec::new(),
+        }
+    }
+}
+
+impl SimulationScenario {
+    /// SOC in % at the given step
+    pub fn soc_at(&self, step: u32) -> u16 {
+        (self.initial_soc + self.soc_step * step as f32)
+            .clamp(0.0, 100.0)
+            .round() as u16
+    }
+
+    /// Maximum temperature in °C at the given step
+    pub fn temperature_at(&self, step: u32) -> f32 {
+        self.initial_temperature + self.temperature_step * step as f32
+    }
+
+    /// Raw alarm status at the given step
+    pub fn alarms_at(&self, step: u32) -> u64 {
+        self.alarm_events
+            .iter()
+            .filter(|(at, bit)| *at <= step && *bit < 64)
+            .fold(0u64, |acc, (_, bit)| acc | (1u64 << bit))
+    }
+
+    /// Override the payload of a simulated frame for the given step
+    fn apply(&self, command: u8, step: u32, data: &mut [u8]) {
+        match command {
+            0x81 => data[0..2].copy_from_slice(&self.soc_at(step).to_le_bytes()),
+            0x84 => {
+                let max = (self.temperature_at(step) * 10.0).round() as i16;
+                // Keep the default 2.2°C spread between max and min
+                let min = max - 22;
+                data[0..2].copy_from_slice(&max.to_le_bytes());
+                data[4..6].copy_from_slice(&min.to_le_bytes());
+            }
+            0xC0 => data[0..8].copy_from_slice(&self.alarms_at(step).to_le_bytes()),
+            _ => {}
+        }
+    }
+}
+
+/// Alarm bits periodically raised by the default simulation cycle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedAlarmPattern {
+    /// Alarm bits to raise
+    pub bits: Vec<u8>,
+    /// Raise the bits during every Nth command cycle (0 disables)
+    pub every_n_cycles: u32,
+}
+
+impl Default for SimulatedAlarmPattern {
+    fn default() -> Self {
+        SimulatedAlarmPattern {
+            // CellOverVoltage (severity 3) + ChargingOverTempAlarm (severity 2)
+            bits: vec![0, 2],
+            every_n_cycles: 5,
+        }
+    }
+}
+
+impl SimulatedAlarmPattern {
+    /// Raw alarm status for the given command cycle
+    pub fn alarms_at(&self, cycle: u32) -> u64 {
+        if self.every_n_cycles == 0 || cycle % self.every_n_cycles != self.every_n_cycles - 1 {
+            return 0;
+        }
+        self.bits
+            .iter()
+            .filter(|&&bit| bit < 64)
+            .fold(0u64, |acc, &bit| acc | (1u64 << bit))
+    }
+}
+
+/// Simulated time between two frames; one scenario step is 10 frames
+pub const SIMULATED_FRAME_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Simulation handler for testing without hardware
+pub struct SimulationHandler {
+    connected: bool,
+    frame_counter: u32,
+    /// Simulated seconds per wall-clock second (0 = don't sleep at all)
+    time_scale: f32,
+    /// Queried commands awaiting a response
+    pending: VecDeque<u8>,
+    scenario: Option<SimulationScenario>,
+    alarm_pattern: SimulatedAlarmPattern,
+    /// Alarm bits set in every 0xC0 frame until cleared, for UI testing
+    injected_alarms: u64,
+    /// Address the simulated BMS answers from
+    source_address: u8,
+}
+
+impl SimulationHandler {
+    pub fn new() -> Self {
+        SimulationHandler {
+            connected: false,
+            frame_counter: 0,
+            time_scale: 1.0,
+            pending: VecDeque::new(),
+            scenario: None,
+            alarm_pattern: SimulatedAlarmPattern::default(),
+            injected_alarms: 0,
+            source_address: 0x01,
+        }
+    }
+
+    /// Create a handler that plays back a scripted scenario
+    pub fn with_scenario(scenario: SimulationScenario) -> Self {
+        SimulationHandler {
+            scenario: Some(scenario),
+            ..Self::new()
+        }
+    }
+
+    /// Replace the active scenario and restart it from step 0
+    pub fn set_scenario(&mut self, scenario: Option<SimulationScenario>) {
+        self.scenario = scenario;
+        self.frame_counter = 0;
+    }
+
+    /// Speed up (> 1) or slow down (< 1) the simulation
+    ///
+    /// Scenario steps advance per frame; the scale only sets how long each
+    /// frame takes in wall-clock time. At 0 frames come back without sleeping.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.time_scale = scale.max(0.0);
+    }
+
+    /// Simulated time since the scenario started
+    pub fn simulated_time(&self) -> Duration {
+        SIMULATED_FRAME_INTERVAL * self.frame_counter
+    }
+
+    /// Set which alarms the default cycle raises and how often
+    pub fn set_alarm_pattern(&mut self, pattern: SimulatedAlarmPattern) {
+        self.alarm_pattern = pattern;
+    }
+
+    /// Raise `bit` in all following 0xC0 frames, on top of the pattern or scenario
+    pub fn inject_alarm(&mut self, bit: u8) {
+        if bit < 64 {
+            self.injected_alarms |= 1u64 << bit;
+        }
+    }
+
+    /// Answer from `address` instead of 0x01, e.g. as a second simulated rack
+    pub fn set_source_address(&mut self, address: u8) {
+        self.source_address = address;
+    }
+
+    /// Drop all alarms raised with `inject_alarm`
+    pub fn clear_injected_alarms(&mut self) {
+        self.injected_alarms = 0;
+    }
+
+    fn generate_test_frame(&mut self) -> CanFrame {
+        // Cycle through different commands
+        let command = match (self.frame_counter + 1) % 10 {
+            0 => 0x80u8, // Limits
+            1 => 0x81,   // SOC/SOH
+            2 => 0x82,   // Voltage/Current
+            3 => 0x83,   // Cell Voltage
+            4 => 0x84,   // Temperature
+            5 => 0x85,   // Operation Status
+            6 => 0x86,   // Accumulated Times
+            7 => 0x87,   // Accumulated Power
+            8 => 0x8F,   // Version
+            _ => 0xC0,   // Alarm
+        };
+        self.generate_frame(command)
+    }
+
+    /// Build the simulated response to `command`
+    fn generate_frame(&mut self, command: u8) -> CanFrame {
+        self.frame_counter += 1;
+
+        let frame_id = ParsedFrameId {
+            ptp: true,
+            command,
+            destination_address: 0x80, // PCS
+            source_address: self.source_address,
+            cnt: false,
+        };
+
+        let mut data = BmsCommand::try_from(command)
+            .map(simulated_payload)
+            .unwrap_or_else(|_| vec![0; 8]);
+
+        let cycle = (self.frame_counter - 1) / 10;
+        match self.scenario {
+            Some(ref scenario) => scenario.apply(command, cycle, &mut data),
+            None if command == 0xC0 => {
+                data.copy_from_slice(&self.alarm_pattern.alarms_at(cycle).to_le_bytes())
             }
+            None => {}
+        }
+        if command == 0xC0 && self.injected_alarms != 0 {
+            let raw = u64::from_le_bytes(data[..8].try_into().unwrap()) | self.injected_alarms;
+            data.copy_from_slice(&raw.to_le_bytes());
+        }
+
+        CanFrame {
+            id: frame_id.to_id(),
+            format: FrameFormat::Extended,
+            data,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            hw_timestamp: None,
         }
+    }
+}
+
+/// Default simulated payload for `command`, before any scenario is applied
+fn simulated_payload(command: BmsCommand) -> Vec<u8> {
+    use crate::bms_parser::*;
+
+    let order = Endianness::Little;
+    let data = match command {
+        BmsCommand::ChargeDischargeLimits => encode_charge_discharge_limits(
+            &ChargeDischargeLimits {
+                charge_voltage_limit: 859.2,
+                charge_current_limit: 100.0,
+                discharge_voltage_limit: 672.0,
+                discharge_current_limit: 100.0,
+            },
+            order,
+        ),
+        BmsCommand::SocSoh => encode_soc_soh(
+            &SocSohData {
+                soc: 80,
+                soh: 100,
+                backup_time_minutes: 60,
+            },
+            order,
+        ),
+        BmsCommand::VoltageCurrent => encode_voltage_current(
+            &VoltageCurrentData {
+                voltage: 812.1,
+                current: 5.6, // Discharging
+                power: 0.0,   // Derived on parse
+                power_w: 0.0,
+                raw_current: 5.6,
+            },
+            order,
+        ),
+        BmsCommand::CellVoltage => encode_cell_voltage(
+            &CellVoltageData {
+                max_voltage: 3.394,
+                max_voltage_pack_no: 1,
+                max_voltage_cell_no: 5,
+                min_voltage: 3.384,
+                min_voltage_pack_no: 2,
+                min_voltage_cell_no: 8,
+                voltage_delta: 0.0, // Derived on parse
+            },
+            order,
+        ),
+        BmsCommand::Temperature => encode_temperature(
+            &TemperatureData {
+                max_temperature: 27.0,
+                max_temp_pack_no: 1,
+                max_temp_sensor_no: 3,
+                min_temperature: 24.8,
+                min_temp_pack_no: 2,
+                min_temp_sensor_no: 5,
+                temp_delta: 0.0, // Derived on parse
+            },
+            order,
+        ),
+        BmsCommand::OperationStatus => encode_operation_status(&OperationStatusData {
+            system_status: SystemStatus::Discharge,
+            work_status: WorkStatus::Boot,
+            operation_status: OperationStatusCode::Normal,
+            discharge_prohibited: false,
+            charge_prohibited: false,
+            discharge_prohibited_hard: false,
+            prohibition_flags: 0,
+        }),
+        BmsCommand::AccumulatedTimes => encode_accumulated_times(
+            &AccumulatedTimesData {
+                charge_times: 100,
+                discharge_times: 98,
+            },
+            order,
+        ),
+        BmsCommand::AccumulatedPower => encode_accumulated_power(
+            // 17200kWh, 18275kWh
+            &AccumulatedPowerData::from_raw(172_000, 182_750),
+            order,
+        ),
+        BmsCommand::SoftwareVersion => encode_software_version("V2.19S"),
+        // Set by the alarm pattern or scenario
+        _ => [0; 8],
+    };
+    data.to_vec()
+}
+
+impl Default for SimulationHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CanBackend for SimulationHandler {
+    fn connect(&mut self) -> Result<(), CanError> {
+        self.connected = true;
+        log::info!("Simulation mode connected");
+        Ok(())
+    }
 
+    fn disconnect(&mut self) -> Result<(), CanError> {
+        self.connected = false;
+        log::info!("Simulation mode disconnected");
         Ok(())
     }
 
-    /// Start continuous data reception
-    pub fn start_receiving(&mut self) -> Result<(), CanError> {
-        *self.running.lock() = true;
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
 
-        loop {
-            if !*self.running.lock() {
-                break;
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<(), CanError> {
+        // Queue a response for queried commands; anything else is ignored
+        let command = ParsedFrameId::from_frame(frame).command;
+        if ALL_QUERY_COMMANDS.iter().any(|&c| c as u8 == command) {
+            self.pending.push_back(command);
+        }
+        Ok(())
+    }
+
+    fn receive_frame(&mut self, _timeout: Duration) -> Result<Option<CanFrame>, CanError> {
+        if !self.connected {
+            return Ok(None);
+        }
+
+        if self.time_scale > 0.0 {
+            std::thread::sleep(SIMULATED_FRAME_INTERVAL.div_f32(self.time_scale));
+        }
+        match self.pending.pop_front() {
+            Some(command) => Ok(Some(self.generate_frame(command))),
+            None => Ok(Some(self.generate_test_frame())),
+        }
+    }
+
+    fn as_simulation(&mut self) -> Option<&mut SimulationHandler> {
+        Some(self)
+    }
+}
+
+/// Link statistics, reset on every connect
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CanStats {
+    /// Frames successfully handed to the adapter
+    pub frames_sent: u64,
+    /// Frames received from the adapter
+    pub frames_received: u64,
+    /// Received bytes/frames that could not be decoded
+    pub parse_failures: u64,
+    /// I+ frames dropped because of a checksum mismatch
+    pub checksum_errors: u64,
+    /// Queried commands left unanswered when their response deadline expired
+    pub timeouts: u64,
+    /// Frames skipped because they came from another BMS address
+    pub address_mismatches: u64,
+    /// Frames the iTEKON adapter discarded on receive-buffer overflows
+    #[serde(default)]
+    pub dropped_frames: u64,
+    /// Repeated frames dropped by the `dedup_window_ms` filter
+    #[serde(default)]
+    pub duplicate_frames: u64,
+}
+
+/// Response time of one command, see `CanManager::command_latencies`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandLatency {
+    pub command: BmsCommand,
+    /// Time from the end of the cycle's sends to the response in ms, `None`
+    /// if none arrived
+    ///
+    /// Queries are sent back to back before any reply is read, so all
+    /// commands are timed from the last send; a reply that arrived during
+    /// the sends reads close to 0.
+    pub latency_ms: Option<f64>,
+}
+
+/// Round-trip times of `CanManager::ping`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PingStats {
+    /// Version queries sent
+    pub sent: u32,
+    /// Queries that timed out without a response
+    pub timeouts: u32,
+    /// Round-trip time of each answered query in ms
+    pub latencies_ms: Vec<f64>,
+    pub min_ms: Option<f64>,
+    pub avg_ms: Option<f64>,
+    pub max_ms: Option<f64>,
+}
+
+impl PingStats {
+    /// Add one try; `None` is a timeout
+    pub fn record(&mut self, latency: Option<Duration>) {
+        self.sent += 1;
+        let Some(latency) = latency else {
+            self.timeouts += 1;
+            return;
+        };
+        let ms = latency.as_secs_f64() * 1000.0;
+        self.latencies_ms.push(ms);
+        self.min_ms = Some(self.min_ms.map_or(ms, |min| min.min(ms)));
+        self.max_ms = Some(self.max_ms.map_or(ms, |max| max.max(ms)));
+        self.avg_ms = Some(self.latencies_ms.iter().sum::<f64>() / self.latencies_ms.len() as f64);
+    }
+}
+
+/// Approximate bits on the wire for an extended frame with `data_len` bytes
+///
+/// SOF, 29-bit ID, control, CRC, ACK, EOF and interframe space add 67 bits;
+/// bit stuffing is not counted.
+pub fn extended_frame_bits(data_len: usize) -> u32 {
+    67 + 8 * data_len.min(8) as u32
+}
+
+/// Bus load over the meter window
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BusLoad {
+    /// Received frames per second
+    pub frames_per_sec: f32,
+    /// Sent and received bits per second
+    pub bits_per_sec: f32,
+    /// `bits_per_sec` as a percentage of the CAN baud rate
+    pub utilization_percent: f32,
+}
+
+/// Sliding-window frame rate and bus utilization meter
+#[derive(Debug)]
+pub struct BusLoadMeter {
+    window: Duration,
+    started: Instant,
+    /// (time, bits, received) per frame inside the window
+    frames: VecDeque<(Instant, u32, bool)>,
+}
+
+impl BusLoadMeter {
+    /// Default window of `CanManager`'s meter
+    pub const WINDOW: Duration = Duration::from_secs(5);
+
+    pub fn new(window: Duration, now: Instant) -> Self {
+        BusLoadMeter {
+            window,
+            started: now,
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// Record a sent or received frame with `data_len` data bytes
+    pub fn record(&mut self, now: Instant, data_len: usize, received: bool) {
+        self.frames
+            .push_back((now, extended_frame_bits(data_len), received));
+        self.expire(now);
+    }
+
+    /// Load over the window ending at `now` (or since start if shorter)
+    pub fn load(&mut self, now: Instant, baud_rate: u32) -> BusLoad {
+        self.expire(now);
+        let span = now.duration_since(self.started).min(self.window);
+        let secs = span.as_secs_f32();
+        if secs <= 0.0 {
+            return BusLoad::default();
+        }
+
+        let received = self.frames.iter().filter(|f| f.2).count();
+        let bits: u32 = self.frames.iter().map(|f| f.1).sum();
+        let bits_per_sec = bits as f32 / secs;
+        BusLoad {
+            frames_per_sec: received as f32 / secs,
+            bits_per_sec,
+            utilization_percent: bits_per_sec / baud_rate.max(1) as f32 * 100.0,
+        }
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while self
+            .frames
+            .front()
+            .is_some_and(|f| now.duration_since(f.0) > self.window)
+        {
+            self.frames.pop_front();
+        }
+    }
+}
+
+/// Drops frames repeated with the same id and data within a short window
+///
+/// A reply to a query sent again is never a duplicate, however unchanged;
+/// `forget_command` clears the command's entries when its query goes out.
+#[derive(Debug)]
+pub struct FrameDeduplicator {
+    window: Duration,
+    /// Command, data and receive time of the last frame per id
+    recent: HashMap<u32, (u8, Vec<u8>, Instant)>,
+}
+
+impl FrameDeduplicator {
+    /// Ids tracked before expired ones are dropped
+    const MAX_ENTRIES: usize = 256;
+
+    pub fn new(window: Duration) -> Self {
+        FrameDeduplicator {
+            window,
+            recent: HashMap::new(),
+        }
+    }
+
+    /// Whether `frame` received at `now` repeats one seen within the window
+    pub fn is_duplicate(&mut self, frame: &CanFrame, now: Instant) -> bool {
+        if let Some((_, data, at)) = self.recent.get(&frame.id) {
+            if *data == frame.data && now.duration_since(*at) < self.window {
+                return true;
+            }
+        }
+
+        if self.recent.len() >= Self::MAX_ENTRIES {
+            let window = self.window;
+            self.recent
+                .retain(|_, (_, _, at)| now.duration_since(*at) < window);
+        }
+        let command = ParsedFrameId::from_frame(frame).command;
+        self.recent
+            .insert(frame.id, (command, frame.data.clone(), now));
+        false
+    }
+
+    /// Forget the frames seen for `command`, e.g. because it was queried again
+    pub fn forget_command(&mut self, command: u8) {
+        self.recent.retain(|_, (seen, _, _)| *seen != command);
+    }
+}
+
+/// Collapses repeated log messages of one kind, e.g. during a bad-baud fault storm
+///
+/// Messages are grouped by a fixed `kind`, so a storm of frames with varying
+/// ids still collapses. The first message of a kind is logged; repeats within
+/// `interval` are counted and reported by `flush` once the interval has
+/// passed, or with the next message of the kind.
+#[derive(Debug)]
+pub struct LogLimiter {
+    interval: Duration,
+    /// Last logged time and suppressed repeats per kind
+    entries: HashMap<String, (Instant, u64)>,
+}
+
+impl LogLimiter {
+    /// Default interval of `CanManager`'s parse-failure log
+    pub const INTERVAL: Duration = Duration::from_secs(1);
+    /// Kinds tracked before the least recently logged one is dropped
+    const MAX_ENTRIES: usize = 64;
+
+    pub fn new(interval: Duration) -> Self {
+        LogLimiter {
+            interval,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Line to log for `message` of `kind` at `now`, or `None` if it is suppressed
+    pub fn check(&mut self, kind: &str, message: &str, now: Instant) -> Option<String> {
+        if let Some((last, suppressed)) = self.entries.get_mut(kind) {
+            if now.duration_since(*last) < self.interval {
+                *suppressed += 1;
+                return None;
             }
+            *last = now;
+            return Some(match std::mem::take(suppressed) {
+                0 => message.to_string(),
+                n => format!("{} ({} similar suppressed)", message, n),
+            });
+        }
 
-            if let Ok(Some(frame)) = self.receive_frame(Duration::from_millis(100)) {
-                let mut data = self.bms_data.lock();
-                crate::bms_parser::parse_can_frame(&frame, &mut data);
+        if self.entries.len() >= Self::MAX_ENTRIES {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (last, _))| *last)
+                .map(|(kind, _)| kind.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
             }
         }
+        self.entries.insert(kind.to_string(), (now, 0));
+        Some(message.to_string())
+    }
 
-        Ok(())
+    /// Summary lines for kinds with repeats suppressed over a full interval
+    ///
+    /// Kinds with nothing left to report are forgotten once their interval
+    /// has passed.
+    pub fn flush(&mut self, now: Instant) -> Vec<String> {
+        let interval = self.interval;
+        let mut lines = Vec::new();
+        self.entries.retain(|kind, (last, suppressed)| {
+            if now.duration_since(*last) < interval {
+                return true;
+            }
+            if *suppressed > 0 {
+                lines.push(format!("{}: {} similar suppressed", kind, suppressed));
+            }
+            false
+        });
+        lines
     }
 
-    /// Get available serial ports
-    pub fn list_serial_ports() -> Vec<String> {
-        serialport::available_ports()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|p| p.port_name)
-            .collect()
+    /// Log `message` of `kind` as a warning unless it is suppressed
+    pub fn warn(&mut self, kind: &str, message: String) {
+        if let Some(line) = self.check(kind, &message, Instant::now()) {
+            log::warn!("{}", line);
+        }
+    }
+
+    /// Log the suppressed counts that are due, see `flush`
+    pub fn warn_suppressed(&mut self) {
+        for line in self.flush(Instant::now()) {
+            log::warn!("{}", line);
+        }
+    }
+}
+
+/// Callback invoked with the BMS address and its updated data after each
+/// received frame is parsed; extra channels report their own address
+pub type DataUpdateHook = Arc<dyn Fn(u8, &BmsData) + Send + Sync>;
+
+/// Callback invoked with each raw received frame before parsing
+pub type FrameHook = Arc<dyn Fn(&CanFrame) + Send + Sync>;
+
+/// Adapter description for `get_adapter_info`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterInfo {
+    pub adapter_type: AdapterType,
+    /// Serial port of the I+ adapters
+    pub serial_port: Option<String>,
+    pub connected: bool,
+    /// Board info read from a connected iTEKON adapter
+    pub board: Option<crate::itekon_handler::BoardInfo>,
+}
+
+/// Adapters usable on this machine, for `list_adapters`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdapterAvailability {
+    /// Serial ports for the I+ USB-CAN adapter
+    pub serial_ports: Vec<String>,
+    /// Serial ports that look like Bluetooth links, for the I+BT adapter
+    pub bluetooth_ports: Vec<String>,
+    /// CAN network interfaces (Linux only)
+    pub socket_can_interfaces: Vec<String>,
+    /// Whether at least one SocketCAN interface exists
+    pub socket_can_available: bool,
+    /// Located iTEKON VCI library (Windows only)
+    pub itekon_library: Option<String>,
+    /// Whether the iTEKON library could be located
+    pub itekon_available: bool,
+    /// Simulation needs no hardware and is always available
+    pub simulation_available: bool,
+}
+
+impl AdapterAvailability {
+    /// Build from the detected ports, interfaces and library
+    pub fn new(
+        serial_ports: Vec<String>,
+        bluetooth_ports: Vec<String>,
+        socket_can_interfaces: Vec<String>,
+        itekon_library: Option<std::path::PathBuf>,
+    ) -> Self {
+        AdapterAvailability {
+            serial_ports,
+            bluetooth_ports,
+            socket_can_available: !socket_can_interfaces.is_empty(),
+            socket_can_interfaces,
+            itekon_available: itekon_library.is_some(),
+            itekon_library: itekon_library.map(|path| path.display().to_string()),
+            simulation_available: true,
+        }
+    }
+}
+
+/// `ARPHRD_CAN` in `/sys/class/net/<if>/type`
+const ARPHRD_CAN: &str = "280";
+
+/// Names of the CAN interfaces listed in a sysfs `class/net` directory
+pub fn socket_can_interfaces_in(sys_class_net: &std::path::Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(sys_class_net) else {
+        return Vec::new();
+    };
+    let mut interfaces: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            std::fs::read_to_string(entry.path().join("type"))
+                .is_ok_and(|kind| kind.trim() == ARPHRD_CAN)
+        })
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    interfaces.sort();
+    interfaces
+}
+
+/// CAN network interfaces of this machine; always empty off Linux
+pub fn socket_can_interfaces() -> Vec<String> {
+    if cfg!(target_os = "linux") {
+        socket_can_interfaces_in(std::path::Path::new("/sys/class/net"))
+    } else {
+        Vec::new()
+    }
+}
+
+impl AdapterInfo {
+    /// Descriptor from the config alone, for adapters without board info
+    pub fn from_config(config: &CanConfig, connected: bool) -> Self {
+        AdapterInfo {
+            adapter_type: config.adapter_type,
+            serial_port: config.serial_port.clone(),
+            connected,
+            board: None,
+        }
+    }
+}
+
+/// The single adapter-owning manager, shared by queries and the receive loop
+pub type SharedManager = Arc<Mutex<Option<CanManager>>>;
+
+/// How long the receive loop holds the manager per receive
+pub const RECEIVE_LOOP_TIMEOUT: Duration = Duration::from_millis(50);
+
+// `SharedManager` is moved into worker threads
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<CanManager>();
+};
+
+/// Run the receive loop for `manager` on a dedicated thread
+///
+/// The caller keeps `running` and stops the loop by clearing it; the loop
+/// also ends once the manager is taken out. The manager is locked for one
+/// short receive at a time so queries can interleave. `on_receive` gets
+/// the receive result and the adapter type, outside the lock. A fatal error
+/// (see `CanError::is_fatal`) ends the loop after it was passed on, as
+/// further reads can't succeed until the app reconnects.
+pub fn spawn_receive_loop(
+    manager: SharedManager,
+    running: Arc<Mutex<bool>>,
+    mut on_receive: impl FnMut(Result<bool, CanError>, AdapterType) + Send + 'static,
+) -> std::io::Result<std::thread::JoinHandle<()>> {
+    std::thread::Builder::new()
+        .name("can-receive".to_string())
+        .spawn(move || {
+            while *running.lock() {
+                let mut guard = manager.lock();
+                let Some(manager) = guard.as_mut() else {
+                    break;
+                };
+                let result = manager.receive_once(RECEIVE_LOOP_TIMEOUT);
+                let adapter_type = manager.adapter_type();
+                drop(guard);
+
+                let fatal = result.as_ref().is_err_and(|e| e.is_fatal());
+                if let Err(ref e) = result {
+                    if fatal {
+                        log::warn!("Receive loop stopped: {}", e);
+                    }
+                }
+                on_receive(result, adapter_type);
+                if fatal {
+                    break;
+                }
+            }
+            *running.lock() = false;
+        })
+}
+
+/// Frames buffered between the stream's reader thread and its consumer
+pub const FRAME_STREAM_CAPACITY: usize = 256;
+
+/// Received frames of `manager` as an async stream, for embedding in async code
+///
+/// A reader thread locks the manager for one short receive at a time, like
+/// `spawn_receive_loop`, so the blocking API keeps working alongside; the
+/// frames are still parsed into the shared data. The stream ends once the
+/// manager is taken out or a fatal error occurs, and the reader stops when
+/// the stream is dropped. While the consumer lags, up to
+/// `FRAME_STREAM_CAPACITY` frames are buffered before the reader waits.
+pub fn frame_stream(
+    manager: SharedManager,
+) -> std::io::Result<tokio_stream::wrappers::ReceiverStream<CanFrame>> {
+    let (sender, receiver) = tokio::sync::mpsc::channel(FRAME_STREAM_CAPACITY);
+    std::thread::Builder::new()
+        .name("can-frame-stream".to_string())
+        .spawn(move || {
+            while !sender.is_closed() {
+                let mut guard = manager.lock();
+                let Some(manager) = guard.as_mut() else {
+                    break;
+                };
+                let result = manager.receive_next(RECEIVE_LOOP_TIMEOUT);
+                drop(guard);
+
+                match result {
+                    Ok(Some(frame)) => {
+                        if sender.blocking_send(frame).is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) if e.is_fatal() => {
+                        log::warn!("Frame stream stopped: {}", e);
+                        break;
+                    }
+                    Err(e) => log::debug!("Frame stream receive failed: {}", e),
+                }
+            }
+        })?;
+    Ok(tokio_stream::wrappers::ReceiverStream::new(receiver))
+}
+
+/// Backend of an extra channel and the BMS it polls
+struct Channel {
+    backend: Box<dyn CanBackend>,
+    bms_address: u8,
+}
+
+/// CAN Manager for handling communication
+///
+/// Threading: a manager is `Send` but not shared by itself. Exactly one
+/// manager owns the adapter and lives in a `SharedManager`; every query and
+/// the receive loop lock it for one operation at a time, so a query never
+/// sees frames consumed by the loop. The manager can be connected on one
+/// thread and used on another. Parsed data is published through the
+/// `bms_data` mutex, which is only held while a single frame is applied.
+pub struct CanManager {
+    /// Adapter picked from `adapter_type`, or the one given to `connect_backend`
+    backend: Box<dyn CanBackend>,
+    /// Backends of `extra_channels`, opened on connect
+    channels: Vec<Channel>,
+    /// Data of the BMSes on `channels`, by address
+    channel_data: BTreeMap<u8, BmsData>,
+    /// Channel `receive_once` reads next (0 = primary)
+    next_channel: usize,
+    config: CanConfig,
+    bms_data: Arc<Mutex<BmsData>>,
+    update_hook: Option<DataUpdateHook>,
+    frame_hook: Option<FrameHook>,
+    stats: CanStats,
+    bus_load: BusLoadMeter,
+    clock: FrameClock,
+    /// Source addresses seen on frames that didn't match `bms_address`
+    detected_addresses: BTreeSet<u8>,
+    poll_cycle: u32,
+    /// `query_all_data` calls since connect, for the static data cadence
+    all_data_cycle: u32,
+    parse_log: LogLimiter,
+    /// Response time per command from the last query that included it
+    latencies: HashMap<BmsCommand, Option<Duration>>,
+    /// Duplicate filter, set on connect when `dedup_window_ms` is configured
+    dedup: Option<FrameDeduplicator>,
+    /// Frozen data detection per BMS address
+    freeze: BTreeMap<u8, FreezeDetector>,
+    /// Set from another thread to abort a running query, cleared on connect
+    cancel: Arc<AtomicBool>,
+}
+
+impl CanManager {
+    pub fn new_with_mutex(config: CanConfig, bms_data: Arc<Mutex<BmsData>>) -> Self {
+        let clock = FrameClock::new(config.timestamp_source);
+        CanManager {
+            backend: Self::backend_for(&config),
+            channels: Vec::new(),
+            channel_data: BTreeMap::new(),
+            next_channel: 0,
+            config,
+            bms_data,
+            update_hook: None,
+            frame_hook: None,
+            stats: CanStats::default(),
+            bus_load: BusLoadMeter::new(BusLoadMeter::WINDOW, Instant::now()),
+            clock,
+            detected_addresses: BTreeSet::new(),
+            poll_cycle: 0,
+            all_data_cycle: 0,
+            parse_log: LogLimiter::new(LogLimiter::INTERVAL),
+            latencies: HashMap::new(),
+            dedup: None,
+            freeze: BTreeMap::new(),
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Backend for the configured adapter type
+    fn backend_for(config: &CanConfig) -> Box<dyn CanBackend> {
+        match config.adapter_type {
+            AdapterType::Simulation => Box::new(SimulationHandler::new()),
+            AdapterType::UsbCan | AdapterType::BluetoothCan => Box::new(SerialBackend::new(config)),
+            AdapterType::ItekonCan => Box::new(crate::itekon_handler::ItekonHandler::new()),
+            // TODO: Implement SocketCAN
+            #[cfg(target_os = "linux")]
+            AdapterType::SocketCan => Box::new(SimulationHandler::new()),
+        }
+    }
+
+    /// Connected backend for an extra channel; `validate` only allows iTEKON
+    /// and simulation
+    ///
+    /// iTEKON channels are started on the device the primary backend opened,
+    /// as opening it again would take the device over.
+    fn backend_for_channel(
+        &mut self,
+        extra: &ExtraChannel,
+    ) -> Result<Box<dyn CanBackend>, CanError> {
+        match self.config.adapter_type {
+            AdapterType::ItekonCan => self.backend.open_channel(extra.channel),
+            _ => {
+                let mut handler = SimulationHandler::new();
+                handler.set_source_address(extra.bms_address);
+                handler.connect()?;
+                Ok(Box::new(handler))
+            }
+        }
+    }
+
+    /// Set the simulation scenario (no-op for hardware adapters)
+    pub fn set_simulation_scenario(&mut self, scenario: Option<SimulationScenario>) {
+        if let Some(handler) = self.backend.as_simulation() {
+            handler.set_scenario(scenario);
+        }
+    }
+
+    /// Set the simulation time scale, see `SimulationHandler::set_time_scale`
+    pub fn set_simulation_time_scale(&mut self, scale: f32) -> Result<(), CanError> {
+        if !scale.is_finite() || scale < 0.0 {
+            return Err(CanError::InvalidConfig(format!(
+                "time scale must be 0 or positive, got {}",
+                scale
+            )));
+        }
+        for channel in &mut self.channels {
+            if let Some(handler) = channel.backend.as_simulation() {
+                handler.set_time_scale(scale);
+            }
+        }
+        match self.backend.as_simulation() {
+            Some(handler) => {
+                handler.set_time_scale(scale);
+                Ok(())
+            }
+            None => Err(CanError::InvalidConfig(
+                "Simulation is not active".to_string(),
+            )),
+        }
+    }
+
+    /// Raise a documented alarm bit in the simulated 0xC0 frames until cleared
+    pub fn inject_simulated_alarm(&mut self, bit: u8) -> Result<(), CanError> {
+        if alarm_info(bit).is_none() {
+            return Err(CanError::InvalidConfig(format!(
+                "alarm bit {} is not defined",
+                bit
+            )));
+        }
+        match self.backend.as_simulation() {
+            Some(handler) => {
+                handler.inject_alarm(bit);
+                Ok(())
+            }
+            None => Err(CanError::InvalidConfig(
+                "Simulation is not active".to_string(),
+            )),
+        }
+    }
+
+    /// Clear the alarms raised with `inject_simulated_alarm`
+    pub fn clear_simulated_alarms(&mut self) -> Result<(), CanError> {
+        match self.backend.as_simulation() {
+            Some(handler) => {
+                handler.clear_injected_alarms();
+                Ok(())
+            }
+            None => Err(CanError::InvalidConfig(
+                "Simulation is not active".to_string(),
+            )),
+        }
+    }
+
+    /// Set a callback to run after every parsed frame (e.g. telemetry publishing)
+    pub fn set_update_hook(&mut self, hook: DataUpdateHook) {
+        self.update_hook = Some(hook);
+    }
+
+    /// Set a callback to run for every received frame (e.g. capture to file)
+    pub fn set_frame_hook(&mut self, hook: FrameHook) {
+        self.frame_hook = Some(hook);
+    }
+
+    /// Flag that aborts a running query with `CanError::Cancelled` when set
+    ///
+    /// Queries hold the manager for a whole cycle, so this is the only way to
+    /// stop one early; it's checked between sends and receives.
+    pub fn cancel_flag(&self) -> Arc<AtomicBool> {
+        self.cancel.clone()
+    }
+
+    /// Use a flag owned by the caller, so it can be set without the manager
+    pub fn set_cancel_flag(&mut self, flag: Arc<AtomicBool>) {
+        self.cancel = flag;
+    }
+
+    fn check_cancelled(&self) -> Result<(), CanError> {
+        if self.cancel.load(Ordering::SeqCst) {
+            return Err(CanError::Cancelled);
+        }
+        Ok(())
+    }
+
+    pub fn connect(&mut self) -> Result<(), CanError> {
+        let _span = tracing::info_span!(
+            "connect",
+            adapter = ?self.config.adapter_type,
+            bms_address = self.config.bms_address,
+        )
+        .entered();
+        self.config.validate()?;
+        self.reset_link_state();
+        self.backend.connect()?;
+
+        // Don't leave the primary or earlier channels open on a failed channel
+        if let Err(e) = self.connect_channels() {
+            if let Err(close) = self.disconnect() {
+                log::warn!("Closing after a failed channel connect failed: {}", close);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Open the backends of `extra_channels`
+    fn connect_channels(&mut self) -> Result<(), CanError> {
+        for extra in self.config.extra_channels.clone() {
+            let backend = self.backend_for_channel(&extra)?;
+            self.channel_data
+                .insert(extra.bms_address, BmsData::default());
+            self.channels.push(Channel {
+                backend,
+                bms_address: extra.bms_address,
+            });
+        }
+        Ok(())
+    }
+
+    /// Connect through an already opened serial transport (e.g. a `MockPort`)
+    ///
+    /// Frames are exchanged in the I+ format regardless of `adapter_type`.
+    pub fn connect_port(&mut self, port: Box<dyn CanPort>) -> Result<(), CanError> {
+        let backend = SerialBackend::with_port(port, self.config.read_buffer_size);
+        self.connect_backend(Box::new(backend))
+    }
+
+    /// Connect through a caller-provided backend instead of the configured adapter
+    ///
+    /// The backend replaces the current one for good, also across reconnects.
+    pub fn connect_backend(&mut self, mut backend: Box<dyn CanBackend>) -> Result<(), CanError> {
+        self.config.validate()?;
+        self.reset_link_state();
+        backend.connect()?;
+        self.backend = backend;
+        Ok(())
+    }
+
+    fn reset_link_state(&mut self) {
+        self.stats = CanStats::default();
+        self.bus_load = BusLoadMeter::new(BusLoadMeter::WINDOW, Instant::now());
+        self.clock = FrameClock::new(self.config.timestamp_source);
+        self.detected_addresses.clear();
+        self.latencies.clear();
+        self.all_data_cycle = 0;
+        self.channels.clear();
+        self.channel_data.clear();
+        self.next_channel = 0;
+        self.cancel.store(false, Ordering::SeqCst);
+        self.freeze.clear();
+        self.dedup = self
+            .config
+            .dedup_window_ms
+            .map(|ms| FrameDeduplicator::new(Duration::from_millis(ms)));
+    }
+
+    pub fn disconnect(&mut self) -> Result<(), CanError> {
+        self.backend.disconnect()?;
+        for mut channel in self.channels.drain(..) {
+            channel.backend.disconnect()?;
+        }
+        log::info!("Disconnected");
+        Ok(())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.backend.is_connected()
+    }
+
+    pub fn adapter_type(&self) -> AdapterType {
+        self.config.adapter_type
+    }
+
+    /// Describe the adapter, reading the board info of a connected iTEKON
+    pub fn adapter_info(&self) -> Result<AdapterInfo, CanError> {
+        let mut info = AdapterInfo::from_config(&self.config, self.is_connected());
+        info.board = self.backend.board_info()?;
+        Ok(info)
+    }
+
+    /// Link statistics since the last connect
+    pub fn stats(&self) -> CanStats {
+        let mut stats = self.stats.clone();
+        self.backend.add_stats(&mut stats);
+        for channel in &self.channels {
+            channel.backend.add_stats(&mut stats);
+        }
+        stats
+    }
+
+    /// Response times from the last query of each command, in protocol order
+    pub fn command_latencies(&self) -> Vec<CommandLatency> {
+        ALL_QUERY_COMMANDS
+            .iter()
+            .filter_map(|&command| {
+                let latency = self.latencies.get(&command)?;
+                Some(CommandLatency {
+                    command,
+                    latency_ms: latency.map(|d| d.as_secs_f64() * 1000.0),
+                })
+            })
+            .collect()
+    }
+
+    /// Wall-clock time (ms) of the last connect, the reference for monotonic stamps
+    pub fn connected_at_ms(&self) -> i64 {
+        self.clock.connected_at_ms()
+    }
+
+    /// Received frame rate and bus utilization over the last few seconds
+    pub fn bus_load(&mut self) -> BusLoad {
+        self.bus_load
+            .load(Instant::now(), self.config.can_baud_rate)
+    }
+
+    /// Other BMS addresses that answered since the last connect
+    pub fn detected_addresses(&self) -> Vec<u8> {
+        self.detected_addresses.iter().copied().collect()
+    }
+
+    pub fn get_bms_data(&self) -> BmsData {
+        self.bms_data.lock().clone()
+    }
+
+    /// Data of every polled BMS by address, the primary one included
+    pub fn all_bms_data(&self) -> BTreeMap<u8, BmsData> {
+        let mut all = self.channel_data.clone();
+        all.insert(self.config.bms_address, self.get_bms_data());
+        all
+    }
+
+    /// Backend of `channel`: 0 is the primary adapter, then `extra_channels` in order
+    fn channel_backend(&mut self, channel: usize) -> &mut dyn CanBackend {
+        match channel {
+            0 => &mut *self.backend,
+            n => &mut *self.channels[n - 1].backend,
+        }
+    }
+
+    /// BMS address polled on `channel`
+    fn channel_address(&self, channel: usize) -> u8 {
+        match channel {
+            0 => self.config.bms_address,
+            n => self.channels[n - 1].bms_address,
+        }
+    }
+
+    fn send_frame(&mut self, frame: &CanFrame) -> Result<(), CanError> {
+        self.send_frame_on(0, frame)
+    }
+
+    fn send_frame_on(&mut self, channel: usize, frame: &CanFrame) -> Result<(), CanError> {
+        // Frames are built extended; rewrite them for standard-id gateways
+        let standard;
+        let frame = match (self.config.frame_format, frame.format) {
+            (FrameFormat::Standard, FrameFormat::Extended) => {
+                standard = frame.to_standard();
+                &standard
+            }
+            _ => frame,
+        };
+
+        if let Some(dedup) = self.dedup.as_mut() {
+            dedup.forget_command(ParsedFrameId::from_frame(frame).command);
+        }
+        self.channel_backend(channel).send_frame(frame)?;
+        self.stats.frames_sent += 1;
+        self.bus_load
+            .record(Instant::now(), frame.data.len(), false);
+        Ok(())
+    }
+
+    fn receive_frame(&mut self, timeout: Duration) -> Result<Option<CanFrame>, CanError> {
+        self.receive_frame_on(0, timeout)
+    }
+
+    fn receive_frame_on(
+        &mut self,
+        channel: usize,
+        timeout: Duration,
+    ) -> Result<Option<CanFrame>, CanError> {
+        self.parse_log.warn_suppressed();
+        let mut frame = self.channel_backend(channel).receive_frame(timeout)?;
+        if let (Some(received), Some(dedup)) = (frame.as_ref(), self.dedup.as_mut()) {
+            if dedup.is_duplicate(received, Instant::now()) {
+                log::trace!("Dropping duplicate frame 0x{:08X}", received.id);
+                self.stats.duplicate_frames += 1;
+                return Ok(None);
+            }
+        }
+        if let Some(ref mut frame) = frame {
+            self.stats.frames_received += 1;
+            self.bus_load.record(Instant::now(), frame.data.len(), true);
+            if self.config.timestamp_source == TimestampSource::Monotonic {
+                frame.timestamp = self.clock.now_ms();
+            }
+        }
+        Ok(frame)
+    }
+
+    /// Send a caller-built frame, e.g. for protocol debugging
+    ///
+    /// The simulation doesn't react to arbitrary frames, so there the frame is
+    /// only logged. Returns the frame that was built.
+    pub fn send_raw_frame(&mut self, id: u32, data: Vec<u8>) -> Result<CanFrame, CanError> {
+        if data.len() > 8 {
+            return Err(CanError::InvalidFrame(format!(
+                "data length {} exceeds 8 bytes",
+                data.len()
+            )));
+        }
+        let (mask, bits) = match self.config.frame_format {
+            FrameFormat::Standard => (CAN_STD_ID_MASK, 11),
+            FrameFormat::Extended => (CAN_EXT_ID_MASK, 29),
+        };
+        if id & !mask != 0 {
+            return Err(CanError::InvalidFrame(format!(
+                "id 0x{:08X} exceeds {} bits",
+                id, bits
+            )));
+        }
+
+        let frame = CanFrame {
+            id,
+            format: self.config.frame_format,
+            data,
+            timestamp: self.clock.now_ms(),
+            hw_timestamp: None,
+        };
+        if self.backend.as_simulation().is_some() {
+            log::info!("Simulation: not sending raw frame {:?}", frame);
+        } else {
+            self.send_frame(&frame)?;
+        }
+        Ok(frame)
+    }
+
+    /// Write charge/discharge limits to the BMS as a 0x80 frame
+    ///
+    /// Only some deployments accept host-set limits. In simulation the frame
+    /// is only logged. Returns the frame that was built.
+    pub fn set_charge_discharge_limits(
+        &mut self,
+        limits: &ChargeDischargeLimits,
+    ) -> Result<CanFrame, CanError> {
+        use crate::bms_parser::{build_write_frame, encode_charge_discharge_limits};
+
+        let fields = [
+            (
+                "charge voltage",
+                limits.charge_voltage_limit,
+                MAX_VOLTAGE_LIMIT,
+            ),
+            (
+                "charge current",
+                limits.charge_current_limit,
+                MAX_CURRENT_LIMIT,
+            ),
+            (
+                "discharge voltage",
+                limits.discharge_voltage_limit,
+                MAX_VOLTAGE_LIMIT,
+            ),
+            (
+                "discharge current",
+                limits.discharge_current_limit,
+                MAX_CURRENT_LIMIT,
+            ),
+        ];
+        for (name, value, max) in fields {
+            if !(0.0..=max).contains(&value) {
+                return Err(CanError::InvalidFrame(format!(
+                    "{} limit {} is outside 0..={}",
+                    name, value, max
+                )));
+            }
+        }
+
+        let mut frame = build_write_frame(
+            BmsCommand::ChargeDischargeLimits,
+            &encode_charge_discharge_limits(limits, self.config.endianness),
+            self.config.host_address,
+            self.config.bms_address,
+        );
+        frame.timestamp = self.clock.now_ms();
+        if self.backend.as_simulation().is_some() {
+            log::info!("Simulation: not writing limits {:?}", limits);
+        } else {
+            self.send_frame(&frame)?;
+        }
+        Ok(frame)
+    }
+
+    /// Query all BMS data, returning the commands that didn't respond
+    ///
+    /// `STATIC_COMMANDS` are only included every `static_query_every` calls
+    /// (starting with the first after connect) or while their data is
+    /// missing; `refresh_static_data` fetches them on demand.
+    pub fn query_all_data(&mut self) -> Result<Vec<BmsCommand>, CanError> {
+        let _span = tracing::info_span!("query_all_data").entered();
+        let due = self
+            .all_data_cycle
+            .is_multiple_of(self.config.static_query_every.max(1));
+        self.all_data_cycle = self.all_data_cycle.wrapping_add(1);
+
+        let commands: Vec<BmsCommand> = {
+            let data = self.bms_data.lock();
+            ALL_QUERY_COMMANDS
+                .iter()
+                .copied()
+                .filter(|c| due || !STATIC_COMMANDS.contains(c) || data.command_data(*c).is_none())
+                .collect()
+        };
+        self.query_commands(&commands)
+    }
+
+    /// Query the static commands now instead of waiting for their cadence
+    pub fn refresh_static_data(&mut self) -> Result<Vec<BmsCommand>, CanError> {
+        self.query_commands(&STATIC_COMMANDS)
+    }
+
+    /// Query the commands due in the next cycle of the configured plan
+    pub fn poll(&mut self) -> Result<Vec<BmsCommand>, CanError> {
+        let commands = self.config.query_plan.commands_for_cycle(self.poll_cycle);
+        self.poll_cycle = self.poll_cycle.wrapping_add(1);
+        self.query_commands(&commands)
+    }
+
+    /// Replace the polling plan and restart it from cycle 0
+    pub fn set_query_plan(&mut self, plan: QueryPlan) {
+        self.config.query_plan = plan;
+        self.poll_cycle = 0;
+    }
+
+    /// Query one command and wait for its response
+    ///
+    /// Only a reply received after the send and decoded without error counts;
+    /// continuation frames and frames that fail to parse are skipped, as are
+    /// receive errors other than a vanished device. Unrelated frames received
+    /// meanwhile are still processed. Gives up with `CanError::Timeout` after
+    /// `SINGLE_QUERY_ATTEMPTS` reads.
+    pub fn query_single(&mut self, command: BmsCommand) -> Result<CommandData, CanError> {
+        use crate::bms_parser::build_query_frame;
+
+        if !ALL_QUERY_COMMANDS.contains(&command) {
+            return Err(CanError::InvalidFrame(format!(
+                "0x{:02X} is not a query command",
+                command as u8
+            )));
+        }
+
+        let timing = self.link_timing();
+        let frame = build_query_frame(command, self.config.host_address, self.config.bms_address);
+        self.check_cancelled()?;
+        self.send_frame(&frame)?;
+
+        for _ in 0..SINGLE_QUERY_ATTEMPTS {
+            self.check_cancelled()?;
+            let frame = match self.receive_frame(timing.receive_timeout) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => continue,
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(e) => {
+                    log::debug!("Receive failed: {}", e);
+                    continue;
+                }
+            };
+            let decoded = self.process_frame(&frame);
+
+            let id = ParsedFrameId::from_frame(&frame);
+            if decoded
+                && !id.cnt
+                && id.command == command as u8
+                && id.source_address == self.config.bms_address
+            {
+                if let Some(data) = self.bms_data.lock().command_data(command) {
+                    return Ok(data);
+                }
+            }
+        }
+
+        self.stats.timeouts += 1;
+        Err(CanError::Timeout)
+    }
+
+    /// Time `count` software version queries from send to response
+    ///
+    /// Each try is a `query_single`, so a try that times out is counted in
+    /// `PingStats::timeouts` instead of failing the whole measurement. The
+    /// simulation answers after its simulated frame delay.
+    pub fn ping(&mut self, count: u32) -> Result<PingStats, CanError> {
+        if !PING_COUNT_RANGE.contains(&count) {
+            return Err(CanError::InvalidConfig(format!(
+                "ping count must be between {} and {}, got {}",
+                PING_COUNT_RANGE.start(),
+                PING_COUNT_RANGE.end(),
+                count
+            )));
+        }
+
+        let timing = self.link_timing();
+        let mut stats = PingStats::default();
+        for i in 0..count {
+            if i > 0 {
+                std::thread::sleep(timing.send_delay);
+            }
+            let started = Instant::now();
+            match self.query_single(BmsCommand::SoftwareVersion) {
+                Ok(_) => stats.record(Some(started.elapsed())),
+                Err(CanError::Timeout) => stats.record(None),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Query only the given commands
+    ///
+    /// Fails with `CanError::NoResponse` if the BMS sent no frame at all
+    /// (echoes of our own queries and other nodes' traffic don't count), so a
+    /// silent BMS can be told apart from one with a few missing answers.
+    /// Extra channels are queried afterwards; their failures are only logged
+    /// (unless fatal) and the returned commands are those missing on the
+    /// primary channel.
+    pub fn query_commands(&mut self, commands: &[BmsCommand]) -> Result<Vec<BmsCommand>, CanError> {
+        let missing = self.query_channel(0, commands)?;
+        for channel in 1..=self.channels.len() {
+            let address = self.channel_address(channel);
+            match self.query_channel(channel, commands) {
+                Err(e) if e.is_fatal() || matches!(e, CanError::Cancelled) => return Err(e),
+                Ok(m) if !m.is_empty() => {
+                    log::debug!("No response from BMS 0x{:02X} to {:?}", address, m)
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Query of BMS 0x{:02X} failed: {}", address, e),
+            }
+        }
+        Ok(missing)
+    }
+
+    /// One query cycle on `channel`, see `query_commands`
+    fn query_channel(
+        &mut self,
+        channel: usize,
+        commands: &[BmsCommand],
+    ) -> Result<Vec<BmsCommand>, CanError> {
+        use crate::bms_parser::build_query_frame;
+
+        let address = self.channel_address(channel);
+        let _span = tracing::info_span!(
+            "query",
+            adapter = ?self.config.adapter_type,
+            bms_address = address,
+            commands = commands.len(),
+        )
+        .entered();
+        let timing = self.link_timing();
+
+        for &cmd in commands {
+            self.check_cancelled()?;
+            let frame = build_query_frame(cmd, self.config.host_address, address);
+            self.send_frame_on(channel, &frame)?;
+            std::thread::sleep(timing.send_delay);
+        }
+        // Replies are only read from here on, so every command's latency
+        // counts from the same instant
+        let sent_at = Instant::now();
+
+        // Update timestamp
+        let now = chrono::Utc::now().timestamp_millis();
+        if channel == 0 {
+            let mut data = self.bms_data.lock();
+            data.timestamp = now;
+            data.connected = true;
+        } else if let Some(data) = self.channel_data.get_mut(&address) {
+            data.timestamp = now;
+            data.connected = true;
+        }
+
+        // Read until every command has answered or the budget of one receive
+        // timeout per command is used up
+        let mut outstanding: Vec<BmsCommand> = commands.to_vec();
+        if channel == 0 {
+            for &cmd in commands {
+                self.latencies.insert(cmd, None);
+            }
+        }
+        let deadline = Instant::now() + timing.receive_timeout * commands.len() as u32;
+        let mut frames = 0;
+        while !outstanding.is_empty() {
+            self.check_cancelled()?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            // A failed read only loses this attempt, but a vanished device
+            // must reach the caller instead of being retried until the deadline
+            let frame = match self.receive_frame_on(channel, remaining.min(timing.receive_timeout))
+            {
+                Ok(Some(frame)) => frame,
+                Ok(None) => continue,
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(e) => {
+                    log::debug!("Receive failed: {}", e);
+                    continue;
+                }
+            };
+            self.process_frame(&frame);
+
+            // Echoed queries come from the host, so this also skips them
+            let id = ParsedFrameId::from_frame(&frame);
+            if id.source_address != address {
+                continue;
+            }
+            frames += 1;
+            let before = outstanding.len();
+            outstanding.retain(|&c| c as u8 != id.command);
+            if channel == 0 && outstanding.len() < before {
+                if let Some(&command) = commands.iter().find(|&&c| c as u8 == id.command) {
+                    self.latencies.insert(command, Some(sent_at.elapsed()));
+                }
+            }
+        }
+
+        self.stats.timeouts += outstanding.len() as u64;
+        if frames == 0 && !commands.is_empty() {
+            return Err(CanError::NoResponse);
+        }
+        if !outstanding.is_empty() {
+            log::debug!("No response to {:?}", outstanding);
+        }
+        Ok(outstanding)
+    }
+
+    /// Open/send/receive timing for the configured adapter, with the
+    /// `send_delay_ms`/`receive_timeout_ms` overrides applied
+    pub fn link_timing(&self) -> LinkTiming {
+        let mut timing = Self::adapter_timing(&self.config);
+        if let Some(ms) = self.config.send_delay_ms {
+            timing.send_delay = Duration::from_millis(ms);
+        }
+        if let Some(ms) = self.config.receive_timeout_ms {
+            timing.receive_timeout = Duration::from_millis(ms);
+        }
+        timing
+    }
+
+    /// Default timing for the adapter type
+    fn adapter_timing(config: &CanConfig) -> LinkTiming {
+        match config.adapter_type {
+            AdapterType::Simulation => LinkTiming {
+                open_timeout: Duration::from_millis(1000),
+                send_delay: Duration::from_millis(5),
+                receive_timeout: Duration::from_millis(10),
+            },
+            AdapterType::BluetoothCan => LinkTiming {
+                open_timeout: Duration::from_millis(config.bluetooth.open_timeout_ms),
+                send_delay: Duration::from_millis(config.bluetooth.send_delay_ms),
+                receive_timeout: Duration::from_millis(config.bluetooth.receive_timeout_ms),
+            },
+            _ => LinkTiming {
+                open_timeout: Duration::from_millis(1000),
+                send_delay: Duration::from_millis(30), // Reduced from 50ms
+                receive_timeout: Duration::from_millis(50), // Reduced from 100ms
+            },
+        }
+    }
+
+    /// Receive and process at most one frame; returns whether a frame arrived
+    ///
+    /// With extra channels each call reads the next channel in turn.
+    pub fn receive_once(&mut self, timeout: Duration) -> Result<bool, CanError> {
+        Ok(self.receive_next(timeout)?.is_some())
+    }
+
+    /// Like `receive_once`, but hands back the processed frame
+    pub fn receive_next(&mut self, timeout: Duration) -> Result<Option<CanFrame>, CanError> {
+        let channel = self.next_channel % (self.channels.len() + 1);
+        self.next_channel = channel + 1;
+        let frame = self.receive_frame_on(channel, timeout)?;
+        if let Some(ref frame) = frame {
+            self.process_frame(frame);
+        }
+        Ok(frame)
+    }
+
+    /// Parse a received frame into the shared data and notify the update hook
+    ///
+    /// Returns true if the frame was decoded into the data of a polled BMS.
+    fn process_frame(&mut self, frame: &CanFrame) -> bool {
+        if let Some(ref hook) = self.frame_hook {
+            hook(frame);
+        }
+
+        // Our own queries carry zero data and would overwrite good values
+        if crate::bms_parser::is_own_frame(frame, self.config.host_address)
+            || crate::bms_parser::is_query_echo(frame, self.config.bms_address)
+            || self
+                .channels
+                .iter()
+                .any(|c| crate::bms_parser::is_query_echo(frame, c.bms_address))
+        {
+            log::trace!("Ignoring echoed query frame 0x{:08X}", frame.id);
+            return false;
+        }
+
+        let parsed = ParsedFrameId::from_frame(frame);
+        let _span = tracing::debug_span!(
+            "parse_frame",
+            command = parsed.command,
+            source = parsed.source_address,
+        )
+        .entered();
+        let source = parsed.source_address;
+        // Taken out of the map for the update so `apply_frame` can borrow self
+        if let Some(mut data) = self.channel_data.remove(&source) {
+            let decoded = self.apply_frame(frame, &parsed, &mut data);
+            if let Some(ref hook) = self.update_hook {
+                hook(source, &data);
+            }
+            self.channel_data.insert(source, data);
+            return decoded;
+        }
+        if source != self.config.bms_address {
+            if self.detected_addresses.insert(source) {
+                log::warn!(
+                    "Frame from BMS address 0x{:02X}, expected 0x{:02X}",
+                    source,
+                    self.config.bms_address
+                );
+            }
+            self.stats.address_mismatches += 1;
+            return false;
+        }
+
+        let bms_data = self.bms_data.clone();
+        let mut data = bms_data.lock();
+        let decoded = self.apply_frame(frame, &parsed, &mut data);
+        // The hook publishes and emits events; run it on a copy so a slow
+        // consumer doesn't block readers of the shared data
+        let updated = self.update_hook.is_some().then(|| data.clone());
+        drop(data);
+        if let (Some(hook), Some(data)) = (&self.update_hook, updated) {
+            hook(source, &data);
+        }
+        decoded
+    }
+
+    /// Decode `frame` into `data` and run the checks every BMS gets: current
+    /// convention, firmware version, battery layout and freeze detection
+    ///
+    /// Returns false if the frame couldn't be decoded.
+    fn apply_frame(
+        &mut self,
+        frame: &CanFrame,
+        parsed: &ParsedFrameId,
+        data: &mut BmsData,
+    ) -> bool {
+        let source = parsed.source_address;
+        let decoded = crate::bms_parser::parse_can_frame(frame, data, self.config.endianness);
+        if !decoded {
+            self.parse_log.warn(
+                "Failed to parse frame",
+                format!(
+                    "Failed to parse frame 0x{:08X} with {} data bytes",
+                    frame.id,
+                    frame.data.len()
+                ),
+            );
+            self.stats.parse_failures += 1;
+        }
+        if let Some(vc) = data.voltage_current.as_mut() {
+            self.config.current_convention.apply(vc);
+        }
+        Self::check_firmware(self.config.min_firmware_version, source, data);
+        Self::check_layout(self.config.battery_layout, source, data);
+        let config = self.config.freeze_detection;
+        let freeze = self
+            .freeze
+            .entry(source)
+            .or_insert_with(|| FreezeDetector::new(config));
+        Self::check_frozen(freeze, parsed.command, source, data);
+        decoded
+    }
+
+    /// Flag firmware older than `min`; operation continues, with a warning
+    fn check_firmware(min: FirmwareVersion, source: u8, data: &mut BmsData) {
+        if min.apply(data) {
+            log::warn!(
+                "BMS 0x{:02X} firmware {} is older than {}; fields may be decoded incorrectly",
+                source,
+                data.software_version.as_deref().unwrap_or("?"),
+                min
+            );
+        }
+    }
+
+    /// Flag reported indices outside the configured layout
+    fn check_layout(layout: BatteryLayout, source: u8, data: &mut BmsData) {
+        for error in layout.apply(data) {
+            log::warn!("BMS 0x{:02X}: {}", source, error);
+        }
+    }
+
+    /// Update `possibly_frozen` after an 0x82 frame. SOC from 0x81 is part of
+    /// the compared values, but only 0x82 counts so each query cycle is one update.
+    fn check_frozen(detector: &mut FreezeDetector, command: u8, source: u8, data: &mut BmsData) {
+        if command != BmsCommand::VoltageCurrent as u8 {
+            return;
+        }
+        let was_frozen = detector.is_frozen();
+        data.possibly_frozen = detector.update(data);
+        if data.possibly_frozen && !was_frozen {
+            log::warn!(
+                "BMS 0x{:02X}: SOC, voltage and current haven't changed; data may be frozen",
+                source
+            );
+        } else if was_frozen && !data.possibly_frozen {
+            log::info!("BMS 0x{:02X}: data is changing again", source);
+        }
+    }
+
+    /// Get available serial ports
+    pub fn list_serial_ports() -> Vec<String> {
+        serialport::available_ports()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| p.port_name)
+            .collect()
+    }
+
+    /// Detect which adapters can be used on this machine
+    pub fn list_adapters() -> AdapterAvailability {
+        AdapterAvailability::new(
+            Self::list_serial_ports(),
+            Self::list_bluetooth_ports(),
+            socket_can_interfaces(),
+            crate::itekon_handler::locate_library(),
+        )
+    }
+
+    /// Get serial ports that look like Bluetooth SPP links (RFCOMM / BT COM ports)
+    pub fn list_bluetooth_ports() -> Vec<String> {
+        serialport::available_ports()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| {
+                matches!(p.port_type, serialport::SerialPortType::BluetoothPort)
+                    || is_bluetooth_port_name(&p.port_name)
+            })
+            .map(|p| p.port_name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bms_parser::{build_query_frame, parse_can_frame};
+
+    #[test]
+    fn test_scenario_drives_soc_down() {
+        let scenario = SimulationScenario {
+            initial_soc: 80.0,
+            soc_step: -1.0,
+            alarm_events: vec![(1, 0)],
+            ..Default::default()
+        };
+        let mut handler = SimulationHandler::with_scenario(scenario);
+        handler.connect().unwrap();
+
+        let mut data = BmsData::default();
+        let mut socs = Vec::new();
+        let mut alarms = Vec::new();
+        for _ in 0..20 {
+            let frame = handler.generate_test_frame();
+            parse_can_frame(&frame, &mut data, Endianness::Little);
+            match ParsedFrameId::from_frame(&frame).command {
+                0x81 => socs.push(data.soc_soh.as_ref().unwrap().soc),
+                0xC0 => alarms.push(data.alarm_status.as_ref().unwrap().raw_status),
+                _ => {}
+            }
+        }
+
+        assert_eq!(socs, vec![80, 79]);
+        assert_eq!(alarms, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_command_latencies_recorded() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+        manager.connect().unwrap();
+        assert!(manager.command_latencies().is_empty());
+
+        manager
+            .query_commands(&[BmsCommand::SocSoh, BmsCommand::VoltageCurrent])
+            .unwrap();
+        let latencies = manager.command_latencies();
+        assert_eq!(latencies.len(), 2);
+        assert_eq!(latencies[0].command, BmsCommand::SocSoh);
+        assert!(latencies
+            .iter()
+            .all(|l| l.latency_ms.is_some_and(|ms| ms > 0.0)));
+
+        // A BMS at another address never answers
+        manager.config.bms_address = 0x02;
+        assert!(matches!(
+            manager.query_commands(&[BmsCommand::SocSoh]),
+            Err(CanError::NoResponse)
+        ));
+        let latencies = manager.command_latencies();
+        assert_eq!(latencies[0].latency_ms, None);
+        assert!(latencies[1].latency_ms.is_some());
+    }
+
+    #[test]
+    fn test_inject_simulated_epo_alarm() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect().unwrap();
+        assert!(manager.inject_simulated_alarm(17).is_err());
+
+        manager.inject_simulated_alarm(31).unwrap();
+        manager.query_commands(&[BmsCommand::AlarmStatus]).unwrap();
+        let status = bms_data.lock().alarm_status.clone().unwrap();
+        assert!(status.active_alarms.contains(&31));
+        assert_eq!(status.max_severity, 3);
+
+        manager.clear_simulated_alarms().unwrap();
+        manager.query_commands(&[BmsCommand::AlarmStatus]).unwrap();
+        let status = bms_data.lock().alarm_status.clone().unwrap();
+        assert!(!status.active_alarms.contains(&31));
+
+        // Rejected for hardware adapters
+        let (mut hardware, _) = mock_manager(&MockPort::new());
+        assert!(hardware.inject_simulated_alarm(31).is_err());
+        assert!(hardware.clear_simulated_alarms().is_err());
+    }
+
+    #[test]
+    fn test_simulation_raises_periodic_alarms() {
+        let mut handler = SimulationHandler::new();
+        let mut data = BmsData::default();
+        let mut alarm_frames = 0;
+        for _ in 0..50 {
+            let frame = handler.generate_test_frame();
+            parse_can_frame(&frame, &mut data, Endianness::Little);
+            if ParsedFrameId::from_frame(&frame).command != 0xC0 {
+                continue;
+            }
+            let alarm = data.alarm_status.as_ref().unwrap();
+            if !alarm.active_alarms.is_empty() {
+                alarm_frames += 1;
+                assert_eq!(alarm.active_alarms, vec![0, 2]);
+                assert_eq!(alarm.max_severity, 3);
+            }
+        }
+        assert_eq!(alarm_frames, 1);
+
+        handler.set_alarm_pattern(SimulatedAlarmPattern {
+            bits: vec![9],
+            every_n_cycles: 1,
+        });
+        for _ in 0..10 {
+            parse_can_frame(
+                &handler.generate_test_frame(),
+                &mut data,
+                Endianness::Little,
+            );
+        }
+        assert_eq!(data.alarm_status.unwrap().active_alarms, vec![9]);
+    }
+
+    #[test]
+    fn test_time_scale_speeds_up_scenario() {
+        let scenario = SimulationScenario {
+            initial_soc: 100.0,
+            soc_step: -1.0,
+            ..Default::default()
+        };
+        let soc_after = |scale: f32, wall: Duration| {
+            let mut handler = SimulationHandler::with_scenario(scenario.clone());
+            handler.set_time_scale(scale);
+            handler.connect().unwrap();
+            let mut data = BmsData::default();
+            let start = Instant::now();
+            while start.elapsed() < wall {
+                let frame = handler.receive_frame(Duration::ZERO).unwrap().unwrap();
+                parse_can_frame(&frame, &mut data, Endianness::Little);
+            }
+            (data.soc_soh.unwrap().soc, handler.simulated_time())
+        };
+
+        // ~2 steps at real time vs ~20 at 10x
+        let (real_soc, real_time) = soc_after(1.0, Duration::from_millis(200));
+        let (fast_soc, fast_time) = soc_after(10.0, Duration::from_millis(200));
+        assert!(fast_soc + 5 < real_soc, "{} vs {}", fast_soc, real_soc);
+        assert!(fast_time > real_time * 4);
+
+        // Unscaled: 500 frames = 50 steps without any sleeping
+        let mut handler = SimulationHandler::with_scenario(scenario);
+        handler.set_time_scale(0.0);
+        handler.connect().unwrap();
+        let mut data = BmsData::default();
+        let start = Instant::now();
+        for _ in 0..500 {
+            let frame = handler.receive_frame(Duration::ZERO).unwrap().unwrap();
+            parse_can_frame(&frame, &mut data, Endianness::Little);
+        }
+        assert!(start.elapsed() < Duration::from_millis(200));
+        assert_eq!(data.soc_soh.unwrap().soc, 51);
+        assert_eq!(handler.simulated_time(), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_default_simulation_is_unchanged() {
+        let mut handler = SimulationHandler::new();
+        let mut data = BmsData::default();
+        for _ in 0..20 {
+            parse_can_frame(
+                &handler.generate_test_frame(),
+                &mut data,
+                Endianness::Little,
+            );
+        }
+
+        assert_eq!(data.soc_soh.unwrap().soc, 80);
+        assert!((data.temperature.unwrap().max_temperature - 27.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_simulated_soc_frame_parses_back() {
+        let mut handler = SimulationHandler::new();
+        let frame = handler.generate_frame(BmsCommand::SocSoh as u8);
+
+        let mut data = BmsData::default();
+        assert!(parse_can_frame(&frame, &mut data, Endianness::Little));
+        assert_eq!(data.soc_soh.unwrap().soc, 80);
+    }
+
+    #[test]
+    fn test_stats_count_simulated_traffic() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data);
+        manager.connect().unwrap();
+        manager.query_all_data().unwrap();
+
+        let stats = manager.stats();
+        assert_eq!(stats.frames_sent, 10);
+        assert_eq!(stats.frames_received, 10);
+        assert_eq!(stats.parse_failures, 0);
+        assert_eq!(stats.timeouts, 0);
+
+        manager.connect().unwrap();
+        assert_eq!(manager.stats().frames_sent, 0);
+    }
+
+    #[test]
+    fn test_iplus_frame_errors() {
+        let frame = CanFrame {
+            id: 0x1881_80F4,
+            format: FrameFormat::Extended,
+            data: vec![0x50, 0x00],
+            timestamp: 0,
+            hw_timestamp: None,
+        };
+        let mut bytes = build_iplus_frame(&frame);
+        assert_eq!(parse_iplus_frame(&bytes).unwrap().data, frame.data);
+
+        *bytes.last_mut().unwrap() ^= 0xFF;
+        assert_eq!(
+            parse_iplus_frame(&bytes).unwrap_err(),
+            IplusFrameError::ChecksumMismatch
+        );
+        assert_eq!(
+            parse_iplus_frame(&bytes[..5]).unwrap_err(),
+            IplusFrameError::TooShort
+        );
+    }
+
+    #[test]
+    fn test_iplus_frame_round_trip() {
+        let frames = [
+            (0x1881_80F4, FrameFormat::Extended, vec![]),
+            (0x1881_80F4, FrameFormat::Extended, vec![0x50]),
+            // Sum overflows several times
+            (0x1FFF_FFFF, FrameFormat::Extended, vec![0xFF; 8]),
+            (0x40C, FrameFormat::Standard, vec![1, 2, 3, 4, 5, 6, 7, 8]),
+        ];
+
+        for (id, format, data) in frames {
+            let frame = CanFrame {
+                id,
+                format,
+                data,
+                timestamp: 0,
+                hw_timestamp: None,
+            };
+            let bytes = build_iplus_frame(&frame);
+            assert_eq!(bytes.len(), 7 + frame.data.len() + 1);
+
+            let parsed = parse_iplus_frame(&bytes).unwrap();
+            assert_eq!(parsed.id, frame.id);
+            assert_eq!(parsed.format, frame.format);
+            assert_eq!(parsed.data, frame.data);
+        }
+    }
+
+    #[test]
+    fn test_query_commands_only_requests_selected() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect().unwrap();
+        manager
+            .query_commands(&[BmsCommand::SocSoh, BmsCommand::VoltageCurrent])
+            .unwrap();
+
+        assert_eq!(manager.stats().frames_sent, 2);
+        let data = bms_data.lock();
+        assert!(data.soc_soh.is_some());
+        assert!(data.voltage_current.is_some());
+        assert!(data.limits.is_none());
+        assert!(data.software_version.is_none());
+    }
+
+    #[test]
+    fn test_query_plan_interleaves_slow_set() {
+        let plan = QueryPlan {
+            slow_every: 3,
+            ..Default::default()
+        };
+        assert_eq!(plan.commands_for_cycle(0).len(), 10);
+        assert_eq!(
+            plan.commands_for_cycle(1),
+            vec![
+                BmsCommand::SocSoh,
+                BmsCommand::VoltageCurrent,
+                BmsCommand::AlarmStatus
+            ]
+        );
+        assert_eq!(plan.commands_for_cycle(3).len(), 10);
+    }
+
+    #[test]
+    fn test_static_commands_queried_at_reduced_rate() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            static_query_every: 3,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data);
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let hook_received = received.clone();
+        manager.set_frame_hook(Arc::new(move |frame: &CanFrame| {
+            hook_received
+                .lock()
+                .push(ParsedFrameId::from_frame(frame).command);
+        }));
+        manager.connect().unwrap();
+        manager.set_simulation_time_scale(0.0).unwrap();
+
+        for _ in 0..6 {
+            assert!(manager.query_all_data().unwrap().is_empty());
+        }
+        let count = |command: BmsCommand| {
+            received
+                .lock()
+                .iter()
+                .filter(|&&c| c == command as u8)
+                .count()
+        };
+        assert_eq!(count(BmsCommand::SocSoh), 6);
+        assert_eq!(count(BmsCommand::VoltageCurrent), 6);
+        assert_eq!(count(BmsCommand::SoftwareVersion), 2);
+        assert_eq!(count(BmsCommand::AccumulatedTimes), 2);
+
+        manager.refresh_static_data().unwrap();
+        assert_eq!(count(BmsCommand::SoftwareVersion), 3);
+    }
+
+    #[test]
+    fn test_two_channels_route_by_address() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            extra_channels: vec![ExtraChannel {
+                channel: 1,
+                bms_address: 0x02,
+            }],
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        let updated = Arc::new(Mutex::new(BTreeSet::new()));
+        let seen = updated.clone();
+        manager.set_update_hook(Arc::new(move |bms_address: u8, _: &BmsData| {
+            seen.lock().insert(bms_address);
+        }));
+        manager.connect().unwrap();
+        manager.set_simulation_time_scale(0.0).unwrap();
+        assert!(manager.query_all_data().unwrap().is_empty());
+        assert_eq!(*updated.lock(), BTreeSet::from([0x01, 0x02]));
+
+        let all = manager.all_bms_data();
+        assert_eq!(all.keys().copied().collect::<Vec<_>>(), vec![0x01, 0x02]);
+        for data in all.values() {
+            assert!(data.soc_soh.is_some());
+            assert!(data.voltage_current.is_some());
+        }
+        assert!(manager.detected_addresses().is_empty());
+        assert_eq!(manager.stats().address_mismatches, 0);
+
+        // The receive loop alternates between the channels
+        let before = manager.stats().frames_received;
+        assert!(manager.receive_once(Duration::from_millis(10)).unwrap());
+        assert!(manager.receive_once(Duration::from_millis(10)).unwrap());
+        assert_eq!(manager.stats().frames_received, before + 2);
+
+        let duplicate = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            extra_channels: vec![ExtraChannel {
+                channel: 1,
+                bms_address: 0x01,
+            }],
+            ..Default::default()
+        };
+        assert!(duplicate.validate().is_err());
+        for channels in [vec![0], vec![1, 1]] {
+            let reused = CanConfig {
+                adapter_type: AdapterType::ItekonCan,
+                extra_channels: channels
+                    .into_iter()
+                    .zip([0x02, 0x03])
+                    .map(|(channel, bms_address)| ExtraChannel {
+                        channel,
+                        bms_address,
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+            assert!(reused.validate().is_err(), "{:?}", reused.extra_channels);
+        }
+        let serial = CanConfig {
+            extra_channels: vec![ExtraChannel {
+                channel: 1,
+                bms_address: 0x02,
+            }],
+            ..Default::default()
+        };
+        assert!(serial.validate().is_err());
+    }
+
+    #[test]
+    fn test_foreign_address_is_skipped_and_reported() {
+        // The simulated BMS answers from 0x01
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            bms_address: 0x02,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect().unwrap();
+        assert!(matches!(
+            manager.query_all_data(),
+            Err(CanError::NoResponse)
+        ));
+
+        assert!(bms_data.lock().soc_soh.is_none());
+        assert_eq!(manager.detected_addresses(), vec![0x01]);
+        assert_eq!(manager.stats().address_mismatches, 10);
+    }
+
+    #[test]
+    fn test_echoed_query_does_not_clobber_data() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config.clone(), bms_data.clone());
+        manager.connect().unwrap();
+        manager.query_commands(&[BmsCommand::SocSoh]).unwrap();
+        let soc = bms_data.lock().soc_soh.as_ref().unwrap().soc;
+
+        let echo = build_query_frame(BmsCommand::SocSoh, config.host_address, config.bms_address);
+        manager.process_frame(&echo);
+
+        assert_eq!(bms_data.lock().soc_soh.as_ref().unwrap().soc, soc);
+        assert_eq!(manager.stats().parse_failures, 0);
+        assert_eq!(manager.stats().address_mismatches, 0);
+        assert!(manager.detected_addresses().is_empty());
+    }
+
+    #[test]
+    fn test_configured_send_delay_is_used() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            send_delay_ms: Some(40),
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+        manager.connect().unwrap();
+
+        let started = Instant::now();
+        manager
+            .query_commands(&[
+                BmsCommand::SocSoh,
+                BmsCommand::VoltageCurrent,
+                BmsCommand::AlarmStatus,
+            ])
+            .unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(120));
+    }
+
+    #[test]
+    fn test_timing_out_of_range_is_rejected() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            receive_timeout_ms: Some(0),
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+
+        assert!(matches!(manager.connect(), Err(CanError::InvalidConfig(_))));
+        assert!(!manager.is_connected());
+    }
+
+    /// I+ encoded response from the BMS at 0x01 to the host at 0x80
+    fn iplus_response(command: BmsCommand, data: &[u8]) -> Vec<u8> {
+        let id = ParsedFrameId {
+            ptp: true,
+            command: command as u8,
+            destination_address: 0x80,
+            source_address: 0x01,
+            cnt: false,
+        };
+        build_iplus_frame(&CanFrame {
+            id: id.to_id(),
+            format: FrameFormat::Extended,
+            data: data.to_vec(),
+            timestamp: 0,
+            hw_timestamp: None,
+        })
+    }
+
+    fn mock_manager(port: &MockPort) -> (CanManager, Arc<Mutex<BmsData>>) {
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(CanConfig::default(), bms_data.clone());
+        manager.connect_port(Box::new(port.clone())).unwrap();
+        (manager, bms_data)
+    }
+
+    #[test]
+    fn test_old_firmware_is_flagged_but_still_read() {
+        let port = MockPort::new();
+        let config = CanConfig {
+            min_firmware_version: FirmwareVersion {
+                major: 1,
+                minor: 20,
+            },
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect_port(Box::new(port.clone())).unwrap();
+
+        port.push_incoming(&iplus_response(
+            BmsCommand::SoftwareVersion,
+            &crate::bms_parser::encode_software_version("V1.10S"),
+        ));
+        port.push_incoming(&iplus_response(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+        ));
+        manager
+            .query_commands(&[BmsCommand::SoftwareVersion, BmsCommand::SocSoh])
+            .unwrap();
+
+        let data = bms_data.lock().clone();
+        assert_eq!(data.protocol_compatible, Some(false));
+        assert_eq!(data.soc_soh.as_ref().unwrap().soc, 34);
+        let summary = crate::health::health_summary(&data, &Default::default());
+        assert!(summary.firmware_warning.unwrap().contains("V1.10S"));
+
+        port.push_incoming(&iplus_response(
+            BmsCommand::SoftwareVersion,
+            &crate::bms_parser::encode_software_version("V1.20"),
+        ));
+        manager
+            .query_commands(&[BmsCommand::SoftwareVersion])
+            .unwrap();
+        assert_eq!(bms_data.lock().protocol_compatible, Some(true));
+    }
+
+    #[test]
+    fn test_out_of_range_pack_is_flagged() {
+        let port = MockPort::new();
+        let config = CanConfig {
+            battery_layout: BatteryLayout {
+                pack_count: Some(16),
+                cells_per_pack: Some(24),
+            },
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect_port(Box::new(port.clone())).unwrap();
+
+        // Max cell in pack 30 of 16
+        let cells = CellVoltageData {
+            max_voltage: 3.45,
+            max_voltage_pack_no: 30,
+            max_voltage_cell_no: 5,
+            min_voltage: 3.30,
+            min_voltage_pack_no: 2,
+            min_voltage_cell_no: 24,
+            voltage_delta: 0.15,
+        };
+        port.push_incoming(&iplus_response(
+            BmsCommand::CellVoltage,
+            &crate::bms_parser::encode_cell_voltage(&cells, Endianness::Little),
+        ));
+        manager.query_commands(&[BmsCommand::CellVoltage]).unwrap();
+
+        let data = bms_data.lock().clone();
+        assert_eq!(data.cell_voltage.unwrap().max_voltage_pack_no, 30);
+        assert_eq!(
+            data.index_errors,
+            vec!["Max cell voltage pack 30 is outside 1..=16 packs".to_string()]
+        );
+
+        // Cleared once a frame with valid indices arrives
+        let cells = CellVoltageData {
+            max_voltage_pack_no: 3,
+            ..cells
+        };
+        port.push_incoming(&iplus_response(
+            BmsCommand::CellVoltage,
+            &crate::bms_parser::encode_cell_voltage(&cells, Endianness::Little),
+        ));
+        manager.query_commands(&[BmsCommand::CellVoltage]).unwrap();
+        assert!(bms_data.lock().index_errors.is_empty());
+
+        let invalid = CanConfig {
+            battery_layout: BatteryLayout {
+                pack_count: Some(0),
+                cells_per_pack: None,
+            },
+            ..Default::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_repeated_identical_frames_are_flagged_frozen() {
+        let port = MockPort::new();
+        let config = CanConfig {
+            freeze_detection: FreezeConfig {
+                min_repeats: 5,
+                min_duration_ms: 0,
+            },
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect_port(Box::new(port.clone())).unwrap();
+
+        let soc = iplus_response(BmsCommand::SocSoh, &[0x50, 0x00, 0x62, 0x00, 0x3C, 0x00]);
+        let vc = iplus_response(
+            BmsCommand::VoltageCurrent,
+            &[0xB9, 0x1F, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00],
+        );
+        let mut poll = |soc: &[u8], vc: &[u8]| {
+            port.push_incoming(soc);
+            port.push_incoming(vc);
+            manager
+                .query_commands(&[BmsCommand::SocSoh, BmsCommand::VoltageCurrent])
+                .unwrap();
+            bms_data.lock().possibly_frozen
+        };
+
+        // One update per poll, not one per frame; the fifth identical poll trips the flag
+        for _ in 0..4 {
+            assert!(!poll(&soc, &vc));
+        }
+        assert!(poll(&soc, &vc));
+        assert!(poll(&soc, &vc));
+
+        // A moving current clears it
+        let moved = iplus_response(
+            BmsCommand::VoltageCurrent,
+            &[0xB9, 0x1F, 0x15, 0x00, 0x00, 0x00, 0x00, 0x00],
+        );
+        assert!(!poll(&soc, &moved));
+    }
+
+    #[test]
+    fn test_silent_bms_is_reported() {
+        let port = MockPort::new();
+        let (mut manager, bms_data) = mock_manager(&port);
+
+        // Queries go out, but nothing ever comes back
+        assert!(matches!(
+            manager.query_all_data(),
+            Err(CanError::NoResponse)
+        ));
+        assert!(!port.written().is_empty());
+        assert!(bms_data.lock().soc_soh.is_none());
+        let timeouts = ALL_QUERY_COMMANDS.len() as u64;
+        assert_eq!(manager.stats().timeouts, timeouts);
+
+        // An idle receive is no timeout
+        assert!(!manager.receive_once(Duration::from_millis(10)).unwrap());
+        assert_eq!(manager.stats().timeouts, timeouts);
+
+        // Echoes of our own queries aren't an answer
+        let echo = build_query_frame(BmsCommand::SocSoh, 0x80, 0x01);
+        port.push_incoming(&build_iplus_frame(&echo));
+        assert!(matches!(
+            manager.query_commands(&[BmsCommand::SocSoh]),
+            Err(CanError::NoResponse)
+        ));
+
+        // A single answer is enough to report the rest as merely missing
+        port.push_incoming(&iplus_response(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+        ));
+        let missing = manager.query_all_data().unwrap();
+        assert_eq!(missing.len(), ALL_QUERY_COMMANDS.len() - 1);
+        assert!(!missing.contains(&BmsCommand::SocSoh));
+    }
+
+    #[test]
+    fn test_unplugged_device_is_reported_not_retried() {
+        let port = MockPort::new();
+        let (mut manager, bms_data) = mock_manager(&port);
+
+        // One answer, then the adapter vanishes
+        port.push_incoming(&iplus_response(
+            BmsCommand::SocSoh,
+            &[0x50, 0x00, 0x62, 0x00, 0x3C, 0x00],
+        ));
+        port.push_read_error(std::io::ErrorKind::BrokenPipe);
+        let result = manager.query_all_data();
+        assert!(
+            matches!(result, Err(CanError::DeviceDisconnected(_))),
+            "{:?}",
+            result
+        );
+        assert!(result.unwrap_err().is_fatal());
+        assert!(bms_data.lock().soc_soh.is_some());
+
+        // A transient error only loses that read
+        port.push_read_error(std::io::ErrorKind::Interrupted);
+        assert!(matches!(
+            manager.receive_frame(Duration::from_millis(10)),
+            Ok(None)
+        ));
+        port.push_read_error(std::io::ErrorKind::InvalidData);
+        assert!(!manager
+            .receive_frame(Duration::from_millis(10))
+            .unwrap_err()
+            .is_fatal());
+
+        // The receive loop hands the error on and stops
+        port.push_read_error(std::io::ErrorKind::BrokenPipe);
+        let shared: SharedManager = Arc::new(Mutex::new(Some(manager)));
+        let running = Arc::new(Mutex::new(true));
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let receiver = {
+            let results = results.clone();
+            spawn_receive_loop(shared, running.clone(), move |result, _| {
+                results.lock().push(result.map_err(|e| e.is_fatal()));
+            })
+            .unwrap()
+        };
+        receiver.join().unwrap();
+        assert!(!*running.lock());
+        assert_eq!(*results.lock(), vec![Err(true)]);
+    }
+
+    #[test]
+    fn test_cancel_flag_stops_query_early() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+        manager.connect().unwrap();
+
+        // Cancel as soon as the first response comes in
+        let cancel = manager.cancel_flag();
+        let hook_cancel = cancel.clone();
+        manager.set_frame_hook(Arc::new(move |_: &CanFrame| {
+            hook_cancel.store(true, Ordering::SeqCst);
+        }));
+        assert!(matches!(manager.query_all_data(), Err(CanError::Cancelled)));
+        assert_eq!(manager.stats().frames_received, 1);
+
+        // Nothing is sent while the flag is set; a reconnect clears it
+        let sent = manager.stats().frames_sent;
+        assert!(matches!(
+            manager.query_single(BmsCommand::SocSoh),
+            Err(CanError::Cancelled)
+        ));
+        assert_eq!(manager.stats().frames_sent, sent);
+        manager.disconnect().unwrap();
+        manager.connect().unwrap();
+        assert!(!cancel.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_ping_reports_simulated_delay() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+        manager.connect().unwrap();
+        assert!(manager.ping(0).is_err());
+
+        let stats = manager.ping(3).unwrap();
+        assert_eq!((stats.sent, stats.timeouts), (3, 0));
+        assert_eq!(stats.latencies_ms.len(), 3);
+        let (min, avg, max) = (
+            stats.min_ms.unwrap(),
+            stats.avg_ms.unwrap(),
+            stats.max_ms.unwrap(),
+        );
+        assert!(min <= avg && avg <= max);
+        assert!(min >= SIMULATED_FRAME_INTERVAL.as_secs_f64() * 1000.0);
+
+        let mut timeouts = PingStats::default();
+        timeouts.record(None);
+        assert_eq!((timeouts.sent, timeouts.timeouts), (1, 1));
+        assert_eq!(timeouts.avg_ms, None);
+    }
+
+    #[test]
+    fn test_duplicate_frames_within_window_are_dropped() {
+        let frame = CanFrame {
+            id: 0x1802_8001,
+            format: FrameFormat::Extended,
+            data: vec![0x22, 0x00, 0x64, 0x00],
+            timestamp: 0,
+            hw_timestamp: None,
+        };
+        let start = Instant::now();
+        let mut dedup = FrameDeduplicator::new(Duration::from_millis(20));
+        assert!(!dedup.is_duplicate(&frame, start));
+        assert!(dedup.is_duplicate(&frame, start + Duration::from_millis(5)));
+        assert!(!dedup.is_duplicate(&frame, start + Duration::from_millis(30)));
+        let changed = CanFrame {
+            data: vec![0x23, 0x00, 0x64, 0x00],
+            ..frame.clone()
+        };
+        assert!(!dedup.is_duplicate(&changed, start + Duration::from_millis(31)));
+        // The same reply to a query sent again
+        dedup.forget_command(ParsedFrameId::from_frame(&frame).command);
+        assert!(!dedup.is_duplicate(&changed, start + Duration::from_millis(32)));
+
+        // Off by default: both copies are received
+        let timeout = Duration::from_millis(10);
+        let response = iplus_response(BmsCommand::SocSoh, &[0x22, 0, 0x64, 0, 0x1E, 0, 0, 0]);
+        let port = MockPort::new();
+        let (mut manager, _) = mock_manager(&port);
+        port.push_incoming(&response);
+        port.push_incoming(&response);
+        assert!(manager.receive_once(timeout).unwrap());
+        assert!(manager.receive_once(timeout).unwrap());
+
+        let config = CanConfig {
+            dedup_window_ms: Some(1000),
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+        manager.connect_port(Box::new(port.clone())).unwrap();
+        port.push_incoming(&response);
+        port.push_incoming(&response);
+        assert!(manager.receive_once(timeout).unwrap());
+        assert!(!manager.receive_once(timeout).unwrap());
+        assert_eq!(manager.stats().frames_received, 1);
+        assert_eq!(manager.stats().duplicate_frames, 1);
+        assert_eq!(manager.stats().timeouts, 0);
+
+        // Unchanged answers to repeated queries all count
+        for _ in 0..3 {
+            port.push_incoming(&response);
+            assert!(manager
+                .query_commands(&[BmsCommand::SocSoh])
+                .unwrap()
+                .is_empty());
+        }
+        assert_eq!(manager.stats().duplicate_frames, 1);
+    }
+
+    #[test]
+    fn test_serial_path_reassembles_fragmented_frames() {
+        let port = MockPort::new();
+        let soc = iplus_response(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+        );
+        let vc = iplus_response(
+            BmsCommand::VoltageCurrent,
+            &[0xB9, 0x1F, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00],
+        );
+
+        // Line noise, then the first frame split mid-payload and glued to the second
+        let mut stream = vec![0x00, 0x13];
+        stream.extend_from_slice(&soc);
+        stream.extend_from_slice(&vc);
+        port.push_incoming(&stream[..9]);
+        port.push_incoming(&stream[9..]);
+
+        let (mut manager, bms_data) = mock_manager(&port);
+        manager
+            .query_commands(&[BmsCommand::SocSoh, BmsCommand::VoltageCurrent])
+            .unwrap();
+
+        let data = bms_data.lock();
+        assert_eq!(data.soc_soh.as_ref().unwrap().soc, 34);
+        assert!((data.voltage_current.as_ref().unwrap().voltage - 812.1).abs() < 0.01);
+        assert_eq!(manager.stats().frames_received, 2);
+
+        let query = build_query_frame(BmsCommand::SocSoh, 0x80, 0x01);
+        assert!(port.written().starts_with(&build_iplus_frame(&query)));
+    }
+
+    #[test]
+    fn test_serial_path_reads_burst() {
+        let burst = [
+            iplus_response(
+                BmsCommand::SocSoh,
+                &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+            ),
+            iplus_response(
+                BmsCommand::VoltageCurrent,
+                &[0xB9, 0x1F, 0x38, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ),
+            iplus_response(
+                BmsCommand::AccumulatedTimes,
+                &[0x0A, 0x00, 0x0B, 0x00, 0x00, 0x00, 0x00, 0x00],
+            ),
+        ]
+        .concat();
+
+        // Default buffer takes the whole burst in one read; a minimal one
+        // leaves the rest in the port for the following reads
+        for read_buffer_size in [DEFAULT_READ_BUFFER_SIZE, 16] {
+            let port = MockPort::new();
+            port.push_incoming(&burst);
+            let bms_data = Arc::new(Mutex::new(BmsData::default()));
+            let config = CanConfig {
+                read_buffer_size,
+                ..Default::default()
+            };
+            let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+            manager.connect_port(Box::new(port.clone())).unwrap();
+
+            let missing = manager
+                .query_commands(&[
+                    BmsCommand::SocSoh,
+                    BmsCommand::VoltageCurrent,
+                    BmsCommand::AccumulatedTimes,
+                ])
+                .unwrap();
+            assert!(missing.is_empty(), "{:?}", missing);
+            assert_eq!(manager.stats().frames_received, 3);
+
+            let data = bms_data.lock();
+            assert_eq!(data.soc_soh.as_ref().unwrap().soc, 34);
+            assert!(data.voltage_current.is_some());
+            assert_eq!(data.accumulated_times.as_ref().unwrap().discharge_times, 11);
+        }
+
+        let config = CanConfig {
+            read_buffer_size: 4,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_serial_path_drops_bad_checksum() {
+        let port = MockPort::new();
+        let mut corrupt = iplus_response(
+            BmsCommand::SocSoh,
+            &[0x50, 0x00, 0x64, 0x00, 0x3C, 0x00, 0x00, 0x00],
+        );
+        *corrupt.last_mut().unwrap() ^= 0xFF;
+        port.push_incoming(&corrupt);
+        port.push_incoming(&iplus_response(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+        ));
+
+        let (mut manager, bms_data) = mock_manager(&port);
+        manager.query_commands(&[BmsCommand::SocSoh]).unwrap();
+
+        assert_eq!(bms_data.lock().soc_soh.as_ref().unwrap().soc, 34);
+        assert_eq!(manager.stats().checksum_errors, 1);
+        assert_eq!(manager.stats().parse_failures, 0);
+        assert_eq!(manager.stats().timeouts, 0);
+    }
+
+    #[test]
+    fn test_set_limits_validates_and_writes() {
+        let port = MockPort::new();
+        let (mut manager, _) = mock_manager(&port);
+
+        let mut limits = ChargeDischargeLimits {
+            charge_voltage_limit: 859.2,
+            charge_current_limit: 100.0,
+            discharge_voltage_limit: 672.0,
+            discharge_current_limit: 100.0,
+        };
+        let frame = manager.set_charge_discharge_limits(&limits).unwrap();
+        assert_eq!(frame.data, [0x90, 0x21, 0xE8, 0x03, 0x40, 0x1A, 0xE8, 0x03]);
+        assert_eq!(port.written(), build_iplus_frame(&frame));
+
+        limits.discharge_current_limit = -1.0;
+        assert!(manager.set_charge_discharge_limits(&limits).is_err());
+        limits.discharge_current_limit = MAX_CURRENT_LIMIT + 0.1;
+        assert!(manager.set_charge_discharge_limits(&limits).is_err());
+        assert_eq!(manager.stats().frames_sent, 1);
+    }
+
+    #[test]
+    fn test_query_reports_missing_responses() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+        manager.connect().unwrap();
+        assert!(manager.query_all_data().unwrap().is_empty());
+
+        // Only 0x81 answers; 0x82 is dropped on the link
+        let port = MockPort::new();
+        port.push_incoming(&iplus_response(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+        ));
+        let (mut manager, bms_data) = mock_manager(&port);
+        let missing = manager
+            .query_commands(&[BmsCommand::SocSoh, BmsCommand::VoltageCurrent])
+            .unwrap();
+
+        assert_eq!(missing, vec![BmsCommand::VoltageCurrent]);
+        assert_eq!(bms_data.lock().soc_soh.as_ref().unwrap().soc, 34);
+    }
+
+    #[test]
+    fn test_query_single_waits_for_a_decoded_reply() {
+        let reply = [0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00];
+        let port = MockPort::new();
+        port.push_incoming(&iplus_response(
+            BmsCommand::SocSoh,
+            &[0x10, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+        ));
+        let (mut manager, _) = mock_manager(&port);
+        assert!(manager.query_single(BmsCommand::SocSoh).is_ok());
+
+        // A read error and an undecodable reply don't return the old SOC
+        port.push_read_error(std::io::ErrorKind::InvalidData);
+        port.push_incoming(&iplus_response(BmsCommand::SocSoh, &reply[..2]));
+        port.push_incoming(&iplus_response(BmsCommand::SocSoh, &reply));
+        match manager.query_single(BmsCommand::SocSoh).unwrap() {
+            CommandData::SocSoh(soc_soh) => assert_eq!(soc_soh.soc, 34),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        port.push_incoming(&iplus_response(BmsCommand::SocSoh, &reply[..2]));
+        assert!(matches!(
+            manager.query_single(BmsCommand::SocSoh),
+            Err(CanError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_update_hook_runs_without_data_lock() {
+        let port = MockPort::new();
+        port.push_incoming(&iplus_response(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+        ));
+        let (mut manager, bms_data) = mock_manager(&port);
+        let unlocked = Arc::new(Mutex::new(Vec::new()));
+        let (shared, seen) = (bms_data.clone(), unlocked.clone());
+        manager.set_update_hook(Arc::new(move |_: u8, data: &BmsData| {
+            let free = shared.try_lock().is_some();
+            seen.lock()
+                .push((free, data.soc_soh.as_ref().map(|s| s.soc)));
+        }));
+        manager.query_commands(&[BmsCommand::SocSoh]).unwrap();
+
+        assert_eq!(*unlocked.lock(), vec![(true, Some(34))]);
+    }
+
+    #[test]
+    fn test_unanswered_queries_go_stale() {
+        use crate::alarms::{communication_status, LinkFault, DEFAULT_STALE_AFTER_MS};
+
+        let port = MockPort::new();
+        let (mut manager, bms_data) = mock_manager(&port);
+        assert!(matches!(
+            manager.query_commands(&[BmsCommand::SocSoh]),
+            Err(CanError::NoResponse)
+        ));
+        let now = chrono::Utc::now().timestamp_millis();
+        let status = communication_status(&bms_data.lock(), true, now, DEFAULT_STALE_AFTER_MS);
+        assert_eq!(status.link_fault, Some(LinkFault::NoData));
+
+        // Answered once long ago; sending more queries doesn't make it fresh
+        bms_data
+            .lock()
+            .updated_at
+            .insert(BmsCommand::SocSoh, now - 2 * DEFAULT_STALE_AFTER_MS);
+        assert!(manager.query_commands(&[BmsCommand::SocSoh]).is_err());
+        let now = chrono::Utc::now().timestamp_millis();
+        let data = bms_data.lock();
+        assert!(data.timestamp >= now - DEFAULT_STALE_AFTER_MS);
+        let status = communication_status(&data, true, now, DEFAULT_STALE_AFTER_MS);
+        assert_eq!(status.link_fault, Some(LinkFault::Stale));
+    }
+
+    #[test]
+    fn test_standard_frame_format() {
+        let query = build_query_frame(BmsCommand::SocSoh, 0x80, 0x01).to_standard();
+        assert_eq!(query.id, (0x81 << 3) | 0x01);
+        assert_eq!(
+            build_iplus_frame(&query)[..6],
+            [0xAA, 0x00, 0x09, 0x04, 0x00, 0x00]
+        );
+
+        let port = MockPort::new();
+        let response = CanFrame {
+            id: (0x81 << 3) | 0x01,
+            format: FrameFormat::Standard,
+            data: vec![0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+            timestamp: 0,
+            hw_timestamp: None,
+        };
+        port.push_incoming(&build_iplus_frame(&response));
+
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let config = CanConfig {
+            frame_format: FrameFormat::Standard,
+            ..Default::default()
+        };
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect_port(Box::new(port.clone())).unwrap();
+        let missing = manager.query_commands(&[BmsCommand::SocSoh]).unwrap();
+
+        assert!(missing.is_empty());
+        assert_eq!(bms_data.lock().soc_soh.as_ref().unwrap().soc, 34);
+        assert_eq!(port.written(), build_iplus_frame(&query));
+        assert!(manager.send_raw_frame(0x800, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_query_single_soc_soh() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect().unwrap();
+
+        match manager.query_single(BmsCommand::SocSoh).unwrap() {
+            CommandData::SocSoh(soc_soh) => assert_eq!(soc_soh.soc, 80),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(bms_data.lock().voltage_current.is_none());
+        assert!(manager.query_single(BmsCommand::Reset).is_err());
+    }
+
+    /// Backend answering every query with a fixed SOC frame from address 0x01
+    struct DummyBackend {
+        connected: bool,
+        sent: Arc<Mutex<Vec<CanFrame>>>,
+        pending: VecDeque<CanFrame>,
+    }
+
+    impl CanBackend for DummyBackend {
+        fn connect(&mut self) -> Result<(), CanError> {
+            self.connected = true;
+            Ok(())
+        }
+
+        fn disconnect(&mut self) -> Result<(), CanError> {
+            self.connected = false;
+            Ok(())
+        }
+
+        fn send_frame(&mut self, frame: &CanFrame) -> Result<(), CanError> {
+            self.sent.lock().push(frame.clone());
+            let id = ParsedFrameId {
+                ptp: true,
+                command: BmsCommand::SocSoh as u8,
+                destination_address: 0x80,
+                source_address: 0x01,
+                cnt: false,
+            };
+            self.pending.push_back(CanFrame {
+                id: id.to_id(),
+                format: FrameFormat::Extended,
+                data: vec![0x2A, 0x00, 0x60, 0x00, 0x10, 0x00, 0x00, 0x00],
+                timestamp: 0,
+                hw_timestamp: None,
+            });
+            Ok(())
+        }
+
+        fn receive_frame(&mut self, _timeout: Duration) -> Result<Option<CanFrame>, CanError> {
+            Ok(self.pending.pop_front())
+        }
+
+        fn is_connected(&self) -> bool {
+            self.connected
+        }
+    }
+
+    /// Multi-channel adapter without a CAN3, tracking its open channels
+    struct ChannelBackend {
+        channel: u32,
+        open: Arc<Mutex<BTreeSet<u32>>>,
+    }
+
+    impl CanBackend for ChannelBackend {
+        fn connect(&mut self) -> Result<(), CanError> {
+            self.open.lock().insert(self.channel);
+            Ok(())
+        }
+
+        fn disconnect(&mut self) -> Result<(), CanError> {
+            self.open.lock().remove(&self.channel);
+            Ok(())
+        }
+
+        fn send_frame(&mut self, _frame: &CanFrame) -> Result<(), CanError> {
+            Ok(())
+        }
+
+        fn receive_frame(&mut self, _timeout: Duration) -> Result<Option<CanFrame>, CanError> {
+            Ok(None)
+        }
+
+        fn is_connected(&self) -> bool {
+            self.open.lock().contains(&self.channel)
+        }
+
+        fn open_channel(&mut self, channel: u32) -> Result<Box<dyn CanBackend>, CanError> {
+            if channel == 2 {
+                return Err(CanError::DeviceNotFound("no CAN3".to_string()));
+            }
+            self.open.lock().insert(channel);
+            Ok(Box::new(ChannelBackend {
+                channel,
+                open: self.open.clone(),
+            }))
+        }
+    }
+
+    #[test]
+    fn test_extra_channels_share_the_adapter() {
+        let extra = |channel: u32, bms_address: u8| ExtraChannel {
+            channel,
+            bms_address,
+        };
+        let open = Arc::new(Mutex::new(BTreeSet::new()));
+        let config = CanConfig {
+            adapter_type: AdapterType::ItekonCan,
+            extra_channels: vec![extra(1, 0x02)],
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+        let backend = ChannelBackend {
+            channel: 0,
+            open: open.clone(),
+        };
+        manager.connect_backend(Box::new(backend)).unwrap();
+        manager.connect().unwrap();
+        assert_eq!(*open.lock(), BTreeSet::from([0, 1]));
+        manager.disconnect().unwrap();
+        assert!(open.lock().is_empty());
+
+        // A channel that fails to open closes the ones opened before it
+        manager.config.extra_channels = vec![extra(1, 0x02), extra(2, 0x03)];
+        assert!(matches!(
+            manager.connect(),
+            Err(CanError::DeviceNotFound(_))
+        ));
+        assert!(open.lock().is_empty());
+        assert!(!manager.is_connected());
+    }
+
+    #[test]
+    fn test_manager_uses_custom_backend() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(CanConfig::default(), bms_data.clone());
+        manager
+            .connect_backend(Box::new(DummyBackend {
+                connected: false,
+                sent: sent.clone(),
+                pending: VecDeque::new(),
+            }))
+            .unwrap();
+        assert!(manager.is_connected());
+
+        match manager.query_single(BmsCommand::SocSoh).unwrap() {
+            CommandData::SocSoh(soc_soh) => assert_eq!(soc_soh.soc, 42),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(sent.lock().len(), 1);
+        assert_eq!(
+            ParsedFrameId::from_frame(&sent.lock()[0]).command,
+            BmsCommand::SocSoh as u8
+        );
+        let stats = manager.stats();
+        assert_eq!((stats.frames_sent, stats.frames_received), (1, 1));
+
+        // Simulation-only controls don't apply to a hardware-like backend
+        assert!(manager.set_simulation_time_scale(2.0).is_err());
+        assert!(manager.send_raw_frame(0x123, vec![1]).is_ok());
+        assert_eq!(sent.lock().len(), 2);
+
+        manager.disconnect().unwrap();
+        assert!(!manager.is_connected());
+    }
+
+    #[test]
+    fn test_bus_load_meter_rate() {
+        let start = Instant::now();
+        let mut meter = BusLoadMeter::new(Duration::from_secs(5), start);
+
+        // 20 received 8-byte frames per second for 10 seconds
+        for i in 1..=200 {
+            meter.record(start + Duration::from_millis(i * 50), 8, true);
+        }
+        let load = meter.load(start + Duration::from_secs(10), CAN_BAUD_RATE);
+        assert!((load.frames_per_sec - 20.0).abs() < 0.5, "{:?}", load);
+        // 20 * 131 bits = 2620 bit/s of 125k
+        assert!((load.utilization_percent - 2.096).abs() < 0.1, "{:?}", load);
+    }
+
+    /// Records the names of entered spans
+    #[derive(Default)]
+    struct SpanRecorder {
+        names: Mutex<Vec<&'static str>>,
+        entered: Mutex<Vec<&'static str>>,
+    }
+
+    impl tracing::Subscriber for SpanRecorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut names = self.names.lock();
+            names.push(span.metadata().name());
+            tracing::span::Id::from_u64(names.len() as u64)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, span: &tracing::span::Id) {
+            let name = self.names.lock()[span.into_u64() as usize - 1];
+            self.entered.lock().push(name);
+        }
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[test]
+    fn test_simulation_query_enters_spans() {
+        let recorder = Arc::new(SpanRecorder::default());
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let config = CanConfig {
+                adapter_type: AdapterType::Simulation,
+                ..Default::default()
+            };
+            let mut manager =
+                CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+            manager.connect().unwrap();
+            manager.query_all_data().unwrap();
+        });
+
+        let entered = recorder.entered.lock();
+        for name in ["connect", "query_all_data", "query", "parse_frame"] {
+            assert!(
+                entered.contains(&name),
+                "{} not entered: {:?}",
+                name,
+                entered
+            );
+        }
+    }
+
+    #[test]
+    fn test_adapter_availability_flags() {
+        let root = std::env::temp_dir().join(format!("bms-sysfs-{}", std::process::id()));
+        for (name, kind) in [("can0", "280\n"), ("eth0", "1\n"), ("vcan1", "280\n")] {
+            std::fs::create_dir_all(root.join(name)).unwrap();
+            std::fs::write(root.join(name).join("type"), kind).unwrap();
+        }
+        assert_eq!(socket_can_interfaces_in(&root), vec!["can0", "vcan1"]);
+        assert!(socket_can_interfaces_in(&root.join("missing")).is_empty());
+
+        let dll = root.join("ControlCAN.dll");
+        std::fs::write(&dll, b"").unwrap();
+        assert_eq!(
+            crate::itekon_handler::find_library([root.join("ECANVCI.dll"), dll.clone()]),
+            Some(dll.clone())
+        );
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let found = AdapterAvailability::new(vec![], vec![], vec!["can0".to_string()], Some(dll));
+        assert!(found.socket_can_available && found.itekon_available);
+        assert!(found.itekon_library.unwrap().ends_with("ControlCAN.dll"));
+        let none = AdapterAvailability::new(vec![], vec![], vec![], None);
+        assert!(!none.socket_can_available && !none.itekon_available);
+
+        // What this platform can offer
+        let detected = CanManager::list_adapters();
+        assert!(detected.simulation_available);
+        if !cfg!(target_os = "linux") {
+            assert!(!detected.socket_can_available);
+        }
+        if !cfg!(target_os = "windows") {
+            assert!(!detected.itekon_available);
+        }
+    }
+
+    #[test]
+    fn test_connect_and_query_on_different_threads() {
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let shared: SharedManager = Arc::new(Mutex::new(None));
+
+        let connector = {
+            let (shared, bms_data) = (shared.clone(), bms_data.clone());
+            std::thread::spawn(move || {
+                let config = CanConfig {
+                    adapter_type: AdapterType::Simulation,
+                    ..Default::default()
+                };
+                let mut manager = CanManager::new_with_mutex(config, bms_data);
+                manager.connect().unwrap();
+                *shared.lock() = Some(manager);
+            })
+        };
+        connector.join().unwrap();
+
+        let running = Arc::new(Mutex::new(true));
+        let receiver = spawn_receive_loop(shared.clone(), running.clone(), |_, _| {}).unwrap();
+
+        let querier = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                shared
+                    .lock()
+                    .as_mut()
+                    .unwrap()
+                    .query_commands(&[BmsCommand::SocSoh])
+                    .unwrap()
+            })
+        };
+        assert!(querier.join().unwrap().is_empty());
+        assert!(bms_data.lock().soc_soh.is_some());
+
+        // Control stays with the flag's owner
+        *running.lock() = false;
+        receiver.join().unwrap();
+        assert!(shared.lock().as_ref().unwrap().is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_frame_stream_in_simulation() {
+        use tokio_stream::StreamExt;
+
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect().unwrap();
+        let shared: SharedManager = Arc::new(Mutex::new(Some(manager)));
+
+        let mut stream = frame_stream(shared.clone()).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = stream.next().await {
+            frames.push(frame);
+            if frames.len() == 3 {
+                break;
+            }
+        }
+        assert_eq!(frames.len(), 3);
+        assert!(frames
+            .iter()
+            .all(|f| ParsedFrameId::from_frame(f).source_address == 0x01));
+        assert!(bms_data.lock().timestamp > 0);
+
+        // The blocking API still works next to the stream
+        let missing = shared
+            .lock()
+            .as_mut()
+            .unwrap()
+            .query_commands(&[BmsCommand::SocSoh])
+            .unwrap();
+        assert!(missing.is_empty());
+
+        // Taking the manager out ends the stream
+        shared.lock().take();
+        while stream.next().await.is_some() {}
+    }
+
+    #[test]
+    fn test_log_limiter_collapses_repeats() {
+        let start = Instant::now();
+        let mut limiter = LogLimiter::new(Duration::from_secs(1));
+
+        // 5000 failures with garbage ids over 2.5 seconds
+        let lines: Vec<String> = (0..5000u32)
+            .filter_map(|i| {
+                let now = start + Duration::from_micros(i as u64 * 500);
+                let message = format!("Failed to parse frame 0x{:08X}", i);
+                limiter.check("Failed to parse frame", &message, now)
+            })
+            .collect();
+        assert_eq!(lines.len(), 3, "{:?}", lines);
+        assert_eq!(lines[0], "Failed to parse frame 0x00000000");
+        assert_eq!(
+            lines[1],
+            "Failed to parse frame 0x000007D0 (1999 similar suppressed)"
+        );
+
+        // A different kind is not held back
+        let end = start + Duration::from_millis(2500);
+        assert!(limiter
+            .check("Bad length", "Dropped I+ frame with data length 12", end)
+            .is_some());
+
+        // The tail of the storm is reported without another failure
+        assert!(limiter.flush(end).is_empty());
+        assert_eq!(
+            limiter.flush(end + Duration::from_secs(1)),
+            vec!["Failed to parse frame: 999 similar suppressed"]
+        );
+        assert!(limiter.entries.is_empty());
+
+        // Full: the least recently logged kind makes room
+        for i in 0..=LogLimiter::MAX_ENTRIES {
+            let now = start + Duration::from_millis(i as u64);
+            limiter.check(&format!("kind {}", i), "message", now);
+        }
+        assert_eq!(limiter.entries.len(), LogLimiter::MAX_ENTRIES);
+        assert!(!limiter.entries.contains_key("kind 0"));
+    }
+
+    #[test]
+    fn test_simulation_frame_rate_is_plausible() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let mut manager =
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())));
+        manager.connect().unwrap();
+
+        // Roughly one received frame every 20ms
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_millis(400) {
+            manager.receive_frame(Duration::from_millis(10)).unwrap();
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        let load = manager.bus_load();
+        assert!(
+            load.frames_per_sec > 10.0 && load.frames_per_sec < 60.0,
+            "{:?}",
+            load
+        );
+        assert!(load.utilization_percent > 0.0 && load.utilization_percent < 100.0);
+    }
+
+    #[test]
+    fn test_monotonic_timestamps_never_go_backwards() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            timestamp_source: TimestampSource::Monotonic,
+            ..Default::default()
+        };
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let mut manager = CanManager::new_with_mutex(config, bms_data);
+        manager.connect().unwrap();
+
+        let mut last = manager.connected_at_ms();
+        for _ in 0..20 {
+            let frame = manager
+                .receive_frame(Duration::from_millis(10))
+                .unwrap()
+                .unwrap();
+            assert!(frame.timestamp >= last);
+            last = frame.timestamp;
+        }
+    }
+
+    #[test]
+    fn test_bluetooth_uses_longer_timeouts() {
+        let manager = |adapter_type| {
+            let config = CanConfig {
+                adapter_type,
+                ..Default::default()
+            };
+            CanManager::new_with_mutex(config, Arc::new(Mutex::new(BmsData::default())))
+        };
+        let usb = manager(AdapterType::UsbCan).link_timing();
+        let bluetooth = manager(AdapterType::BluetoothCan).link_timing();
+
+        assert!(bluetooth.receive_timeout > usb.receive_timeout);
+        assert!(bluetooth.open_timeout > usb.open_timeout);
+        assert_eq!(bluetooth.receive_timeout, Duration::from_millis(200));
+
+        assert!(is_bluetooth_port_name("/dev/rfcomm0"));
+        assert!(is_bluetooth_port_name("/dev/cu.Bluetooth-Incoming-Port"));
+        assert!(!is_bluetooth_port_name("/dev/ttyUSB0"));
     }
 }
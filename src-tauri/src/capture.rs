@@ -0,0 +1,249 @@
+//! Frame Capture
+//! Records received CAN frames as JSON lines for bug reports and reads them back.
+//!
+//! Each line is `{"id":<u32>,"format":"Extended","data":"<hex>","timestamp":<ms>}`;
+//! `format` defaults to `Extended` for older captures.
+
+use crate::bms_types::{CanFrame, FrameFormat};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Frames buffered between the receive loop and the writer thread
+const CAPTURE_QUEUE_SIZE: usize = 1024;
+
+/// One line of a capture file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapturedFrame {
+    /// CAN identifier
+    pub id: u32,
+    #[serde(default)]
+    pub format: FrameFormat,
+    /// Payload as lowercase hex
+    pub data: String,
+    /// Timestamp in ms
+    pub timestamp: i64,
+}
+
+impl From<&CanFrame> for CapturedFrame {
+    fn from(frame: &CanFrame) -> Self {
+        CapturedFrame {
+            id: frame.id,
+            format: frame.format,
+            data: frame.data.iter().map(|b| format!("{:02x}", b)).collect(),
+            timestamp: frame.timestamp,
+        }
+    }
+}
+
+impl CapturedFrame {
+    /// Convert back to a frame; fails on malformed hex
+    pub fn to_frame(&self) -> Result<CanFrame, String> {
+        if !self.data.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("invalid hex payload: {}", self.data));
+        }
+        if !self.data.len().is_multiple_of(2) {
+            return Err(format!("odd-length hex payload: {}", self.data));
+        }
+        // All ASCII from here, so byte offsets are char boundaries
+        let data = (0..self.data.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&self.data[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|e| format!("invalid hex payload {}: {}", self.data, e))?;
+
+        Ok(CanFrame {
+            id: self.id,
+            format: self.format,
+            data,
+            timestamp: self.timestamp,
+            hw_timestamp: None,
+        })
+    }
+}
+
+/// Write frames to a JSON-lines file
+pub fn write_frames(path: impl AsRef<Path>, frames: &[CanFrame]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for frame in frames {
+        write_line(&mut writer, frame)?;
+    }
+    writer.flush()
+}
+
+/// Read frames from a JSON-lines file, skipping blank lines
+pub fn read_frames(path: impl AsRef<Path>) -> std::io::Result<Vec<CanFrame>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut frames = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let frame = serde_json::from_str::<CapturedFrame>(&line)
+            .map_err(|e| e.to_string())
+            .and_then(|captured| captured.to_frame())
+            .map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("line {}: {}", index + 1, e),
+                )
+            })?;
+        frames.push(frame);
+    }
+
+    Ok(frames)
+}
+
+fn write_line(writer: &mut impl Write, frame: &CanFrame) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, &CapturedFrame::from(frame))?;
+    writer.write_all(b"\n")
+}
+
+/// Live capture to a file on a background writer thread
+///
+/// `record` never blocks: frames are dropped (and counted) when the writer
+/// falls behind, and a write error such as a full disk stops the capture.
+pub struct FrameCapture {
+    sender: Option<SyncSender<CanFrame>>,
+    writer: Option<JoinHandle<()>>,
+    failed: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl FrameCapture {
+    /// Create (truncate) `path` and start the writer thread
+    pub fn start(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = File::create(path.as_ref())?;
+        let (sender, receiver) = sync_channel::<CanFrame>(CAPTURE_QUEUE_SIZE);
+        let failed = Arc::new(AtomicBool::new(false));
+
+        let thread_failed = failed.clone();
+        let writer = std::thread::Builder::new()
+            .name("frame-capture".to_string())
+            .spawn(move || {
+                let mut writer = BufWriter::new(file);
+                for frame in receiver {
+                    if let Err(e) = write_line(&mut writer, &frame) {
+                        log::error!("Frame capture stopped: {}", e);
+                        thread_failed.store(true, Ordering::SeqCst);
+                        return;
+                    }
+                }
+                if let Err(e) = writer.flush() {
+                    log::error!("Frame capture flush failed: {}", e);
+                    thread_failed.store(true, Ordering::SeqCst);
+                }
+            })?;
+
+        log::info!("Capturing frames to {}", path.as_ref().display());
+        Ok(FrameCapture {
+            sender: Some(sender),
+            writer: Some(writer),
+            failed,
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Queue a frame for writing without blocking
+    pub fn record(&self, frame: &CanFrame) {
+        let Some(ref sender) = self.sender else {
+            return;
+        };
+        match sender.try_send(frame.clone()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                // Writer thread stopped after an I/O error
+            }
+        }
+    }
+
+    /// Frames dropped because the writer fell behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Whether the writer stopped because of an I/O error
+    pub fn has_failed(&self) -> bool {
+        self.failed.load(Ordering::SeqCst)
+    }
+
+    /// Flush remaining frames and close the file
+    pub fn stop(&mut self) -> Result<(), String> {
+        self.sender = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+        if self.has_failed() {
+            return Err("capture stopped early after a write error".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FrameCapture {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_round_trip() {
+        let path = std::env::temp_dir().join(format!("bms-capture-{}.jsonl", std::process::id()));
+        let frames = vec![
+            CanFrame {
+                id: 0x1881_80F4,
+                format: FrameFormat::Extended,
+                data: vec![0x50, 0x00, 0x64, 0x00, 0x3C, 0x00, 0x00, 0x00],
+                timestamp: 1_700_000_000_000,
+                hw_timestamp: None,
+            },
+            CanFrame {
+                id: 0x18C0_80F4,
+                format: FrameFormat::Extended,
+                data: vec![],
+                timestamp: 1_700_000_000_010,
+                hw_timestamp: None,
+            },
+        ];
+
+        let mut capture = FrameCapture::start(&path).unwrap();
+        for frame in &frames {
+            capture.record(frame);
+        }
+        capture.stop().unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            text.starts_with(r#"{"id":411140340,"format":"Extended","data":"500064003c000000""#)
+        );
+
+        let read = read_frames(&path).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].id, frames[0].id);
+        assert_eq!(read[0].data, frames[0].data);
+        assert_eq!(read[1].timestamp, frames[1].timestamp);
+
+        std::fs::remove_file(&path).unwrap();
+
+        // Malformed payloads are rejected rather than panicking
+        let mut line = CapturedFrame::from(&frames[0]);
+        for data in ["aéb", "a", "0g", "+f"] {
+            line.data = data.to_string();
+            assert!(line.to_frame().is_err(), "{}", data);
+        }
+    }
+}
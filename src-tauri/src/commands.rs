@@ -1,17 +1,108 @@
 //! Tauri Commands for BMS Monitor
 
+use crate::alarms::{
+    active_alarm_details, communication_status, AlarmAcknowledgements, AlarmAction,
+    AlarmActionHook, AlarmDetail, CommunicationStatus, CriticalAlarmWatch, CRITICAL_ALARM_EVENT,
+    DEFAULT_ALARM_ACTION_DEBOUNCE, DEFAULT_STALE_AFTER_MS,
+};
 use crate::bms_types::*;
-use crate::can_handler::{AdapterType, CanConfig, CanManager};
+use crate::can_handler::{
+    spawn_receive_loop, AdapterAvailability, AdapterInfo, AdapterType, BluetoothConfig, BusLoad,
+    CanConfig, CanError, CanManager, CanStats, CommandLatency, ExtraChannel, PingStats, QueryPlan,
+    SharedManager, SimulationScenario, TimestampSource,
+};
+use crate::capture::FrameCapture;
+use crate::connection::{
+    ConnectionEvent, ConnectionReason, ConnectionStateTracker, HeartbeatMonitor, LinkMonitor,
+    CONNECTION_STATE_EVENT, HEARTBEAT_INTERVAL_RANGE_MS, LINK_DROP_DEBOUNCE,
+};
+use crate::diagnostics::{run_self_test, DiagnosticsReport};
+use crate::direction::{CurrentDirection, DirectionConfig, DirectionFilter};
+use crate::freeze::FreezeConfig;
+use crate::health::{
+    estimate_battery_life, estimate_time, health_summary, session_energy, BatteryLifeEstimate,
+    EnergySnapshot, HealthSummary, HealthThresholds, LifeThresholds, SessionEnergy, TimeEstimate,
+    TimeEstimateConfig,
+};
+#[cfg(feature = "historian")]
+use crate::historian::Historian;
+use crate::historian::{HistorianStats, HistoryPoint, DEFAULT_HISTORY_POINTS};
+use crate::history::{Sample, SampleHistory};
+#[cfg(feature = "http")]
+use crate::http_server::HttpServer;
+use crate::i18n::{
+    alarm_descriptions, operation_status_name, prohibition_names, system_status_name,
+    unknown_status_name, work_status_name, Language,
+};
+#[cfg(feature = "modbus")]
+use crate::modbus::ModbusServer;
+use crate::mqtt::MqttConfig;
+#[cfg(feature = "mqtt")]
+use crate::mqtt::MqttPublisher;
+use crate::report::format_report;
+use crate::snapshot::{SnapshotDiff, SnapshotStore};
+use crate::units::{BmsDataDisplay, UnitSystem};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tauri::State;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::watch;
 
 /// Application state
+///
+/// All fields are shared handles, so clones refer to the same state. The single
+/// `CanManager` in `can_manager` owns the adapter; queries and the receive loop
+/// both go through it.
+#[derive(Clone)]
 pub struct AppState {
     pub bms_data: Arc<Mutex<BmsData>>,
-    pub can_manager: Arc<Mutex<Option<CanManager>>>,
+    pub can_manager: SharedManager,
     pub config: Arc<Mutex<CanConfig>>,
+    /// Running flag of the background receive loop
+    pub receiving: Arc<Mutex<bool>>,
+    pub receive_thread: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// Recent samples for trend rendering
+    pub sample_history: Arc<Mutex<SampleHistory>>,
+    /// Active JSON-lines frame capture
+    pub frame_capture: Arc<Mutex<Option<FrameCapture>>>,
+    /// Handle for emitting frontend events, set once the app is running
+    pub app_handle: Arc<Mutex<Option<AppHandle>>>,
+    /// Edge detection for `critical-alarm` events, per BMS address
+    pub critical_alarms: Arc<Mutex<HashMap<u8, CriticalAlarmWatch>>>,
+    /// Operator acknowledgements of active alarms
+    pub alarm_acks: Arc<Mutex<AlarmAcknowledgements>>,
+    /// Opt-in command/webhook run for newly raised critical alarms
+    pub alarm_action: Arc<Mutex<Option<AlarmActionHook>>>,
+    /// Debounced charge/discharge direction
+    pub direction: Arc<Mutex<DirectionFilter>>,
+    /// Latest parsed snapshot for lock-free consumers, see `subscribe_data`
+    pub data_snapshots: Arc<watch::Sender<BmsData>>,
+    /// Energy totals at the last counter reset, kept across reconnects but
+    /// only in memory, so a restart starts without a session
+    pub energy_snapshot: Arc<Mutex<Option<EnergySnapshot>>>,
+    /// Last `connection-state` event, for deduplication
+    pub connection_state: Arc<Mutex<ConnectionStateTracker>>,
+    /// Liveness from the version heartbeat, see `start_heartbeat`
+    pub heartbeat: Arc<Mutex<HeartbeatMonitor>>,
+    /// Running flag of the heartbeat loop
+    pub heartbeat_running: Arc<Mutex<bool>>,
+    pub heartbeat_thread: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// Cancel flag of the active manager, set by `disconnect`
+    pub query_cancel: Arc<AtomicBool>,
+    /// Data states captured by `snapshot_bms`
+    pub snapshots: Arc<Mutex<SnapshotStore>>,
+    #[cfg(feature = "historian")]
+    pub historian: Arc<Mutex<Option<Historian>>>,
+    #[cfg(feature = "mqtt")]
+    pub mqtt_publisher: Arc<Mutex<Option<MqttPublisher>>>,
+    #[cfg(feature = "modbus")]
+    pub modbus_server: Arc<Mutex<Option<ModbusServer>>>,
+    #[cfg(feature = "http")]
+    pub http_server: Arc<Mutex<Option<HttpServer>>>,
 }
 
 impl Default for AppState {
@@ -26,10 +117,530 @@ impl AppState {
             bms_data: Arc::new(Mutex::new(BmsData::default())),
             can_manager: Arc::new(Mutex::new(None)),
             config: Arc::new(Mutex::new(CanConfig::default())),
+            receiving: Arc::new(Mutex::new(false)),
+            receive_thread: Arc::new(Mutex::new(None)),
+            sample_history: Arc::new(Mutex::new(SampleHistory::default())),
+            frame_capture: Arc::new(Mutex::new(None)),
+            app_handle: Arc::new(Mutex::new(None)),
+            critical_alarms: Arc::new(Mutex::new(HashMap::new())),
+            alarm_acks: Arc::new(Mutex::new(AlarmAcknowledgements::new())),
+            alarm_action: Arc::new(Mutex::new(None)),
+            direction: Arc::new(Mutex::new(DirectionFilter::new(DirectionConfig::default()))),
+            data_snapshots: Arc::new(watch::Sender::new(BmsData::default())),
+            energy_snapshot: Arc::new(Mutex::new(None)),
+            connection_state: Arc::new(Mutex::new(ConnectionStateTracker::new())),
+            heartbeat: Arc::new(Mutex::new(HeartbeatMonitor::default())),
+            heartbeat_running: Arc::new(Mutex::new(false)),
+            heartbeat_thread: Arc::new(Mutex::new(None)),
+            query_cancel: Arc::new(AtomicBool::new(false)),
+            snapshots: Arc::new(Mutex::new(SnapshotStore::new())),
+            #[cfg(feature = "historian")]
+            historian: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "mqtt")]
+            mqtt_publisher: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "modbus")]
+            modbus_server: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "http")]
+            http_server: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Set the handle used to emit frontend events
+    pub fn set_app_handle(&self, handle: AppHandle) {
+        *self.app_handle.lock() = Some(handle);
+    }
+
+    /// Subscribe to data snapshots published after each parsed frame
+    ///
+    /// Receivers only ever see the latest snapshot, so a slow consumer never
+    /// holds up the receive loop or the `bms_data` lock.
+    pub fn subscribe_data(&self) -> watch::Receiver<BmsData> {
+        self.data_snapshots.subscribe()
+    }
+
+    /// Set or (with `None`) remove the critical alarm action
+    pub fn set_alarm_action(
+        &self,
+        action: Option<AlarmAction>,
+        debounce: Duration,
+    ) -> Result<(), String> {
+        let hook = action
+            .map(|action| AlarmActionHook::new(action, debounce))
+            .transpose()?;
+        *self.alarm_action.lock() = hook;
+        Ok(())
+    }
+
+    /// Create a manager wired to the shared data, history and telemetry outputs
+    ///
+    /// Critical alarms and MQTT cover every polled BMS; snapshots,
+    /// acknowledgements and the current direction follow the primary one,
+    /// which is what `bms_data` holds.
+    pub fn new_manager(&self, config: CanConfig) -> CanManager {
+        let primary = config.bms_address;
+        let mut manager = CanManager::new_with_mutex(config, self.bms_data.clone());
+        manager.set_cancel_flag(self.query_cancel.clone());
+
+        let critical_alarms = self.critical_alarms.clone();
+        let alarm_acks = self.alarm_acks.clone();
+        let alarm_action = self.alarm_action.clone();
+        let direction = self.direction.clone();
+        let app_handle = self.app_handle.clone();
+        let snapshots = self.data_snapshots.clone();
+        #[cfg(feature = "mqtt")]
+        let publisher = self.mqtt_publisher.clone();
+        manager.set_update_hook(Arc::new(move |bms_address: u8, data: &BmsData| {
+            if bms_address == primary {
+                snapshots.send_replace(data.clone());
+                alarm_acks.lock().update(data.alarm_status.as_ref());
+                if let (Some(vc), Some(&at)) = (
+                    data.voltage_current.as_ref(),
+                    data.updated_at.get(&BmsCommand::VoltageCurrent),
+                ) {
+                    direction.lock().update(vc.current, at);
+                }
+            }
+
+            let raised = critical_alarms
+                .lock()
+                .entry(bms_address)
+                .or_default()
+                .update(data.alarm_status.as_ref());
+            for alarm in raised {
+                log::warn!(
+                    "Critical alarm raised on BMS 0x{:02X}: {} (bit {})",
+                    bms_address,
+                    alarm.description,
+                    alarm.bit
+                );
+                if let Some(ref mut hook) = *alarm_action.lock() {
+                    hook.trigger(&alarm, Instant::now());
+                }
+                if let Some(ref handle) = *app_handle.lock() {
+                    let _ = handle.emit(CRITICAL_ALARM_EVENT, alarm);
+                }
+            }
+
+            #[cfg(feature = "mqtt")]
+            if let Some(ref publisher) = *publisher.lock() {
+                publisher.publish(bms_address, data);
+            }
+        }));
+
+        let capture = self.frame_capture.clone();
+        manager.set_frame_hook(Arc::new(move |frame: &CanFrame| {
+            if let Some(ref capture) = *capture.lock() {
+                capture.record(frame);
+            }
+        }));
+
+        manager
+    }
+
+    /// Connect a new manager and store its config as the active one
+    ///
+    /// An already active manager is disconnected first (receive loop and
+    /// heartbeat stopped, port closed), so connecting again acts as a
+    /// reconnect. If the new connect then fails, the app stays disconnected.
+    pub fn connect(&self, config: ConnectionConfig) -> Result<(), CanError> {
+        let can_config = config.to_can_config(&self.config.lock())?;
+        if let Err(e) = self.disconnect() {
+            log::warn!("Closing the previous connection failed: {}", e);
+        }
+
+        let mut manager = self.new_manager(can_config.clone());
+        self.critical_alarms.lock().clear();
+        self.alarm_acks.lock().clear();
+        self.direction.lock().reset();
+
+        manager.connect()?;
+        *self.can_manager.lock() = Some(manager);
+        self.emit_connection_state(true, can_config.adapter_type, ConnectionReason::Connected);
+        *self.config.lock() = can_config;
+        Ok(())
+    }
+
+    /// Stop receiving and disconnect the active manager, if any
+    pub fn disconnect(&self) -> Result<(), CanError> {
+        // Abort a query in progress so the manager lock is released promptly
+        self.query_cancel.store(true, Ordering::SeqCst);
+        self.stop_receiving();
+        self.stop_heartbeat();
+        self.heartbeat.lock().reset();
+
+        let Some(mut manager) = self.can_manager.lock().take() else {
+            return Ok(());
+        };
+        let result = manager.disconnect();
+        if self.config.lock().clear_data_on_disconnect {
+            self.clear_bms_data();
+        }
+        self.emit_connection_state(
+            false,
+            manager.adapter_type(),
+            ConnectionReason::Disconnected,
+        );
+        result
+    }
+
+    /// Reset the shared data to defaults, keeping the `connected` flag
+    ///
+    /// Frames are parsed under the `bms_data` lock, so an in-flight frame is
+    /// either fully applied before the reset or lands on the cleared data.
+    pub fn clear_bms_data(&self) {
+        let mut data = self.bms_data.lock();
+        *data = BmsData {
+            connected: data.connected,
+            ..Default::default()
+        };
+        self.data_snapshots.send_replace(data.clone());
+    }
+
+    /// Emit `connection-state` if the connected state changed
+    fn emit_connection_state(
+        &self,
+        connected: bool,
+        adapter_type: AdapterType,
+        reason: ConnectionReason,
+    ) {
+        emit_connection_event(
+            &self.connection_state,
+            &self.app_handle,
+            ConnectionEvent {
+                connected,
+                adapter_type,
+                reason,
+            },
+        );
+    }
+
+    /// Active alarms with their acknowledgement state
+    pub fn active_alarms_detailed(&self) -> Vec<AlarmDetail> {
+        self.alarm_details(&self.bms_data.lock())
+    }
+
+    /// Active alarms of `data` with their acknowledgement state
+    fn alarm_details(&self, data: &BmsData) -> Vec<AlarmDetail> {
+        let mut acks = self.alarm_acks.lock();
+        acks.update(data.alarm_status.as_ref());
+
+        let mut details = active_alarm_details(data);
+        for detail in &mut details {
+            detail.acknowledged = acks.is_acknowledged(detail.bit);
+        }
+        details
+    }
+
+    /// Acknowledge `bit` if it is currently active
+    pub fn acknowledge_alarm(&self, bit: u8) -> bool {
+        let data = self.bms_data.lock();
+        self.alarm_acks
+            .lock()
+            .acknowledge(bit, data.alarm_status.as_ref())
+    }
+
+    /// Connect a manager from the stored config if none is active
+    fn ensure_manager(&self) -> Result<(), CanError> {
+        let mut guard = self.can_manager.lock();
+        if guard.is_none() {
+            let config = self.config.lock().clone();
+            let adapter_type = config.adapter_type;
+            let mut manager = self.new_manager(config);
+            manager.connect()?;
+            *guard = Some(manager);
+            drop(guard);
+            self.emit_connection_state(true, adapter_type, ConnectionReason::Connected);
+        }
+        Ok(())
+    }
+
+    /// Run `f` against the active manager, connecting one first if needed
+    fn with_manager<T>(
+        &self,
+        f: impl FnOnce(&mut CanManager) -> Result<T, CanError>,
+    ) -> Result<T, CanError> {
+        self.ensure_manager()?;
+        match self.can_manager.lock().as_mut() {
+            Some(manager) => f(manager),
+            None => Err(CanError::DeviceNotFound("Not connected".to_string())),
+        }
+    }
+
+    /// Query all BMS data through the active manager (blocking)
+    pub fn query_all_data(&self) -> Result<Vec<BmsCommand>, CanError> {
+        let missing = self.with_manager(|m| m.query_all_data())?;
+        self.record_cycle();
+        Ok(missing)
+    }
+
+    /// Add the data of a completed query cycle to the trend history and historian
+    ///
+    /// Done once per cycle, not from the per-frame update hook, so one poll
+    /// adds one sample however many frames it received.
+    fn record_cycle(&self) {
+        let data = self.bms_data.lock();
+        self.sample_history.lock().push(Sample::from_data(&data));
+        #[cfg(feature = "historian")]
+        if let Some(ref historian) = *self.historian.lock() {
+            historian.record(&data);
+        }
+    }
+
+    /// Query the static commands through the active manager (blocking)
+    pub fn refresh_static_data(&self) -> Result<Vec<BmsCommand>, CanError> {
+        self.with_manager(|m| m.refresh_static_data())
+    }
+
+    /// Data of every polled BMS by address; just the primary one while disconnected
+    pub fn all_bms_data(&self) -> BTreeMap<u8, BmsData> {
+        if let Some(manager) = self.can_manager.lock().as_ref() {
+            return manager.all_bms_data();
+        }
+        let address = self.config.lock().bms_address;
+        BTreeMap::from([(address, self.bms_data.lock().clone())])
+    }
+
+    /// Query only the given commands through the active manager (blocking)
+    pub fn query_commands(&self, commands: &[BmsCommand]) -> Result<Vec<BmsCommand>, CanError> {
+        self.with_manager(|m| m.query_commands(commands))
+    }
+
+    /// Query the next cycle of the polling plan (blocking)
+    pub fn poll(&self) -> Result<Vec<BmsCommand>, CanError> {
+        let missing = self.with_manager(|m| m.poll())?;
+        self.record_cycle();
+        Ok(missing)
+    }
+
+    /// Set the polling plan for the active and future managers
+    pub fn set_query_plan(&self, plan: QueryPlan) {
+        if let Some(manager) = self.can_manager.lock().as_mut() {
+            manager.set_query_plan(plan.clone());
+        }
+        self.config.lock().query_plan = plan;
+    }
+
+    /// Send a raw frame through the active manager
+    pub fn send_raw_frame(&self, id: u32, data: Vec<u8>) -> Result<CanFrame, CanError> {
+        self.with_manager(|m| m.send_raw_frame(id, data))
+    }
+
+    /// Write charge/discharge limits through the active manager
+    pub fn set_charge_discharge_limits(
+        &self,
+        limits: &ChargeDischargeLimits,
+    ) -> Result<CanFrame, CanError> {
+        self.with_manager(|m| m.set_charge_discharge_limits(limits))
+    }
+
+    /// Snapshot the current 0x87 totals as the start of a new energy session
+    pub fn reset_energy_counters(&self) -> Result<EnergySnapshot, String> {
+        let data = self.bms_data.lock();
+        let Some(ref totals) = data.accumulated_power else {
+            return Err("No accumulated power (0x87) received yet".to_string());
+        };
+        let snapshot = EnergySnapshot::new(totals, chrono::Utc::now().timestamp_millis());
+        *self.energy_snapshot.lock() = Some(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Energy since the last `reset_energy_counters`
+    pub fn session_energy(&self) -> Option<SessionEnergy> {
+        let data = self.bms_data.lock();
+        let snapshot = self.energy_snapshot.lock();
+        Some(session_energy(
+            snapshot.as_ref()?,
+            data.accumulated_power.as_ref()?,
+        ))
+    }
+
+    /// Capture the current data for a later `diff_snapshot`
+    pub fn snapshot_bms(&self) -> u64 {
+        let data = self.bms_data.lock();
+        self.snapshots.lock().take(&data)
+    }
+
+    /// Changes from snapshot `id` to the current data
+    pub fn diff_snapshot(&self, id: u64) -> Result<SnapshotDiff, String> {
+        let data = self.bms_data.lock();
+        self.snapshots.lock().diff(id, &data)
+    }
+
+    /// Link state next to BMS-reported internal comms faults
+    pub fn communication_status(&self, stale_after_ms: i64) -> CommunicationStatus {
+        let connected = self
+            .can_manager
+            .lock()
+            .as_ref()
+            .is_some_and(|m| m.is_connected());
+        communication_status(
+            &self.bms_data.lock(),
+            connected,
+            chrono::Utc::now().timestamp_millis(),
+            stale_after_ms,
+        )
+    }
+
+    /// Everything one dashboard refresh needs
+    ///
+    /// The manager is released before `bms_data` is locked (the receive path
+    /// takes them in that order); data, alarms and staleness then come from a
+    /// single `bms_data` lock, so they are consistent with each other.
+    pub fn dashboard_snapshot(&self, stale_after_ms: i64) -> DashboardSnapshot {
+        let (connected, stats) = match self.can_manager.lock().as_ref() {
+            Some(m) => (m.is_connected(), m.stats()),
+            None => (false, CanStats::default()),
+        };
+
+        let data = self.bms_data.lock();
+        let now = chrono::Utc::now().timestamp_millis();
+        DashboardSnapshot {
+            alarms: self.alarm_details(&data),
+            stale: data
+                .last_received_ms()
+                .is_none_or(|received| now - received > stale_after_ms),
+            data: data.clone(),
+            connected,
+            stats,
+        }
+    }
+
+    /// Query a single command through the active manager (blocking)
+    pub fn query_single(&self, command: BmsCommand) -> Result<CommandData, CanError> {
+        self.with_manager(|m| m.query_single(command))
+    }
+
+    /// Measure version query round trips through the active manager (blocking)
+    pub fn ping(&self, count: u32) -> Result<PingStats, CanError> {
+        self.with_manager(|m| m.ping(count))
+    }
+
+    /// Spawn the background receive loop against the active manager
+    pub fn start_receiving(&self) -> Result<(), String> {
+        let mut receive_thread = self.receive_thread.lock();
+        if receive_thread.as_ref().is_some_and(|t| !t.is_finished()) {
+            return Err("Already receiving".to_string());
+        }
+
+        self.ensure_manager().map_err(|e| e.to_string())?;
+        *self.receiving.lock() = true;
+
+        let connection_state = self.connection_state.clone();
+        let app_handle = self.app_handle.clone();
+        let mut link = LinkMonitor::new(LINK_DROP_DEBOUNCE);
+        let handle = spawn_receive_loop(
+            self.can_manager.clone(),
+            self.receiving.clone(),
+            move |result, adapter_type| {
+                let now = Instant::now();
+                let change = match result {
+                    Err(e) if e.is_fatal() => link.device_lost(now),
+                    result => link.update(result.is_ok(), now),
+                };
+                let Some(connected) = change else {
+                    return;
+                };
+                let reason = if connected {
+                    ConnectionReason::LinkRestored
+                } else {
+                    ConnectionReason::LinkLost
+                };
+                emit_connection_event(
+                    &connection_state,
+                    &app_handle,
+                    ConnectionEvent {
+                        connected,
+                        adapter_type,
+                        reason,
+                    },
+                );
+            },
+        )
+        .map_err(|e| e.to_string())?;
+
+        *receive_thread = Some(handle);
+        Ok(())
+    }
+
+    /// Stop the background receive loop and wait for it to release the port
+    pub fn stop_receiving(&self) {
+        *self.receiving.lock() = false;
+        if let Some(handle) = self.receive_thread.lock().take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// Send a version (0x8F) query every `interval` to track BMS liveness
+    ///
+    /// Much lighter than `query_all_data`; only the version data is refreshed.
+    pub fn start_heartbeat(&self, interval: Duration) -> Result<(), String> {
+        let interval_ms = interval.as_millis() as u64;
+        if !HEARTBEAT_INTERVAL_RANGE_MS.contains(&interval_ms) {
+            return Err(format!(
+                "interval must be between {} and {} ms, got {}",
+                HEARTBEAT_INTERVAL_RANGE_MS.start(),
+                HEARTBEAT_INTERVAL_RANGE_MS.end(),
+                interval_ms
+            ));
+        }
+
+        let mut heartbeat_thread = self.heartbeat_thread.lock();
+        if heartbeat_thread.as_ref().is_some_and(|t| !t.is_finished()) {
+            return Err("Heartbeat already running".to_string());
+        }
+
+        self.ensure_manager().map_err(|e| e.to_string())?;
+        *self.heartbeat_running.lock() = true;
+
+        let state = self.clone();
+        let handle = std::thread::Builder::new()
+            .name("bms-heartbeat".to_string())
+            .spawn(move || {
+                while *state.heartbeat_running.lock() {
+                    let responded = state
+                        .with_manager(|m| m.query_single(BmsCommand::SoftwareVersion))
+                        .is_ok();
+                    if let Some(alive) = state.heartbeat.lock().update(responded) {
+                        log::info!("BMS heartbeat: {}", if alive { "alive" } else { "lost" });
+                    }
+
+                    // Sleep in short steps so stopping doesn't wait a full interval
+                    let next = Instant::now() + interval;
+                    while *state.heartbeat_running.lock() && Instant::now() < next {
+                        std::thread::sleep(Duration::from_millis(20));
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+
+        *heartbeat_thread = Some(handle);
+        Ok(())
+    }
+
+    /// Stop the heartbeat loop, keeping the last liveness
+    pub fn stop_heartbeat(&self) {
+        *self.heartbeat_running.lock() = false;
+        if let Some(handle) = self.heartbeat_thread.lock().take() {
+            let _ = handle.join();
         }
     }
 }
 
+/// Emit `event` to the frontend unless it repeats the last state
+fn emit_connection_event(
+    tracker: &Mutex<ConnectionStateTracker>,
+    app_handle: &Mutex<Option<AppHandle>>,
+    event: ConnectionEvent,
+) {
+    let Some(event) = tracker.lock().update(event) else {
+        return;
+    };
+    log::info!("Connection state: {:?}", event);
+    if let Some(ref handle) = *app_handle.lock() {
+        let _ = handle.emit(CONNECTION_STATE_EVENT, event);
+    }
+}
+
 /// Connection configuration from frontend
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectionConfig {
@@ -37,6 +648,149 @@ pub struct ConnectionConfig {
     pub serial_port: Option<String>,
     pub serial_baud_rate: Option<u32>,
     pub bms_address: Option<u8>,
+    pub host_address: Option<u8>,
+    pub unit_system: Option<UnitSystem>,
+    pub timestamp_source: Option<TimestampSource>,
+    pub bluetooth: Option<BluetoothConfig>,
+    pub send_delay_ms: Option<u64>,
+    pub receive_timeout_ms: Option<u64>,
+    pub endianness: Option<Endianness>,
+    pub current_convention: Option<CurrentConvention>,
+    pub static_query_every: Option<u32>,
+    pub frame_format: Option<FrameFormat>,
+    pub clear_data_on_disconnect: Option<bool>,
+    pub read_buffer_size: Option<usize>,
+    pub extra_channels: Option<Vec<ExtraChannel>>,
+    pub dedup_window_ms: Option<u64>,
+    pub min_firmware_version: Option<FirmwareVersion>,
+    pub pack_count: Option<u8>,
+    pub cells_per_pack: Option<u8>,
+    pub freeze_detection: Option<FreezeConfig>,
+}
+
+impl ConnectionConfig {
+    /// Build the adapter config; settings not sent by the frontend come from `current`
+    ///
+    /// Fails for an unknown `adapter_type`, or a USB-CAN adapter without a
+    /// `serial_port` (I+BT falls back to the first Bluetooth port).
+    pub fn to_can_config(self, current: &CanConfig) -> Result<CanConfig, CanError> {
+        let adapter_type = match self.adapter_type.as_str() {
+            "usb" => AdapterType::UsbCan,
+            "bluetooth" => AdapterType::BluetoothCan,
+            "itekon" => AdapterType::ItekonCan,
+            "simulation" => AdapterType::Simulation,
+            other => {
+                return Err(CanError::InvalidConfig(format!(
+                    "Unknown adapter type {:?}, expected usb, bluetooth, itekon or simulation",
+                    other
+                )))
+            }
+        };
+        let has_port = self
+            .serial_port
+            .as_deref()
+            .is_some_and(|p| !p.trim().is_empty());
+        if adapter_type == AdapterType::UsbCan && !has_port {
+            return Err(CanError::InvalidConfig(
+                "serial_port is required for the USB-CAN adapter".to_string(),
+            ));
+        }
+
+        Ok(CanConfig {
+            adapter_type,
+            serial_port: self.serial_port,
+            serial_baud_rate: self.serial_baud_rate.unwrap_or(115200),
+            can_baud_rate: CAN_BAUD_RATE,
+            socket_can_interface: None,
+            bms_address: self.bms_address.unwrap_or(0x01),
+            host_address: self
+                .host_address
+                .unwrap_or(crate::bms_parser::DEFAULT_HOST_ADDRESS),
+            unit_system: self.unit_system.unwrap_or_default(),
+            query_plan: current.query_plan.clone(),
+            static_query_every: self
+                .static_query_every
+                .unwrap_or(current.static_query_every),
+            timestamp_source: self.timestamp_source.unwrap_or_default(),
+            bluetooth: self.bluetooth.unwrap_or_else(|| current.bluetooth.clone()),
+            send_delay_ms: self.send_delay_ms,
+            receive_timeout_ms: self.receive_timeout_ms,
+            endianness: self.endianness.unwrap_or_default(),
+            current_convention: self
+                .current_convention
+                .unwrap_or(current.current_convention),
+            frame_format: self.frame_format.unwrap_or_default(),
+            clear_data_on_disconnect: self
+                .clear_data_on_disconnect
+                .unwrap_or(current.clear_data_on_disconnect),
+            read_buffer_size: self.read_buffer_size.unwrap_or(current.read_buffer_size),
+            extra_channels: self
+                .extra_channels
+                .unwrap_or_else(|| current.extra_channels.clone()),
+            min_firmware_version: self
+                .min_firmware_version
+                .unwrap_or(current.min_firmware_version),
+            freeze_detection: self.freeze_detection.unwrap_or(current.freeze_detection),
+            battery_layout: BatteryLayout {
+                pack_count: self.pack_count.or(current.battery_layout.pack_count),
+                cells_per_pack: self
+                    .cells_per_pack
+                    .or(current.battery_layout.cells_per_pack),
+            },
+            dedup_window_ms: self.dedup_window_ms,
+        })
+    }
+}
+
+/// Result of `get_dashboard_snapshot`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardSnapshot {
+    pub data: BmsData,
+    /// Same as `get_active_alarms_detailed`
+    pub alarms: Vec<AlarmDetail>,
+    pub connected: bool,
+    /// No data yet, or none decoded within `stale_after_ms`
+    pub stale: bool,
+    pub stats: CanStats,
+}
+
+/// A raw CAN id split into its protocol fields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedFrameId {
+    pub id: u32,
+    pub fields: ParsedFrameId,
+    /// Command, if the code is one the protocol defines
+    pub command_name: Option<BmsCommand>,
+}
+
+impl DecodedFrameId {
+    /// Decode a 29-bit id
+    pub fn from_id(id: u32) -> Result<Self, CanError> {
+        if id & !CAN_EXT_ID_MASK != 0 {
+            return Err(CanError::InvalidFrame(format!(
+                "id 0x{:08X} exceeds 29 bits",
+                id
+            )));
+        }
+        let fields = ParsedFrameId::from_id(id);
+        Ok(DecodedFrameId {
+            id,
+            command_name: BmsCommand::try_from(fields.command).ok(),
+            fields,
+        })
+    }
+}
+
+/// How long ago one command's data was last decoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataAge {
+    pub command: BmsCommand,
+    /// Time of the last decode in ms
+    pub updated_at: i64,
+    pub age_ms: i64,
 }
 
 /// Command result type
@@ -72,53 +826,94 @@ pub fn list_ports() -> CommandResult<Vec<String>> {
     CommandResult::ok(ports)
 }
 
+/// Get serial ports that look like Bluetooth (RFCOMM) links
+#[tauri::command]
+pub fn list_bluetooth_ports() -> CommandResult<Vec<String>> {
+    CommandResult::ok(CanManager::list_bluetooth_ports())
+}
+
+/// Get the adapters usable on this machine (ports, SocketCAN, iTEKON library)
+#[tauri::command]
+pub fn list_adapters() -> AdapterAvailability {
+    CanManager::list_adapters()
+}
+
 /// Connect to BMS via CAN adapter
 #[tauri::command]
 pub fn connect(config: ConnectionConfig, state: State<'_, AppState>) -> CommandResult<bool> {
-    let adapter_type = match config.adapter_type.as_str() {
-        "usb" => AdapterType::UsbCan,
-        "bluetooth" => AdapterType::BluetoothCan,
-        "itekon" => AdapterType::ItekonCan,
-        "simulation" => AdapterType::Simulation,
-        _ => AdapterType::UsbCan,
-    };
+    match state.inner().connect(config) {
+        Ok(_) => CommandResult::ok(true),
+        Err(e) => CommandResult::err(format!("Connection failed: {}", e)),
+    }
+}
 
-    let can_config = CanConfig {
-        adapter_type,
-        serial_port: config.serial_port,
-        serial_baud_rate: config.serial_baud_rate.unwrap_or(115200),
-        can_baud_rate: CAN_BAUD_RATE,
-        socket_can_interface: None,
-        bms_address: config.bms_address.unwrap_or(0x01),
-        host_address: 0x80,
-    };
+/// Get the configuration of the last successful connect
+#[tauri::command]
+pub fn get_active_config(state: State<'_, AppState>) -> CanConfig {
+    state.inner().config.lock().clone()
+}
 
-    let bms_data = state.inner().bms_data.clone();
-    let mut manager = CanManager::new_with_mutex(can_config.clone(), bms_data);
+/// Run the connection self-test (uses the last connect config if none is given)
+#[tauri::command]
+pub async fn run_diagnostics(
+    config: Option<ConnectionConfig>,
+    state: State<'_, AppState>,
+) -> Result<CommandResult<DiagnosticsReport>, ()> {
+    if state.inner().can_manager.lock().is_some() {
+        return Ok(CommandResult::err(
+            "Disconnect before running diagnostics".to_string(),
+        ));
+    }
 
-    match manager.connect() {
-        Ok(_) => {
-            *state.inner().can_manager.lock() = Some(manager);
-            *state.inner().config.lock() = can_config;
-            CommandResult::ok(true)
-        }
-        Err(e) => CommandResult::err(format!("Connection failed: {}", e)),
+    let current = state.inner().config.lock().clone();
+    let can_config = match config {
+        Some(config) => match config.to_can_config(&current) {
+            Ok(config) => config,
+            Err(e) => return Ok(CommandResult::err(format!("Invalid config: {}", e))),
+        },
+        None => current,
+    };
+
+    let result = tauri::async_runtime::spawn_blocking(move || run_self_test(&can_config)).await;
+    match result {
+        Ok(report) => Ok(CommandResult::ok(report)),
+        Err(e) => Ok(CommandResult::err(format!("Task failed: {}", e))),
     }
 }
 
 /// Disconnect from BMS
 #[tauri::command]
 pub fn disconnect(state: State<'_, AppState>) -> CommandResult<bool> {
-    if let Some(mut manager) = state.inner().can_manager.lock().take() {
-        match manager.disconnect() {
-            Ok(_) => CommandResult::ok(true),
-            Err(e) => CommandResult::err(format!("Disconnect failed: {}", e)),
-        }
-    } else {
-        CommandResult::ok(true)
+    match state.inner().disconnect() {
+        Ok(_) => CommandResult::ok(true),
+        Err(e) => CommandResult::err(format!("Disconnect failed: {}", e)),
+    }
+}
+
+/// Describe the active adapter; iTEKON adapters include their board info
+///
+/// Without a connection this describes the configured adapter.
+#[tauri::command]
+pub fn get_adapter_info(state: State<'_, AppState>) -> CommandResult<AdapterInfo> {
+    let result = match state.inner().can_manager.lock().as_ref() {
+        Some(manager) => manager.adapter_info(),
+        None => Ok(AdapterInfo::from_config(
+            &state.inner().config.lock(),
+            false,
+        )),
+    };
+    match result {
+        Ok(info) => CommandResult::ok(info),
+        Err(e) => CommandResult::err(format!("Reading adapter info failed: {}", e)),
     }
 }
 
+/// Get the last `connection-state` event (null before the first connect)
+#[tauri::command]
+pub fn get_connection_state(state: State<'_, AppState>) -> Option<ConnectionEvent> {
+    state.inner().connection_state.lock().last().cloned()
+}
+
 /// Check connection status
 #[tauri::command]
 pub fn is_connected(state: State<'_, AppState>) -> bool {
@@ -130,167 +925,1432 @@ pub fn is_connected(state: State<'_, AppState>) -> bool {
         .is_some_and(|m| m.is_connected())
 }
 
+/// Tell a dropped link (ours) apart from a BMS-internal comms fault
+///
+/// `stale_after_ms` defaults to 10s.
+#[tauri::command]
+pub fn get_communication_status(
+    stale_after_ms: Option<i64>,
+    state: State<'_, AppState>,
+) -> CommunicationStatus {
+    state
+        .inner()
+        .communication_status(stale_after_ms.unwrap_or(DEFAULT_STALE_AFTER_MS))
+}
+
 /// Get current BMS data
 #[tauri::command]
 pub fn get_bms_data(state: State<'_, AppState>) -> BmsData {
     state.inner().bms_data.lock().clone()
 }
 
-/// Query all BMS data (async to prevent blocking UI)
+/// Data of every polled BMS keyed by address, for multi-channel setups
 #[tauri::command]
-pub async fn query_all_data(state: State<'_, AppState>) -> Result<CommandResult<bool>, ()> {
-    let can_manager = state.inner().can_manager.clone();
-    let bms_data = state.inner().bms_data.clone();
-    let config = state.inner().config.lock().clone();
+pub fn get_all_bms_data(state: State<'_, AppState>) -> BTreeMap<u8, BmsData> {
+    state.inner().all_bms_data()
+}
 
-    // Run blocking operations in a separate thread
-    let result = tauri::async_runtime::spawn_blocking(move || {
-        let mut guard = can_manager.lock();
-        if let Some(ref mut manager) = *guard {
-            manager.query_all_data()
-        } else {
-            // If no manager, create temporary one for simulation
-            drop(guard); // Release lock before creating new manager
-            let mut temp_manager = CanManager::new_with_mutex(config, bms_data);
-            if temp_manager.connect().is_ok() {
-                let result = temp_manager.query_all_data();
-                // Store the manager for future use
-                *can_manager.lock() = Some(temp_manager);
-                result
-            } else {
-                Err(crate::can_handler::CanError::DeviceNotFound(
-                    "Not connected".to_string(),
-                ))
+/// Data, alarms, link flags and stats for one dashboard refresh
+///
+/// Replaces separate `get_bms_data`, `get_active_alarms_detailed`,
+/// `is_connected` and `get_can_stats` calls. `stale_after_ms` defaults to 10s.
+#[tauri::command]
+pub fn get_dashboard_snapshot(
+    stale_after_ms: Option<i64>,
+    state: State<'_, AppState>,
+) -> DashboardSnapshot {
+    state
+        .inner()
+        .dashboard_snapshot(stale_after_ms.unwrap_or(DEFAULT_STALE_AFTER_MS))
+}
+
+/// Reset the last received values so the UI shows a clean slate
+#[tauri::command]
+pub fn clear_bms_data(state: State<'_, AppState>) -> bool {
+    state.inner().clear_bms_data();
+    true
+}
+
+/// Get current BMS data converted to the configured unit system
+#[tauri::command]
+pub fn get_bms_data_display(state: State<'_, AppState>) -> BmsDataDisplay {
+    let units = state.inner().config.lock().unit_system;
+    BmsDataDisplay::new(&state.inner().bms_data.lock(), units)
+}
+
+/// Markdown summary of the current data for pasting into a support ticket
+#[tauri::command]
+pub fn export_report(state: State<'_, AppState>) -> String {
+    let now = chrono::Utc::now().timestamp_millis();
+    format_report(&state.inner().bms_data.lock(), now)
+}
+
+/// Get the most recent trend samples (oldest first)
+#[tauri::command]
+pub fn get_sample_history(limit: Option<usize>, state: State<'_, AppState>) -> Vec<Sample> {
+    state
+        .inner()
+        .sample_history
+        .lock()
+        .latest(limit.unwrap_or(usize::MAX))
+}
+
+/// Set how many trend samples are kept
+#[tauri::command]
+pub fn set_sample_history_capacity(capacity: usize, state: State<'_, AppState>) -> bool {
+    state.inner().sample_history.lock().set_capacity(capacity);
+    true
+}
+
+/// Start storing key metrics in the SQLite database at `path`
+#[tauri::command]
+pub fn start_historian(path: String, state: State<'_, AppState>) -> CommandResult<bool> {
+    #[cfg(feature = "historian")]
+    {
+        match Historian::open(&path) {
+            Ok(historian) => {
+                *state.inner().historian.lock() = Some(historian);
+                log::info!("Historian writing to {}", path);
+                CommandResult::ok(true)
             }
+            Err(e) => CommandResult::err(e),
         }
-    })
-    .await;
+    }
 
-    match result {
-        Ok(Ok(_)) => Ok(CommandResult::ok(true)),
-        Ok(Err(e)) => Ok(CommandResult::err(format!("Query failed: {}", e))),
-        Err(e) => Ok(CommandResult::err(format!("Task failed: {}", e))),
+    #[cfg(not(feature = "historian"))]
+    {
+        let _ = (path, state);
+        CommandResult::err("Historian support is not enabled in this build".to_string())
     }
 }
 
-/// Start continuous data reception (async)
+/// Stop storing metrics; the database file is kept
 #[tauri::command]
-pub async fn start_receiving(state: State<'_, AppState>) -> Result<CommandResult<bool>, ()> {
-    let bms_data = state.inner().bms_data.clone();
-    let config = state.inner().config.lock().clone();
-
-    tokio::spawn(async move {
-        let mut manager = CanManager::new_with_mutex(config, bms_data);
-        if manager.connect().is_ok() {
-            let _ = manager.start_receiving();
-        }
-    });
-
-    Ok(CommandResult::ok(true))
-}
-
-/// Get alarm descriptions
-#[tauri::command]
-pub fn get_alarm_descriptions() -> Vec<(u8, String, u8)> {
-    vec![
-        (0, "Cell over voltage".to_string(), 3),
-        (1, "Cell under voltage".to_string(), 3),
-        (2, "Charging over temperature alarm".to_string(), 2),
-        (3, "Charging low temperature alarm".to_string(), 2),
-        (4, "Discharging over temperature pre-alarm".to_string(), 2),
-        (5, "Discharging low temperature pre-alarm".to_string(), 2),
-        (6, "Discharging over current pre-alarm".to_string(), 2),
-        (7, "Charging over current pre-alarm".to_string(), 2),
-        (8, "Total over voltage pre-alarm".to_string(), 2),
-        (9, "Total under voltage warning".to_string(), 2),
-        (10, "Circuit breaker disconnected".to_string(), 1),
-        (11, "Balanced charging failed".to_string(), 1),
-        (12, "Positive battery pack voltage imbalance".to_string(), 1),
-        (13, "Negative battery pack voltage imbalance".to_string(), 1),
-        (14, "BMU communication interruption".to_string(), 3),
-        (15, "Water flooding detection alarm".to_string(), 1),
-        (16, "Water flooding detection and protection".to_string(), 1),
-        (18, "Charging over temperature protection".to_string(), 3),
-        (19, "Charging low temperature protection".to_string(), 3),
-        (20, "Discharging over temperature protection".to_string(), 3),
-        (21, "Discharging low temperature protection".to_string(), 3),
-        (
-            22,
-            "Discharging over current protection level 1".to_string(),
-            3,
-        ),
-        (
-            23,
-            "Discharging over current protection level 2".to_string(),
-            3,
-        ),
-        (
-            24,
-            "Charging over current protection level 1".to_string(),
-            3,
-        ),
-        (
-            25,
-            "Charging over current protection level 2".to_string(),
-            3,
-        ),
-        (
-            26,
-            "Charging over current protection level 3".to_string(),
-            3,
-        ),
-        (27, "Total charging over voltage protection".to_string(), 3),
-        (28, "Total charging under voltage protection".to_string(), 3),
-        (29, "Charging DC contactor failure".to_string(), 3),
-        (30, "Discharging DC contactor failure".to_string(), 3),
-        (31, "EPO shut down".to_string(), 3),
-        (32, "Fire protection".to_string(), 3),
-        (33, "Parallel communication abnormality".to_string(), 1),
-        (34, "Parallel address conflict".to_string(), 1),
-        (35, "Insulation monitoring alarm".to_string(), 1),
-        (36, "Hydrogen protection".to_string(), 1),
-        (37, "Battery pack fan malfunction".to_string(), 1),
-        (38, "Battery pack fuse temperature too high".to_string(), 1),
-        (39, "CAN Hall communication interruption".to_string(), 1),
-        (40, "CAN Hall data failure".to_string(), 1),
-    ]
+pub fn stop_historian(state: State<'_, AppState>) -> CommandResult<bool> {
+    #[cfg(feature = "historian")]
+    {
+        *state.inner().historian.lock() = None;
+        CommandResult::ok(true)
+    }
+
+    #[cfg(not(feature = "historian"))]
+    {
+        let _ = state;
+        CommandResult::err("Historian support is not enabled in this build".to_string())
+    }
 }
 
-/// Get system status description
+/// Stored metrics in `[from, to)` (Unix ms), averaged into at most `max_points` points
 #[tauri::command]
-pub fn get_system_status_name(status: u8) -> String {
-    match status {
-        0 => "Power On".to_string(),
-        1 => "Start".to_string(),
-        2 => "Alone".to_string(),
-        3 => "Charging".to_string(),
-        4 => "Discharging".to_string(),
-        5 => "Wait to Charge".to_string(),
-        6 => "Wait to Discharge".to_string(),
-        7 => "Lock".to_string(),
-        _ => "Unknown".to_string(),
+pub fn query_history(
+    from: i64,
+    to: i64,
+    max_points: Option<usize>,
+    state: State<'_, AppState>,
+) -> CommandResult<Vec<HistoryPoint>> {
+    let max_points = max_points.unwrap_or(DEFAULT_HISTORY_POINTS);
+
+    #[cfg(feature = "historian")]
+    {
+        match state.inner().historian.lock().as_ref() {
+            Some(historian) => match historian.query(from, to, max_points) {
+                Ok(points) => CommandResult::ok(points),
+                Err(e) => CommandResult::err(e),
+            },
+            None => CommandResult::err("Historian is not running".to_string()),
+        }
+    }
+
+    #[cfg(not(feature = "historian"))]
+    {
+        let _ = (from, to, max_points, state);
+        CommandResult::err("Historian support is not enabled in this build".to_string())
     }
 }
 
-/// Get work status description
+/// Rows the running historian dropped because its writer fell behind or failed
+#[tauri::command]
+pub fn get_historian_stats(state: State<'_, AppState>) -> CommandResult<HistorianStats> {
+    #[cfg(feature = "historian")]
+    {
+        match state.inner().historian.lock().as_ref() {
+            Some(historian) => CommandResult::ok(historian.stats()),
+            None => CommandResult::err("Historian is not running".to_string()),
+        }
+    }
+
+    #[cfg(not(feature = "historian"))]
+    {
+        let _ = state;
+        CommandResult::err("Historian support is not enabled in this build".to_string())
+    }
+}
+
+/// Start writing received frames to a JSON-lines file
 #[tauri::command]
-pub fn get_work_status_name(status: u8) -> String {
-    match status {
-        0 => "Empty".to_string(),
-        1 => "Boot".to_string(),
-        2 => "Shut Down".to_string(),
-        _ => "Unknown".to_string(),
+pub fn start_frame_capture(path: String, state: State<'_, AppState>) -> CommandResult<bool> {
+    let mut capture = state.inner().frame_capture.lock();
+    if capture.is_some() {
+        return CommandResult::err("Frame capture already running".to_string());
+    }
+
+    match FrameCapture::start(&path) {
+        Ok(started) => {
+            *capture = Some(started);
+            CommandResult::ok(true)
+        }
+        Err(e) => CommandResult::err(format!("Failed to create {}: {}", path, e)),
     }
 }
 
-/// Get operation status description
+/// Stop the frame capture; returns the number of frames dropped while busy
+#[tauri::command]
+pub fn stop_frame_capture(state: State<'_, AppState>) -> CommandResult<u64> {
+    let Some(mut capture) = state.inner().frame_capture.lock().take() else {
+        return CommandResult::err("Frame capture not running".to_string());
+    };
+
+    match capture.stop() {
+        Ok(()) => CommandResult::ok(capture.dropped()),
+        Err(e) => CommandResult::err(format!("Frame capture failed: {}", e)),
+    }
+}
+
+/// Get frame counters for the current connection
+#[tauri::command]
+pub fn get_can_stats(state: State<'_, AppState>) -> CanStats {
+    state
+        .inner()
+        .can_manager
+        .lock()
+        .as_ref()
+        .map(|m| m.stats())
+        .unwrap_or_default()
+}
+
+/// Get the response time of each command from its last query
+#[tauri::command]
+pub fn get_command_latencies(state: State<'_, AppState>) -> Vec<CommandLatency> {
+    state
+        .inner()
+        .can_manager
+        .lock()
+        .as_ref()
+        .map(|m| m.command_latencies())
+        .unwrap_or_default()
+}
+
+/// Get received frames/sec and estimated bus utilization
+#[tauri::command]
+pub fn get_bus_load(state: State<'_, AppState>) -> BusLoad {
+    state
+        .inner()
+        .can_manager
+        .lock()
+        .as_mut()
+        .map(|m| m.bus_load())
+        .unwrap_or_default()
+}
+
+/// Get BMS addresses seen on the bus that differ from the configured one
 #[tauri::command]
-pub fn get_operation_status_name(status: u8) -> String {
-    match status {
-        0 => "Empty".to_string(),
-        1 => "Normal".to_string(),
-        2 => "Alarm".to_string(),
-        3 => "Fault".to_string(),
-        _ => "Unknown".to_string(),
+pub fn get_detected_addresses(state: State<'_, AppState>) -> Vec<u8> {
+    state
+        .inner()
+        .can_manager
+        .lock()
+        .as_ref()
+        .map(|m| m.detected_addresses())
+        .unwrap_or_default()
+}
+
+/// Query all BMS data (async to prevent blocking UI)
+///
+/// Returns the commands that got no response.
+#[tauri::command]
+pub async fn query_all_data(
+    state: State<'_, AppState>,
+) -> Result<CommandResult<Vec<BmsCommand>>, ()> {
+    let app = state.inner().clone();
+
+    // Run blocking operations in a separate thread
+    let result = tauri::async_runtime::spawn_blocking(move || app.query_all_data()).await;
+
+    match result {
+        Ok(Ok(missing)) => Ok(CommandResult::ok(missing)),
+        Ok(Err(e)) => Ok(CommandResult::err(format!("Query failed: {}", e))),
+        Err(e) => Ok(CommandResult::err(format!("Task failed: {}", e))),
+    }
+}
+
+/// Query the software version and accumulated times now
+///
+/// `query_all_data` only refreshes them every `static_query_every` calls.
+/// Returns the commands that got no response.
+#[tauri::command]
+pub async fn refresh_static_data(
+    state: State<'_, AppState>,
+) -> Result<CommandResult<Vec<BmsCommand>>, ()> {
+    let app = state.inner().clone();
+    let result = tauri::async_runtime::spawn_blocking(move || app.refresh_static_data()).await;
+
+    match result {
+        Ok(Ok(missing)) => Ok(CommandResult::ok(missing)),
+        Ok(Err(e)) => Ok(CommandResult::err(format!("Query failed: {}", e))),
+        Err(e) => Ok(CommandResult::err(format!("Task failed: {}", e))),
+    }
+}
+
+/// Query only the given commands (async to prevent blocking UI)
+///
+/// Returns the commands that got no response.
+#[tauri::command]
+pub async fn query_commands(
+    commands: Vec<BmsCommand>,
+    state: State<'_, AppState>,
+) -> Result<CommandResult<Vec<BmsCommand>>, ()> {
+    let app = state.inner().clone();
+    let result = tauri::async_runtime::spawn_blocking(move || app.query_commands(&commands)).await;
+
+    match result {
+        Ok(Ok(missing)) => Ok(CommandResult::ok(missing)),
+        Ok(Err(e)) => Ok(CommandResult::err(format!("Query failed: {}", e))),
+        Err(e) => Ok(CommandResult::err(format!("Task failed: {}", e))),
+    }
+}
+
+/// Query one command by code (e.g. 0x81) and return just its data
+#[tauri::command]
+pub async fn query_command(
+    code: u8,
+    state: State<'_, AppState>,
+) -> Result<CommandResult<CommandData>, ()> {
+    let Ok(command) = BmsCommand::try_from(code) else {
+        return Ok(CommandResult::err(format!(
+            "Unknown command code 0x{:02X}",
+            code
+        )));
+    };
+
+    let app = state.inner().clone();
+    let result = tauri::async_runtime::spawn_blocking(move || app.query_single(command)).await;
+
+    match result {
+        Ok(Ok(data)) => Ok(CommandResult::ok(data)),
+        Ok(Err(e)) => Ok(CommandResult::err(format!("Query failed: {}", e))),
+        Err(e) => Ok(CommandResult::err(format!("Task failed: {}", e))),
+    }
+}
+
+/// Ping-like round-trip measurement with `count` version queries
+#[tauri::command]
+pub async fn ping_bms(
+    count: u32,
+    state: State<'_, AppState>,
+) -> Result<CommandResult<PingStats>, ()> {
+    let app = state.inner().clone();
+    let result = tauri::async_runtime::spawn_blocking(move || app.ping(count)).await;
+
+    match result {
+        Ok(Ok(stats)) => Ok(CommandResult::ok(stats)),
+        Ok(Err(e)) => Ok(CommandResult::err(format!("Ping failed: {}", e))),
+        Err(e) => Ok(CommandResult::err(format!("Task failed: {}", e))),
+    }
+}
+
+/// Query the next fast/slow cycle of the polling plan
+#[tauri::command]
+pub async fn poll_data(state: State<'_, AppState>) -> Result<CommandResult<Vec<BmsCommand>>, ()> {
+    let app = state.inner().clone();
+    let result = tauri::async_runtime::spawn_blocking(move || app.poll()).await;
+
+    match result {
+        Ok(Ok(missing)) => Ok(CommandResult::ok(missing)),
+        Ok(Err(e)) => Ok(CommandResult::err(format!("Query failed: {}", e))),
+        Err(e) => Ok(CommandResult::err(format!("Task failed: {}", e))),
+    }
+}
+
+/// Set which commands are polled every cycle and which only every Nth
+#[tauri::command]
+pub fn set_query_plan(plan: QueryPlan, state: State<'_, AppState>) -> CommandResult<bool> {
+    state.inner().set_query_plan(plan);
+    CommandResult::ok(true)
+}
+
+/// Change the log level at runtime ("off", "error", "warn", "info", "debug", "trace")
+#[tauri::command]
+pub fn set_log_level(level: String) -> CommandResult<String> {
+    match crate::logging::set_level(&level) {
+        Ok(filter) => CommandResult::ok(filter.to_string().to_lowercase()),
+        Err(e) => CommandResult::err(e),
+    }
+}
+
+/// Active log level
+#[tauri::command]
+pub fn get_log_level() -> String {
+    crate::logging::current_level().to_string().to_lowercase()
+}
+
+/// Code and name of every `BmsCommand`, e.g. for a custom query dropdown
+#[tauri::command]
+pub fn list_bms_commands() -> Vec<(u8, String)> {
+    BmsCommand::ALL
+        .iter()
+        .map(|&command| (command as u8, command.name()))
+        .collect()
+}
+
+/// Decode a raw CAN id into its fields, for protocol debugging
+#[tauri::command]
+pub fn decode_frame_id(id: u32) -> CommandResult<DecodedFrameId> {
+    match DecodedFrameId::from_id(id) {
+        Ok(decoded) => CommandResult::ok(decoded),
+        Err(e) => CommandResult::err(e.to_string()),
+    }
+}
+
+/// Decode pasted `(extended id, data)` frames into a fresh `BmsData`
+///
+/// For analysing customer hex dumps offline; the live connection and shared
+/// data are not touched. Payloads are read little-endian with positive
+/// current = discharge unless `endianness` / `current_convention` are given.
+#[tauri::command]
+pub fn parse_frames(
+    frames: Vec<(u32, Vec<u8>)>,
+    endianness: Option<Endianness>,
+    current_convention: Option<CurrentConvention>,
+) -> BmsData {
+    let order = endianness.unwrap_or_default();
+    let mut data = BmsData::default();
+    for (id, payload) in frames {
+        let frame = CanFrame {
+            id,
+            format: FrameFormat::Extended,
+            data: payload,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            hw_timestamp: None,
+        };
+        crate::bms_parser::parse_can_frame(&frame, &mut data, order);
+    }
+    if let Some(vc) = data.voltage_current.as_mut() {
+        current_convention.unwrap_or_default().apply(vc);
+    }
+    data
+}
+
+/// Build the raw CAN id for the given fields
+#[tauri::command]
+pub fn encode_frame_id(fields: ParsedFrameId) -> u32 {
+    fields.to_id()
+}
+
+/// Send an arbitrary CAN frame (at most 8 data bytes) for protocol debugging
+///
+/// Returns the frame that was sent. A logged no-op in simulation mode.
+#[tauri::command]
+pub fn send_raw_frame(
+    id: u32,
+    data: Vec<u8>,
+    state: State<'_, AppState>,
+) -> CommandResult<CanFrame> {
+    match state.inner().send_raw_frame(id, data) {
+        Ok(frame) => CommandResult::ok(frame),
+        Err(e) => CommandResult::err(format!("Send failed: {}", e)),
+    }
+}
+
+/// Write charge/discharge limits to the BMS (0x80)
+#[tauri::command]
+pub fn set_charge_discharge_limits(
+    limits: ChargeDischargeLimits,
+    state: State<'_, AppState>,
+) -> CommandResult<CanFrame> {
+    match state.inner().set_charge_discharge_limits(&limits) {
+        Ok(frame) => CommandResult::ok(frame),
+        Err(e) => CommandResult::err(format!("Write failed: {}", e)),
+    }
+}
+
+/// Start continuous data reception in the background
+#[tauri::command]
+pub async fn start_receiving(state: State<'_, AppState>) -> Result<CommandResult<bool>, ()> {
+    match state.inner().start_receiving() {
+        Ok(_) => Ok(CommandResult::ok(true)),
+        Err(e) => Ok(CommandResult::err(format!("Start receiving failed: {}", e))),
+    }
+}
+
+/// Stop continuous data reception
+#[tauri::command]
+pub fn stop_receiving(state: State<'_, AppState>) -> CommandResult<bool> {
+    state.inner().stop_receiving();
+    CommandResult::ok(true)
+}
+
+/// Start the version (0x8F) heartbeat used for liveness detection
+#[tauri::command]
+pub fn start_heartbeat(interval_ms: u64, state: State<'_, AppState>) -> CommandResult<bool> {
+    match state
+        .inner()
+        .start_heartbeat(Duration::from_millis(interval_ms))
+    {
+        Ok(_) => CommandResult::ok(true),
+        Err(e) => CommandResult::err(format!("Start heartbeat failed: {}", e)),
+    }
+}
+
+/// Stop the heartbeat
+#[tauri::command]
+pub fn stop_heartbeat(state: State<'_, AppState>) -> CommandResult<bool> {
+    state.inner().stop_heartbeat();
+    CommandResult::ok(true)
+}
+
+/// Whether the BMS answers heartbeats (null before the first result)
+#[tauri::command]
+pub fn get_link_alive(state: State<'_, AppState>) -> Option<bool> {
+    state.inner().heartbeat.lock().alive()
+}
+
+/// Set or clear the scripted scenario of the active simulation
+#[tauri::command]
+pub fn set_simulation_scenario(
+    scenario: Option<SimulationScenario>,
+    state: State<'_, AppState>,
+) -> CommandResult<bool> {
+    let mut guard = state.inner().can_manager.lock();
+    match guard.as_mut() {
+        Some(manager) if manager.adapter_type() == AdapterType::Simulation => {
+            manager.set_simulation_scenario(scenario);
+            CommandResult::ok(true)
+        }
+        _ => CommandResult::err("Simulation is not active".to_string()),
+    }
+}
+
+/// Speed up or slow down the active simulation (0 = as fast as possible)
+#[tauri::command]
+pub fn set_simulation_time_scale(scale: f32, state: State<'_, AppState>) -> CommandResult<bool> {
+    let mut guard = state.inner().can_manager.lock();
+    match guard.as_mut() {
+        Some(manager) => match manager.set_simulation_time_scale(scale) {
+            Ok(_) => CommandResult::ok(true),
+            Err(e) => CommandResult::err(e.to_string()),
+        },
+        None => CommandResult::err("Simulation is not active".to_string()),
+    }
+}
+
+/// Raise an alarm bit in the simulated alarm frames, for walking the alarm UI
+#[tauri::command]
+pub fn inject_simulated_alarm(bit: u8, state: State<'_, AppState>) -> CommandResult<bool> {
+    let mut guard = state.inner().can_manager.lock();
+    match guard.as_mut() {
+        Some(manager) => match manager.inject_simulated_alarm(bit) {
+            Ok(_) => CommandResult::ok(true),
+            Err(e) => CommandResult::err(e.to_string()),
+        },
+        None => CommandResult::err("Simulation is not active".to_string()),
+    }
+}
+
+/// Clear all alarms raised with `inject_simulated_alarm`
+#[tauri::command]
+pub fn clear_simulated_alarms(state: State<'_, AppState>) -> CommandResult<bool> {
+    let mut guard = state.inner().can_manager.lock();
+    match guard.as_mut() {
+        Some(manager) => match manager.clear_simulated_alarms() {
+            Ok(_) => CommandResult::ok(true),
+            Err(e) => CommandResult::err(e.to_string()),
+        },
+        None => CommandResult::err("Simulation is not active".to_string()),
+    }
+}
+
+/// Run a command or call a webhook whenever a critical alarm is raised
+///
+/// Off by default; `null` turns it off again. See `AlarmAction` for the
+/// placeholders and why no shell is involved. `debounce_ms` (default 60 s)
+/// is the minimum time between two runs for the same alarm bit.
+#[tauri::command]
+pub fn set_alarm_action(
+    action: Option<AlarmAction>,
+    debounce_ms: Option<u64>,
+    state: State<'_, AppState>,
+) -> CommandResult<bool> {
+    let debounce = debounce_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_ALARM_ACTION_DEBOUNCE);
+    match state.inner().set_alarm_action(action, debounce) {
+        Ok(()) => CommandResult::ok(true),
+        Err(e) => CommandResult::err(e),
+    }
+}
+
+/// The configured critical alarm action, if any
+#[tauri::command]
+pub fn get_alarm_action(state: State<'_, AppState>) -> Option<AlarmAction> {
+    state
+        .inner()
+        .alarm_action
+        .lock()
+        .as_ref()
+        .map(|hook| hook.action().clone())
+}
+
+/// Start publishing BMS data to an MQTT broker
+#[tauri::command]
+pub fn start_mqtt_publishing(
+    config: MqttConfig,
+    state: State<'_, AppState>,
+) -> CommandResult<bool> {
+    #[cfg(feature = "mqtt")]
+    {
+        match MqttPublisher::start(&config) {
+            Ok(publisher) => {
+                *state.inner().mqtt_publisher.lock() = Some(publisher);
+                CommandResult::ok(true)
+            }
+            Err(e) => CommandResult::err(format!("MQTT start failed: {}", e)),
+        }
+    }
+
+    #[cfg(not(feature = "mqtt"))]
+    {
+        let _ = (config, state);
+        CommandResult::err("MQTT support is not enabled in this build".to_string())
+    }
+}
+
+/// Stop publishing BMS data to MQTT
+#[tauri::command]
+pub fn stop_mqtt_publishing(state: State<'_, AppState>) -> CommandResult<bool> {
+    #[cfg(feature = "mqtt")]
+    {
+        if let Some(publisher) = state.inner().mqtt_publisher.lock().take() {
+            publisher.stop();
+        }
+        CommandResult::ok(true)
+    }
+
+    #[cfg(not(feature = "mqtt"))]
+    {
+        let _ = state;
+        CommandResult::err("MQTT support is not enabled in this build".to_string())
+    }
+}
+
+/// Start the Modbus TCP server on the given port
+#[tauri::command]
+pub async fn start_modbus_server(
+    port: u16,
+    state: State<'_, AppState>,
+) -> Result<CommandResult<bool>, ()> {
+    #[cfg(feature = "modbus")]
+    {
+        let bms_data = state.inner().bms_data.clone();
+        match ModbusServer::start(port, bms_data).await {
+            Ok(server) => {
+                if let Some(old) = state.inner().modbus_server.lock().replace(server) {
+                    old.stop();
+                }
+                Ok(CommandResult::ok(true))
+            }
+            Err(e) => Ok(CommandResult::err(format!("Modbus start failed: {}", e))),
+        }
+    }
+
+    #[cfg(not(feature = "modbus"))]
+    {
+        let _ = (port, state);
+        Ok(CommandResult::err(
+            "Modbus support is not enabled in this build".to_string(),
+        ))
+    }
+}
+
+/// Stop the Modbus TCP server
+#[tauri::command]
+pub fn stop_modbus_server(state: State<'_, AppState>) -> CommandResult<bool> {
+    #[cfg(feature = "modbus")]
+    {
+        if let Some(server) = state.inner().modbus_server.lock().take() {
+            server.stop();
+        }
+        CommandResult::ok(true)
+    }
+
+    #[cfg(not(feature = "modbus"))]
+    {
+        let _ = state;
+        CommandResult::err("Modbus support is not enabled in this build".to_string())
+    }
+}
+
+/// Start the HTTP JSON API on the given port
+#[tauri::command]
+pub async fn start_http_server(
+    port: u16,
+    state: State<'_, AppState>,
+) -> Result<CommandResult<bool>, ()> {
+    #[cfg(feature = "http")]
+    {
+        let bms_data = state.inner().bms_data.clone();
+        match HttpServer::start(port, bms_data).await {
+            Ok(server) => {
+                if let Some(old) = state.inner().http_server.lock().replace(server) {
+                    old.stop();
+                }
+                Ok(CommandResult::ok(true))
+            }
+            Err(e) => Ok(CommandResult::err(format!("HTTP start failed: {}", e))),
+        }
+    }
+
+    #[cfg(not(feature = "http"))]
+    {
+        let _ = (port, state);
+        Ok(CommandResult::err(
+            "HTTP API support is not enabled in this build".to_string(),
+        ))
+    }
+}
+
+/// Stop the HTTP JSON API
+#[tauri::command]
+pub fn stop_http_server(state: State<'_, AppState>) -> CommandResult<bool> {
+    #[cfg(feature = "http")]
+    {
+        if let Some(server) = state.inner().http_server.lock().take() {
+            server.stop();
+        }
+        CommandResult::ok(true)
+    }
+
+    #[cfg(not(feature = "http"))]
+    {
+        let _ = state;
+        CommandResult::err("HTTP API support is not enabled in this build".to_string())
+    }
+}
+
+/// Ages of the decoded commands in `data` relative to `now_ms`, oldest first
+pub fn data_ages(data: &BmsData, now_ms: i64) -> Vec<DataAge> {
+    let mut ages: Vec<DataAge> = data
+        .updated_at
+        .iter()
+        .map(|(&command, &updated_at)| DataAge {
+            command,
+            updated_at,
+            age_ms: now_ms - updated_at,
+        })
+        .collect();
+    ages.sort_by_key(|age| age.updated_at);
+    ages
+}
+
+/// Get the currently active alarms with descriptions and severity
+#[tauri::command]
+pub fn get_active_alarms_detailed(state: State<'_, AppState>) -> Vec<AlarmDetail> {
+    state.inner().active_alarms_detailed()
+}
+
+/// Capture the current BMS data; returns the snapshot id
+#[tauri::command]
+pub fn snapshot_bms(state: State<'_, AppState>) -> u64 {
+    state.inner().snapshot_bms()
+}
+
+/// Compare a snapshot from `snapshot_bms` with the current data
+#[tauri::command]
+pub fn diff_snapshot(id: u64, state: State<'_, AppState>) -> CommandResult<SnapshotDiff> {
+    match state.inner().diff_snapshot(id) {
+        Ok(diff) => CommandResult::ok(diff),
+        Err(e) => CommandResult::err(e),
+    }
+}
+
+/// Get the age of each part of the BMS data, oldest first
+#[tauri::command]
+pub fn get_data_ages(state: State<'_, AppState>) -> Vec<DataAge> {
+    data_ages(
+        &state.inner().bms_data.lock(),
+        chrono::Utc::now().timestamp_millis(),
+    )
+}
+
+/// Get the debounced charge/discharge direction (null before the first 0x82)
+#[tauri::command]
+pub fn get_current_direction(state: State<'_, AppState>) -> Option<CurrentDirection> {
+    state.inner().direction.lock().direction()
+}
+
+/// Set the deadband and dwell time of the direction indicator
+#[tauri::command]
+pub fn set_direction_config(
+    config: DirectionConfig,
+    state: State<'_, AppState>,
+) -> CommandResult<bool> {
+    state.inner().direction.lock().set_config(config);
+    CommandResult::ok(true)
+}
+
+/// Acknowledge a currently active alarm
+///
+/// The acknowledgement is cleared automatically once the alarm falls.
+#[tauri::command]
+pub fn acknowledge_alarm(bit: u8, state: State<'_, AppState>) -> CommandResult<bool> {
+    if state.inner().acknowledge_alarm(bit) {
+        CommandResult::ok(true)
+    } else {
+        CommandResult::err(format!("Alarm bit {} is not active", bit))
+    }
+}
+
+/// Estimate remaining battery life from SOH and cycle count
+///
+/// Uses the default end-of-life thresholds unless `thresholds` is given.
+#[tauri::command]
+pub fn get_battery_life_estimate(
+    state: State<'_, AppState>,
+    thresholds: Option<LifeThresholds>,
+) -> BatteryLifeEstimate {
+    estimate_battery_life(
+        &state.inner().bms_data.lock(),
+        &thresholds.unwrap_or_default(),
+    )
+}
+
+/// Estimate minutes to full (charging) or to empty (discharging)
+///
+/// Uses a 280Ah pack unless `config` gives the nominal capacity.
+#[tauri::command]
+pub fn get_time_estimate(
+    state: State<'_, AppState>,
+    config: Option<TimeEstimateConfig>,
+) -> TimeEstimate {
+    estimate_time(&state.inner().bms_data.lock(), &config.unwrap_or_default())
+}
+
+/// Start a new energy session from the current 0x87 totals
+///
+/// The snapshot is not saved to disk; it is lost when the app restarts.
+#[tauri::command]
+pub fn reset_energy_counters(state: State<'_, AppState>) -> CommandResult<EnergySnapshot> {
+    match state.inner().reset_energy_counters() {
+        Ok(snapshot) => CommandResult::ok(snapshot),
+        Err(e) => CommandResult::err(e),
+    }
+}
+
+/// Energy charged/discharged since the last `reset_energy_counters`
+///
+/// Null until counters have been reset since the app started and 0x87 data
+/// is available.
+#[tauri::command]
+pub fn get_session_energy(state: State<'_, AppState>) -> Option<SessionEnergy> {
+    state.inner().session_energy()
+}
+
+/// Get the derived health summary (life estimate and sensor plausibility)
+///
+/// Flags are app-side heuristics, not BMS alarms. Uses the default
+/// thresholds unless `thresholds` is given.
+#[tauri::command]
+pub fn get_health_summary(
+    state: State<'_, AppState>,
+    thresholds: Option<HealthThresholds>,
+) -> HealthSummary {
+    health_summary(
+        &state.inner().bms_data.lock(),
+        &thresholds.unwrap_or_default(),
+    )
+}
+
+/// Get alarm descriptions (English unless `language` is given)
+#[tauri::command]
+pub fn get_alarm_descriptions(language: Option<Language>) -> Vec<(u8, &'static str, u8)> {
+    alarm_descriptions(language.unwrap_or_default()).to_vec()
+}
+
+/// Get system status description
+#[tauri::command]
+pub fn get_system_status_name(status: u8, language: Option<Language>) -> String {
+    let language = language.unwrap_or_default();
+    match SystemStatus::from(status) {
+        SystemStatus::Unknown(raw) => unknown_status_name(raw, language),
+        _ => system_status_name(status, language).to_string(),
+    }
+}
+
+/// Get work status description
+#[tauri::command]
+pub fn get_work_status_name(status: u8, language: Option<Language>) -> String {
+    let language = language.unwrap_or_default();
+    match WorkStatus::from(status) {
+        WorkStatus::Unknown(raw) => unknown_status_name(raw, language),
+        _ => work_status_name(status, language).to_string(),
+    }
+}
+
+/// Get the active prohibitions of a 0x85 prohibition byte for display
+#[tauri::command]
+pub fn get_prohibition_names(flags: u8, language: Option<Language>) -> Vec<String> {
+    prohibition_names(flags, language.unwrap_or_default())
+}
+
+/// Get operation status description
+#[tauri::command]
+pub fn get_operation_status_name(status: u8, language: Option<Language>) -> String {
+    let language = language.unwrap_or_default();
+    match OperationStatusCode::from(status) {
+        OperationStatusCode::Unknown(raw) => unknown_status_name(raw, language),
+        _ => operation_status_name(status, language).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn simulation_state() -> AppState {
+        let state = AppState::new();
+        state.config.lock().adapter_type = AdapterType::Simulation;
+        state
+    }
+
+    #[test]
+    fn test_heartbeat_reports_alive() {
+        let state = simulation_state();
+        assert!(state.start_heartbeat(Duration::from_millis(10)).is_err());
+        state.start_heartbeat(Duration::from_millis(100)).unwrap();
+        assert!(state.start_heartbeat(Duration::from_millis(100)).is_err());
+
+        std::thread::sleep(Duration::from_millis(250));
+        assert_eq!(state.heartbeat.lock().alive(), Some(true));
+        assert!(state.bms_data.lock().software_version.is_some());
+        assert!(state.bms_data.lock().soc_soh.is_none());
+
+        state.stop_heartbeat();
+        assert!(state.heartbeat_thread.lock().is_none());
+        state.disconnect().unwrap();
+        assert_eq!(state.heartbeat.lock().alive(), None);
+    }
+
+    #[test]
+    fn test_start_and_stop_receiving() {
+        let state = simulation_state();
+        state.start_receiving().unwrap();
+        assert!(state.start_receiving().is_err());
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(*state.receiving.lock());
+        assert!(state.bms_data.lock().soc_soh.is_some());
+
+        state.stop_receiving();
+        assert!(!*state.receiving.lock());
+        assert!(state.receive_thread.lock().is_none());
+
+        // The loop can be restarted once stopped
+        state.start_receiving().unwrap();
+        state.stop_receiving();
+    }
+
+    #[test]
+    fn test_connect_then_disconnect_emits_two_events() {
+        let state = AppState::new();
+        let config: ConnectionConfig =
+            serde_json::from_value(serde_json::json!({ "adapter_type": "simulation" })).unwrap();
+
+        state.connect(config).unwrap();
+        let connected = state.connection_state.lock().last().cloned().unwrap();
+        assert!(connected.connected);
+        assert_eq!(connected.reason, ConnectionReason::Connected);
+
+        state.disconnect().unwrap();
+        let tracker = state.connection_state.lock();
+        let disconnected = tracker.last().unwrap();
+        assert!(!disconnected.connected);
+        assert_eq!(disconnected.adapter_type, AdapterType::Simulation);
+        assert_ne!(&connected, disconnected);
+        assert_eq!(tracker.emitted(), 2);
+    }
+
+    #[test]
+    fn test_connect_rejects_unknown_adapter_and_missing_port() {
+        let state = AppState::new();
+        let config = |json| serde_json::from_value::<ConnectionConfig>(json).unwrap();
+
+        let typo = state.connect(config(serde_json::json!({ "adapter_type": "simualtion" })));
+        assert!(matches!(typo, Err(CanError::InvalidConfig(ref m)) if m.contains("simualtion")));
+
+        let no_port = state.connect(config(serde_json::json!({ "adapter_type": "usb" })));
+        assert!(
+            matches!(no_port, Err(CanError::InvalidConfig(ref m)) if m.contains("serial_port"))
+        );
+        let blank_port = config(serde_json::json!({ "adapter_type": "usb", "serial_port": " " }));
+        assert!(state.connect(blank_port).is_err());
+
+        assert!(state.can_manager.lock().is_none());
+        assert!(state.connection_state.lock().last().is_none());
+        let usb = config(serde_json::json!({ "adapter_type": "usb", "serial_port": "COM3" }));
+        assert!(usb.to_can_config(&CanConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_custom_host_address_is_used_for_queries() {
+        use crate::bms_parser::{build_query_frame, build_write_frame};
+        use crate::can_handler::{build_iplus_frame, MockPort};
+
+        let config = |host: u8| {
+            serde_json::from_value::<ConnectionConfig>(serde_json::json!({
+                "adapter_type": "usb",
+                "serial_port": "COM3",
+                "bms_address": 0x02,
+                "host_address": host,
+            }))
+            .unwrap()
+            .to_can_config(&CanConfig::default())
+            .unwrap()
+        };
+
+        let can_config = config(0x90);
+        assert_eq!(can_config.host_address, 0x90);
+        let state = AppState::new();
+        let port = MockPort::new();
+        let mut manager = state.new_manager(can_config);
+        manager.connect_port(Box::new(port.clone())).unwrap();
+        let _ = manager.query_commands(&[BmsCommand::SocSoh]);
+        let query = build_query_frame(BmsCommand::SocSoh, 0x90, 0x02);
+        assert_eq!(port.written(), build_iplus_frame(&query));
+
+        // A BMS at 0x80 answers once the host moved away from it
+        let mut can_config = config(0x90);
+        can_config.bms_address = 0x80;
+        assert!(can_config.validate().is_ok());
+        let port = MockPort::new();
+        let reply = build_write_frame(
+            BmsCommand::SocSoh,
+            &[0x22, 0x00, 0x64, 0x00, 0x1E, 0x00, 0x00, 0x00],
+            0x80,
+            0x90,
+        );
+        port.push_incoming(&build_iplus_frame(&reply));
+        let mut manager = state.new_manager(can_config);
+        manager.connect_port(Box::new(port.clone())).unwrap();
+        let missing = manager.query_commands(&[BmsCommand::SocSoh]).unwrap();
+        assert!(missing.is_empty());
+        assert_eq!(state.bms_data.lock().soc_soh.as_ref().unwrap().soc, 34);
+
+        // Unset falls back to 0x80; out of range or clashing addresses are rejected
+        let default = serde_json::json!({ "adapter_type": "simulation" });
+        let default = serde_json::from_value::<ConnectionConfig>(default).unwrap();
+        assert_eq!(
+            default
+                .to_can_config(&CanConfig::default())
+                .unwrap()
+                .host_address,
+            0x80
+        );
+        assert!(config(0xFF).validate().is_err());
+        assert!(config(0x02).validate().is_err());
+        assert!(config(0x90).validate().is_ok());
+    }
+
+    #[test]
+    fn test_decode_and_encode_example_frame_id() {
+        let decoded = DecodedFrameId::from_id(0x1808_0010).unwrap();
+        assert!(decoded.fields.ptp);
+        assert_eq!(decoded.fields.command, 0x80);
+        assert_eq!(decoded.fields.destination_address, 0x80);
+        assert_eq!(decoded.fields.source_address, 0x01);
+        assert!(!decoded.fields.cnt);
+        assert_eq!(
+            decoded.command_name,
+            Some(BmsCommand::ChargeDischargeLimits)
+        );
+        assert_eq!(encode_frame_id(decoded.fields), 0x1808_0010);
+
+        assert_eq!(
+            DecodedFrameId::from_id(0x1FF0_0000).unwrap().command_name,
+            None
+        );
+        assert!(DecodedFrameId::from_id(0x2000_0000).is_err());
+    }
+
+    #[test]
+    fn test_parse_pasted_soc_frame() {
+        let id = ParsedFrameId {
+            ptp: true,
+            command: 0x81,
+            destination_address: 0x80,
+            source_address: 0x01,
+            cnt: false,
+        }
+        .to_id();
+        // SOC 80%, SOH 100%, 60min backup
+        let soc_frame = vec![0x50, 0x00, 0x64, 0x00, 0x3C, 0x00, 0x00, 0x00];
+
+        let data = parse_frames(vec![(id, soc_frame), (0x1FF0_0000, vec![0xFF])], None, None);
+        let soc_soh = data.soc_soh.unwrap();
+        assert_eq!(soc_soh.soc, 80);
+        assert_eq!(soc_soh.soh, 100);
+        assert!(data.voltage_current.is_none());
+    }
+
+    #[test]
+    fn test_send_raw_frame_rejects_long_payload() {
+        let state = simulation_state();
+
+        let err = state.send_raw_frame(0x1C0_1800, vec![0; 9]).unwrap_err();
+        assert!(matches!(err, CanError::InvalidFrame(_)));
+
+        let frame = state.send_raw_frame(0x1C0_1800, vec![1, 2, 3]).unwrap();
+        assert_eq!(frame.id, 0x1C0_1800);
+        assert_eq!(frame.data, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_active_config_matches_connect() {
+        let state = AppState::new();
+        let config: ConnectionConfig = serde_json::from_value(serde_json::json!({
+            "adapter_type": "simulation",
+            "serial_baud_rate": 57600,
+            "bms_address": 1,
+            "send_delay_ms": 20,
+        }))
+        .unwrap();
+        state.connect(config).unwrap();
+
+        let active = state.config.lock().clone();
+        assert_eq!(active.adapter_type, AdapterType::Simulation);
+        assert_eq!(active.serial_baud_rate, 57600);
+        assert_eq!(active.bms_address, 1);
+        assert_eq!(active.send_delay_ms, Some(20));
+
+        let json = serde_json::to_value(&active).unwrap();
+        assert_eq!(json["adapter_type"], "Simulation");
+        assert_eq!(json["serial_baud_rate"], 57600);
+    }
+
+    #[test]
+    fn test_partial_requery_leaves_stale_ages() {
+        let state = simulation_state();
+        state.query_all_data().unwrap();
+        std::thread::sleep(Duration::from_millis(30));
+
+        // Reconnect and only SOC/SOH answers
+        let config: ConnectionConfig =
+            serde_json::from_value(serde_json::json!({ "adapter_type": "simulation" })).unwrap();
+        state.connect(config).unwrap();
+        state.query_commands(&[BmsCommand::SocSoh]).unwrap();
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let ages = data_ages(&state.bms_data.lock(), now);
+        assert_eq!(ages.len(), 10);
+        let age_of = |command| ages.iter().find(|a| a.command == command).unwrap().age_ms;
+        assert!(age_of(BmsCommand::SocSoh) < age_of(BmsCommand::VoltageCurrent));
+        assert!(age_of(BmsCommand::VoltageCurrent) >= 30);
+        assert_eq!(ages.last().unwrap().command, BmsCommand::SocSoh);
+    }
+
+    #[test]
+    fn test_two_data_subscribers() {
+        let state = simulation_state();
+        let mut ui = state.subscribe_data();
+        let mut integration = state.subscribe_data();
+        assert!(ui.borrow().soc_soh.is_none());
+
+        state.query_commands(&[BmsCommand::SocSoh]).unwrap();
+
+        assert!(ui.has_changed().unwrap());
+        assert!(integration.has_changed().unwrap());
+        assert_eq!(ui.borrow_and_update().soc_soh.as_ref().unwrap().soc, 80);
+        assert_eq!(
+            integration
+                .borrow_and_update()
+                .soc_soh
+                .as_ref()
+                .unwrap()
+                .soc,
+            80
+        );
+        assert!(!ui.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_query_and_receive_share_one_manager() {
+        let state = simulation_state();
+        state.start_receiving().unwrap();
+
+        // A scenario set on the stored manager must show up in received data,
+        // proving the loop doesn't run its own adapter connection
+        state
+            .can_manager
+            .lock()
+            .as_mut()
+            .unwrap()
+            .set_simulation_scenario(Some(SimulationScenario {
+                initial_soc: 42.0,
+                ..Default::default()
+            }));
+        state.query_all_data().unwrap();
+        std::thread::sleep(Duration::from_millis(150));
+        assert_eq!(state.bms_data.lock().soc_soh.as_ref().unwrap().soc, 42);
+
+        state.stop_receiving();
+        assert!(state.can_manager.lock().as_ref().unwrap().is_connected());
+    }
+
+    #[test]
+    fn test_connect_twice_replaces_manager() {
+        let state = simulation_state();
+        let config = || -> ConnectionConfig {
+            serde_json::from_value(serde_json::json!({ "adapter_type": "simulation" })).unwrap()
+        };
+        state.connect(config()).unwrap();
+        state.start_receiving().unwrap();
+        assert!(*state.receiving.lock());
+
+        state.connect(config()).unwrap();
+        assert!(!*state.receiving.lock());
+        assert!(state.receive_thread.lock().is_none());
+        assert!(state.can_manager.lock().as_ref().unwrap().is_connected());
+
+        // Connected, Disconnected, Connected
+        let tracker = state.connection_state.lock();
+        assert_eq!(tracker.emitted(), 3);
+        assert!(tracker.last().unwrap().connected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_alarm_action_fires_once_per_activation() {
+        let state = simulation_state();
+        let log_path =
+            std::env::temp_dir().join(format!("bms-alarm-action-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&log_path);
+        // The bit arrives as $0, never as part of the script text
+        state
+            .set_alarm_action(
+                Some(AlarmAction::Command {
+                    program: "sh".to_string(),
+                    args: vec![
+                        "-c".to_string(),
+                        format!("echo \"$0\" >> '{}'", log_path.display()),
+                        "{bit}".to_string(),
+                    ],
+                }),
+                Duration::ZERO,
+            )
+            .unwrap();
+
+        let inject = |raise: bool| {
+            let mut guard = state.can_manager.lock();
+            let manager = guard.as_mut().unwrap();
+            if raise {
+                manager.inject_simulated_alarm(31).unwrap();
+            } else {
+                manager.clear_simulated_alarms().unwrap();
+            }
+        };
+        state.query_all_data().unwrap();
+        inject(true);
+        state.query_all_data().unwrap();
+        state.query_all_data().unwrap();
+        inject(false);
+        state.query_all_data().unwrap();
+        inject(true);
+        state.query_all_data().unwrap();
+
+        let epo_runs = || {
+            std::fs::read_to_string(&log_path)
+                .unwrap_or_default()
+                .lines()
+                .filter(|line| *line == "31")
+                .count()
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while epo_runs() < 2 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(epo_runs(), 2);
+        let _ = std::fs::remove_file(&log_path);
+
+        state.set_alarm_action(None, Duration::ZERO).unwrap();
+        assert!(state.alarm_action.lock().is_none());
+    }
+
+    #[test]
+    fn test_list_bms_commands_covers_enum() {
+        let commands = list_bms_commands();
+        assert_eq!(commands[0], (0x80, "ChargeDischargeLimits".to_string()));
+
+        // Every code the enum accepts is listed once, under its serde name
+        let codes: Vec<u8> = (0..=u8::MAX)
+            .filter(|&code| BmsCommand::try_from(code).is_ok())
+            .collect();
+        assert_eq!(commands.len(), codes.len());
+        for code in codes {
+            let command = BmsCommand::try_from(code).unwrap();
+            let name = serde_json::to_value(command).unwrap();
+            assert!(commands.contains(&(code, name.as_str().unwrap().to_string())));
+        }
+    }
+
+    #[test]
+    fn test_clear_bms_data() {
+        let state = simulation_state();
+        state.query_all_data().unwrap();
+        assert!(state.bms_data.lock().soc_soh.is_some());
+
+        state.clear_bms_data();
+        let data = state.bms_data.lock().clone();
+        assert!(data.soc_soh.is_none());
+        assert!(data.voltage_current.is_none());
+        assert!(data.alarm_status.is_none());
+        assert!(data.updated_at.is_empty());
+        assert!(state.subscribe_data().borrow().soc_soh.is_none());
+
+        // Opt-in clear on disconnect
+        state.query_all_data().unwrap();
+        state.config.lock().clear_data_on_disconnect = true;
+        state.disconnect().unwrap();
+        assert!(state.bms_data.lock().soc_soh.is_none());
+    }
+
+    #[test]
+    fn test_dashboard_snapshot_matches_getters() {
+        let state = simulation_state();
+        let empty = state.dashboard_snapshot(DEFAULT_STALE_AFTER_MS);
+        assert!(!empty.connected);
+        assert!(empty.stale);
+        assert!(empty.alarms.is_empty());
+
+        state.query_all_data().unwrap();
+        let snapshot = state.dashboard_snapshot(DEFAULT_STALE_AFTER_MS);
+        assert!(state
+            .communication_status(DEFAULT_STALE_AFTER_MS)
+            .link_fault
+            .is_none());
+        let manager = state.can_manager.lock();
+        let manager = manager.as_ref().unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&snapshot.data).unwrap(),
+            serde_json::to_value(&*state.bms_data.lock()).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&snapshot.alarms).unwrap(),
+            serde_json::to_value(state.active_alarms_detailed()).unwrap()
+        );
+        assert_eq!(snapshot.connected, manager.is_connected());
+        assert!(!snapshot.stale);
+        assert_eq!(
+            serde_json::to_value(&snapshot.stats).unwrap(),
+            serde_json::to_value(manager.stats()).unwrap()
+        );
+        assert!(snapshot.stats.frames_received > 0);
+    }
+
+    #[test]
+    fn test_dashboard_stale_while_queries_go_unanswered() {
+        let state = simulation_state();
+        state.query_all_data().unwrap();
+        assert!(!state.dashboard_snapshot(DEFAULT_STALE_AFTER_MS).stale);
+
+        // Queries still going out, but the last answer is old
+        let mut data = state.bms_data.lock();
+        data.timestamp = chrono::Utc::now().timestamp_millis();
+        for updated_at in data.updated_at.values_mut() {
+            *updated_at -= 2 * DEFAULT_STALE_AFTER_MS;
+        }
+        drop(data);
+        assert!(state.dashboard_snapshot(DEFAULT_STALE_AFTER_MS).stale);
+    }
+
+    #[test]
+    fn test_query_appends_sample_history() {
+        let state = simulation_state();
+        state.sample_history.lock().set_capacity(2);
+        state.query_all_data().unwrap();
+        assert_eq!(state.sample_history.lock().len(), 1);
+
+        state.poll().unwrap();
+        state.query_all_data().unwrap();
+        let history = state.sample_history.lock();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.latest(1)[0].soc, Some(80));
+    }
+
+    #[test]
+    fn test_alarm_table_is_single_source() {
+        // Every defined bit (0-40 except the unused 17) has exactly one entry
+        for bit in (0..=40u8).filter(|&b| b != 17) {
+            let alarm = AlarmBit::from_bit(bit).unwrap();
+            assert_eq!(alarm as u8, bit);
+            assert_eq!(ALARM_TABLE.iter().filter(|i| i.bit == alarm).count(), 1);
+        }
+        assert!(AlarmBit::from_bit(17).is_none());
+
+        for (bit, _, severity) in get_alarm_descriptions(None) {
+            let alarm = AlarmBit::from_bit(bit).unwrap();
+            assert_eq!(get_alarm_severity(alarm), severity, "bit {}", bit);
+        }
+    }
+
+    #[test]
+    fn test_alarm_descriptions_are_cached() {
+        let first = get_alarm_descriptions(Some(Language::Thai));
+        let second = get_alarm_descriptions(Some(Language::Thai));
+        assert_eq!(first, second);
+        assert_eq!(first.len(), ALARM_TABLE.len());
+        assert!(std::ptr::eq(
+            alarm_descriptions(Language::English),
+            alarm_descriptions(Language::English)
+        ));
+
+        // Same JSON as the previously built (u8, String, u8) tuples
+        let owned: Vec<(u8, String, u8)> = ALARM_TABLE
+            .iter()
+            .map(|info| {
+                let text = crate::i18n::alarm_description(info.bit, Language::English);
+                (info.bit as u8, text.to_string(), info.severity)
+            })
+            .collect();
+        assert_eq!(
+            serde_json::to_string(&get_alarm_descriptions(None)).unwrap(),
+            serde_json::to_string(&owned).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_thai_strings() {
+        let thai = get_alarm_descriptions(Some(Language::Thai));
+        let english = get_alarm_descriptions(None);
+
+        assert_eq!(thai.len(), english.len());
+        assert_eq!(thai[0], (0, "แรงดันเซลล์สูงเกิน", 3));
+        for (th, en) in thai.iter().zip(&english) {
+            assert_eq!((th.0, th.2), (en.0, en.2));
+        }
+
+        assert_eq!(get_system_status_name(3, Some(Language::Thai)), "กำลังชาร์จ");
+        assert_eq!(get_system_status_name(3, None), "Charging");
+        assert_eq!(get_operation_status_name(3, Some(Language::Thai)), "ขัดข้อง");
+    }
+
+    #[test]
+    fn test_unknown_status_names_show_raw_value() {
+        assert_eq!(get_system_status_name(9, None), "Unknown (9)");
+        assert_eq!(get_work_status_name(3, None), "Unknown (3)");
+        assert_eq!(
+            get_operation_status_name(200, Some(Language::Thai)),
+            "ไม่ทราบสถานะ (200)"
+        );
     }
 }
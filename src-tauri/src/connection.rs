@@ -0,0 +1,228 @@
+//! Connection State Events
+//! Pushes connect/disconnect and link drops to the frontend as
+//! `connection-state` events, so it doesn't have to poll `is_connected`.
+
+use crate::can_handler::AdapterType;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Event emitted when the connection state changes
+pub const CONNECTION_STATE_EVENT: &str = "connection-state";
+
+/// Receive errors must persist this long before the link counts as lost
+pub const LINK_DROP_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// What caused a connection state change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionReason {
+    Connected,
+    Disconnected,
+    /// The receive loop kept failing for `LINK_DROP_DEBOUNCE`
+    LinkLost,
+    /// The receive loop succeeded again after a link loss
+    LinkRestored,
+}
+
+/// Payload of `connection-state`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionEvent {
+    pub connected: bool,
+    pub adapter_type: AdapterType,
+    pub reason: ConnectionReason,
+}
+
+/// Remembers the last emitted state so each change is reported only once
+#[derive(Debug, Default)]
+pub struct ConnectionStateTracker {
+    last: Option<ConnectionEvent>,
+    emitted: u64,
+}
+
+impl ConnectionStateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `event` if it changes the connected state, `None` for repeats
+    ///
+    /// Before the first event the state counts as disconnected.
+    pub fn update(&mut self, event: ConnectionEvent) -> Option<ConnectionEvent> {
+        let was_connected = self.last.as_ref().is_some_and(|e| e.connected);
+        if event.connected == was_connected {
+            return None;
+        }
+        self.last = Some(event.clone());
+        self.emitted += 1;
+        Some(event)
+    }
+
+    /// Last emitted event
+    pub fn last(&self) -> Option<&ConnectionEvent> {
+        self.last.as_ref()
+    }
+
+    /// Number of events emitted so far
+    pub fn emitted(&self) -> u64 {
+        self.emitted
+    }
+}
+
+/// Turns receive results into debounced link lost/restored transitions
+///
+/// A single failed receive (e.g. a USB hiccup) that recovers within the
+/// debounce time is ignored, so the UI doesn't flap.
+#[derive(Debug)]
+pub struct LinkMonitor {
+    debounce: Duration,
+    failing_since: Option<Instant>,
+    lost: bool,
+}
+
+impl LinkMonitor {
+    pub fn new(debounce: Duration) -> Self {
+        LinkMonitor {
+            debounce,
+            failing_since: None,
+            lost: false,
+        }
+    }
+
+    /// Feed one receive result; returns the new link state when it changes
+    pub fn update(&mut self, ok: bool, now: Instant) -> Option<bool> {
+        if ok {
+            self.failing_since = None;
+            if self.lost {
+                self.lost = false;
+                return Some(true);
+            }
+            return None;
+        }
+
+        let since = *self.failing_since.get_or_insert(now);
+        if !self.lost && now.duration_since(since) >= self.debounce {
+            self.lost = true;
+            return Some(false);
+        }
+        None
+    }
+
+    /// The device is gone; the link counts as lost without debouncing
+    pub fn device_lost(&mut self, now: Instant) -> Option<bool> {
+        self.failing_since.get_or_insert(now);
+        if self.lost {
+            return None;
+        }
+        self.lost = true;
+        Some(false)
+    }
+}
+
+/// Consecutive missed heartbeats before the BMS counts as not alive
+pub const HEARTBEAT_MAX_MISSED: u32 = 3;
+
+/// Allowed heartbeat interval in ms
+pub const HEARTBEAT_INTERVAL_RANGE_MS: std::ops::RangeInclusive<u64> = 100..=60_000;
+
+/// Tracks whether the BMS answers the periodic version (0x8F) heartbeat
+///
+/// A single missed response doesn't mark the BMS dead; only `max_missed`
+/// misses in a row do.
+#[derive(Debug)]
+pub struct HeartbeatMonitor {
+    max_missed: u32,
+    missed: u32,
+    alive: Option<bool>,
+}
+
+impl Default for HeartbeatMonitor {
+    fn default() -> Self {
+        Self::new(HEARTBEAT_MAX_MISSED)
+    }
+}
+
+impl HeartbeatMonitor {
+    pub fn new(max_missed: u32) -> Self {
+        HeartbeatMonitor {
+            max_missed: max_missed.max(1),
+            missed: 0,
+            alive: None,
+        }
+    }
+
+    /// Feed one heartbeat result; returns the new liveness when it changes
+    pub fn update(&mut self, responded: bool) -> Option<bool> {
+        let alive = if responded {
+            self.missed = 0;
+            true
+        } else {
+            self.missed = self.missed.saturating_add(1);
+            if self.missed < self.max_missed {
+                return None;
+            }
+            false
+        };
+
+        if self.alive == Some(alive) {
+            return None;
+        }
+        self.alive = Some(alive);
+        Some(alive)
+    }
+
+    /// Whether the BMS answers heartbeats, `None` before it is known
+    pub fn alive(&self) -> Option<bool> {
+        self.alive
+    }
+
+    /// Forget the liveness, e.g. after disconnecting
+    pub fn reset(&mut self) {
+        self.missed = 0;
+        self.alive = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_tolerates_single_miss() {
+        let mut heartbeat = HeartbeatMonitor::new(3);
+        assert_eq!(heartbeat.alive(), None);
+        assert_eq!(heartbeat.update(true), Some(true));
+
+        assert_eq!(heartbeat.update(false), None);
+        assert_eq!(heartbeat.update(false), None);
+        assert_eq!(heartbeat.update(true), None);
+        assert_eq!(heartbeat.alive(), Some(true));
+
+        assert_eq!(heartbeat.update(false), None);
+        assert_eq!(heartbeat.update(false), None);
+        assert_eq!(heartbeat.update(false), Some(false));
+        assert_eq!(heartbeat.update(false), None);
+        assert_eq!(heartbeat.update(true), Some(true));
+    }
+
+    #[test]
+    fn test_link_monitor_ignores_short_flaps() {
+        let mut monitor = LinkMonitor::new(Duration::from_secs(2));
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        // Errors that recover within the debounce time
+        assert_eq!(monitor.update(false, at(0)), None);
+        assert_eq!(monitor.update(false, at(1500)), None);
+        assert_eq!(monitor.update(true, at(1600)), None);
+
+        // Errors that persist
+        assert_eq!(monitor.update(false, at(2000)), None);
+        assert_eq!(monitor.update(false, at(4000)), Some(false));
+        assert_eq!(monitor.update(false, at(5000)), None);
+        assert_eq!(monitor.update(true, at(5100)), Some(true));
+
+        // An unplugged device is reported at once, and only once
+        assert_eq!(monitor.device_lost(at(6000)), Some(false));
+        assert_eq!(monitor.update(false, at(9000)), None);
+    }
+}
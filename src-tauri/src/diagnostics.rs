@@ -0,0 +1,145 @@
+//! Connection Self-Test
+//! Step-by-step check of the adapter path for installers: port discovery,
+//! opening the adapter and a single version (0x8F) query.
+
+use crate::bms_types::*;
+use crate::can_handler::{AdapterType, CanConfig, CanManager};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Outcome of one diagnostics step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticStep {
+    pub name: String,
+    pub passed: bool,
+    pub message: String,
+}
+
+/// Result of `run_self_test`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsReport {
+    /// True if every step passed
+    pub passed: bool,
+    pub steps: Vec<DiagnosticStep>,
+}
+
+impl DiagnosticsReport {
+    fn push(&mut self, name: &str, passed: bool, message: impl Into<String>) {
+        self.passed &= passed;
+        self.steps.push(DiagnosticStep {
+            name: name.to_string(),
+            passed,
+            message: message.into(),
+        });
+    }
+}
+
+/// Run the self-test against `config` with a temporary manager (blocking)
+///
+/// Stops at the first failing step; later steps are reported as skipped.
+pub fn run_self_test(config: &CanConfig) -> DiagnosticsReport {
+    let mut report = DiagnosticsReport {
+        passed: true,
+        steps: Vec::new(),
+    };
+    let is_serial = matches!(
+        config.adapter_type,
+        AdapterType::UsbCan | AdapterType::BluetoothCan
+    );
+
+    // 1. Port discovery
+    if is_serial {
+        let ports = CanManager::list_serial_ports();
+        match config.serial_port {
+            Some(ref port) if ports.contains(port) => {
+                report.push("list_ports", true, format!("Found {}", port))
+            }
+            Some(ref port) => report.push(
+                "list_ports",
+                false,
+                format!("{} not found (available: {})", port, ports.join(", ")),
+            ),
+            None => report.push("list_ports", false, "No serial port configured"),
+        }
+    } else {
+        report.push(
+            "list_ports",
+            true,
+            format!(
+                "{:?} adapter does not use a serial port",
+                config.adapter_type
+            ),
+        );
+    }
+
+    // 2. Open the adapter
+    let bms_data = Arc::new(Mutex::new(BmsData::default()));
+    let mut manager = CanManager::new_with_mutex(config.clone(), bms_data.clone());
+    if report.passed {
+        match manager.connect() {
+            Ok(()) => report.push("open_adapter", true, "Adapter opened"),
+            Err(e) => report.push("open_adapter", false, e.to_string()),
+        }
+    } else {
+        report.push("open_adapter", false, "Skipped");
+    }
+
+    // 3. Version query
+    if report.passed {
+        let result = manager.query_commands(&[BmsCommand::SoftwareVersion]);
+        let version = bms_data.lock().software_version.clone();
+        match (result, version) {
+            (Err(e), _) => report.push("version_query", false, e.to_string()),
+            (Ok(_), Some(version)) => {
+                report.push("version_query", true, format!("BMS version {}", version))
+            }
+            (Ok(_), None) => report.push(
+                "version_query",
+                false,
+                format!(
+                    "No version response from BMS address 0x{:02X}",
+                    config.bms_address
+                ),
+            ),
+        }
+        let _ = manager.disconnect();
+    } else {
+        report.push("version_query", false, "Skipped");
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulation_passes() {
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let report = run_self_test(&config);
+
+        assert!(report.passed, "{:?}", report);
+        assert_eq!(report.steps.len(), 3);
+        assert!(report.steps[2].message.contains("V2.19S"));
+    }
+
+    #[test]
+    fn test_missing_port_skips_later_steps() {
+        let config = CanConfig {
+            adapter_type: AdapterType::UsbCan,
+            serial_port: None,
+            ..Default::default()
+        };
+        let report = run_self_test(&config);
+
+        assert!(!report.passed);
+        assert_eq!(report.steps[1].message, "Skipped");
+    }
+}
@@ -0,0 +1,130 @@
+//! Charge/Discharge Direction
+//! Debounced direction derived from the 0x82 current, so the UI doesn't
+//! flicker while the battery floats around 0A.
+
+use serde::{Deserialize, Serialize};
+
+/// Direction of the battery current
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CurrentDirection {
+    Idle,
+    /// Negative current
+    Charging,
+    /// Positive current
+    Discharging,
+}
+
+/// Hysteresis settings for `DirectionFilter`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DirectionConfig {
+    /// Currents within ±deadband A count as idle
+    pub deadband_a: f32,
+    /// A new direction must hold this long in ms before it is reported
+    pub min_dwell_ms: i64,
+}
+
+impl Default for DirectionConfig {
+    fn default() -> Self {
+        DirectionConfig {
+            deadband_a: 0.5,
+            min_dwell_ms: 2000,
+        }
+    }
+}
+
+/// Reports a direction change only once it has persisted for `min_dwell_ms`
+#[derive(Debug, Default)]
+pub struct DirectionFilter {
+    config: DirectionConfig,
+    reported: Option<CurrentDirection>,
+    /// Pending direction and when it was first seen
+    candidate: Option<(CurrentDirection, i64)>,
+}
+
+impl DirectionFilter {
+    pub fn new(config: DirectionConfig) -> Self {
+        DirectionFilter {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Replace the settings, keeping the reported direction
+    pub fn set_config(&mut self, config: DirectionConfig) {
+        self.config = config;
+        self.candidate = None;
+    }
+
+    /// Direction reported so far, `None` before the first sample
+    pub fn direction(&self) -> Option<CurrentDirection> {
+        self.reported
+    }
+
+    /// Feed a current sample in A taken at `timestamp_ms`
+    pub fn update(&mut self, current: f32, timestamp_ms: i64) -> CurrentDirection {
+        let raw = if current < -self.config.deadband_a {
+            CurrentDirection::Charging
+        } else if current > self.config.deadband_a {
+            CurrentDirection::Discharging
+        } else {
+            CurrentDirection::Idle
+        };
+
+        let Some(reported) = self.reported else {
+            self.reported = Some(raw);
+            return raw;
+        };
+        if raw == reported {
+            self.candidate = None;
+            return reported;
+        }
+
+        let since = match self.candidate {
+            Some((direction, since)) if direction == raw => since,
+            _ => {
+                self.candidate = Some((raw, timestamp_ms));
+                timestamp_ms
+            }
+        };
+        if timestamp_ms - since >= self.config.min_dwell_ms {
+            self.reported = Some(raw);
+            self.candidate = None;
+            return raw;
+        }
+        reported
+    }
+
+    /// Forget the reported direction, e.g. after reconnecting
+    pub fn reset(&mut self) {
+        self.reported = None;
+        self.candidate = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noisy_current_keeps_direction_stable() {
+        let mut filter = DirectionFilter::new(DirectionConfig::default());
+
+        // Floating around 0A, with two short excursions past the deadband
+        let noisy = [0.3, -0.4, 0.2, 0.8, -0.1, -0.9, 0.4, -0.3, 0.1, 0.0];
+        for (i, current) in noisy.into_iter().enumerate() {
+            let direction = filter.update(current, i as i64 * 500);
+            assert_eq!(direction, CurrentDirection::Idle, "sample {}", i);
+        }
+
+        // A sustained charge is reported once it has held for the dwell time
+        let start = 10_000;
+        assert_eq!(filter.update(-5.0, start), CurrentDirection::Idle);
+        assert_eq!(filter.update(-5.0, start + 1500), CurrentDirection::Idle);
+        assert_eq!(
+            filter.update(-5.0, start + 2000),
+            CurrentDirection::Charging
+        );
+        assert_eq!(filter.update(0.2, start + 2500), CurrentDirection::Charging);
+    }
+}
@@ -0,0 +1,150 @@
+//! Frozen Data Detection
+//! Some firmware keeps answering with the same SOC/voltage/current while the
+//! battery is changing, so `connected` stays true and the data looks fresh.
+//! Values that don't move at all for long enough are flagged as possibly
+//! frozen. A battery resting at 0A can legitimately look like this, so the
+//! flag is only an advisory.
+
+use crate::bms_types::BmsData;
+use serde::{Deserialize, Serialize};
+
+/// Thresholds for `FreezeDetector`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FreezeConfig {
+    /// Consecutive query cycles (0x82 replies) with identical values before
+    /// data counts as frozen
+    pub min_repeats: u32,
+    /// The values must also have been identical for this long in ms
+    pub min_duration_ms: i64,
+}
+
+impl Default for FreezeConfig {
+    fn default() -> Self {
+        FreezeConfig {
+            min_repeats: 30,
+            min_duration_ms: 300_000,
+        }
+    }
+}
+
+impl FreezeConfig {
+    /// Fewer than two updates can't repeat anything
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_repeats < 2 {
+            return Err(format!(
+                "freeze_detection.min_repeats must be at least 2, got {}",
+                self.min_repeats
+            ));
+        }
+        if self.min_duration_ms < 0 {
+            return Err(format!(
+                "freeze_detection.min_duration_ms must not be negative, got {}",
+                self.min_duration_ms
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// SOC, voltage and current bit patterns, compared exactly
+type Fingerprint = (Option<u16>, Option<u32>, Option<u32>);
+
+fn fingerprint(data: &BmsData) -> Fingerprint {
+    let vc = data.voltage_current.as_ref();
+    (
+        data.soc_soh.as_ref().map(|s| s.soc),
+        vc.map(|v| v.voltage.to_bits()),
+        vc.map(|v| v.current.to_bits()),
+    )
+}
+
+/// Counts how long the key dynamic fields have stayed exactly the same
+#[derive(Debug, Default)]
+pub struct FreezeDetector {
+    config: FreezeConfig,
+    last: Option<Fingerprint>,
+    /// Updates carrying `last`, including the first
+    repeats: u32,
+    /// Timestamp of the first update carrying `last`
+    since_ms: i64,
+    frozen: bool,
+}
+
+impl FreezeDetector {
+    pub fn new(config: FreezeConfig) -> Self {
+        FreezeDetector {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Feed the data once per query cycle; returns whether it looks frozen
+    pub fn update(&mut self, data: &BmsData) -> bool {
+        let current = fingerprint(data);
+        if self.last == Some(current) {
+            self.repeats = self.repeats.saturating_add(1);
+        } else {
+            self.last = Some(current);
+            self.repeats = 1;
+            self.since_ms = data.timestamp;
+        }
+
+        self.frozen = self.repeats >= self.config.min_repeats
+            && data.timestamp - self.since_ms >= self.config.min_duration_ms;
+        self.frozen
+    }
+
+    /// Result of the last `update`
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bms_types::*;
+
+    #[test]
+    fn test_needs_both_repeats_and_duration() {
+        let mut detector = FreezeDetector::new(FreezeConfig {
+            min_repeats: 3,
+            min_duration_ms: 10_000,
+        });
+        let mut data = BmsData {
+            soc_soh: Some(SocSohData {
+                soc: 50,
+                soh: 100,
+                backup_time_minutes: 0,
+            }),
+            ..Default::default()
+        };
+        let mut feed = |data: &BmsData, at: i64| {
+            let data = BmsData {
+                timestamp: at,
+                ..data.clone()
+            };
+            detector.update(&data)
+        };
+
+        // Three repeats within a second aren't enough
+        assert!(!feed(&data, 0));
+        assert!(!feed(&data, 500));
+        assert!(!feed(&data, 1_000));
+        assert!(feed(&data, 10_000));
+
+        // Any change starts over
+        data.soc_soh.as_mut().unwrap().soc = 49;
+        assert!(!feed(&data, 11_000));
+        assert!(!feed(&data, 30_000));
+        assert!(feed(&data, 31_000));
+
+        assert!(FreezeConfig::default().validate().is_ok());
+        let single = FreezeConfig {
+            min_repeats: 1,
+            ..Default::default()
+        };
+        assert!(single.validate().is_err());
+    }
+}
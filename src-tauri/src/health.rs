@@ -0,0 +1,474 @@
+//! Battery Health Heuristics
+//! App-side estimates derived from BMS data. These are advisory only and
+//! never raised as BMS alarms.
+
+use crate::bms_types::*;
+use crate::direction::CurrentDirection;
+use serde::{Deserialize, Serialize};
+
+/// End-of-life thresholds for `estimate_battery_life`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LifeThresholds {
+    /// Warn when SOH drops below this value in %
+    pub min_soh: u16,
+    /// Warn when the cycle count exceeds the rated cycle life
+    pub rated_cycles: u32,
+}
+
+impl Default for LifeThresholds {
+    fn default() -> Self {
+        LifeThresholds {
+            min_soh: 70,
+            rated_cycles: 6000,
+        }
+    }
+}
+
+/// Remaining useful life estimate from SOH (0x81) and cycle count (0x86)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatteryLifeEstimate {
+    /// State of Health in %, if 0x81 has been received
+    pub soh: Option<u16>,
+    /// Full discharge cycles, if 0x86 has been received
+    pub cycle_count: Option<u16>,
+    /// Cycles left before the rated cycle life is reached
+    pub remaining_cycles: Option<u32>,
+    /// True if SOH is below `min_soh` or cycles exceed `rated_cycles`
+    pub end_of_life_warning: bool,
+}
+
+/// Combine SOH and cycle count into an end-of-life estimate
+pub fn estimate_battery_life(data: &BmsData, thresholds: &LifeThresholds) -> BatteryLifeEstimate {
+    let soh = data.soc_soh.as_ref().map(|s| s.soh);
+    let cycle_count = data.accumulated_times.as_ref().map(|t| t.discharge_times);
+
+    let soh_low = soh.is_some_and(|soh| soh < thresholds.min_soh);
+    let cycles_exceeded = cycle_count.is_some_and(|c| c as u32 > thresholds.rated_cycles);
+
+    BatteryLifeEstimate {
+        soh,
+        cycle_count,
+        remaining_cycles: cycle_count.map(|c| thresholds.rated_cycles.saturating_sub(c as u32)),
+        end_of_life_warning: soh_low || cycles_exceeded,
+    }
+}
+
+/// Cell chemistry of the pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Chemistry {
+    /// Lithium iron phosphate
+    Lfp,
+    /// Nickel manganese cobalt
+    Nmc,
+    /// Lithium titanate
+    Lto,
+}
+
+/// Range of readings a healthy sensor can report for a chemistry
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlausibleBounds {
+    /// Cell voltage range in V
+    pub min_cell_voltage: f32,
+    pub max_cell_voltage: f32,
+    /// Temperature range in °C
+    pub min_temperature: f32,
+    pub max_temperature: f32,
+}
+
+impl Chemistry {
+    /// Plausible cell voltage and temperature range
+    pub fn bounds(self) -> PlausibleBounds {
+        let (min_cell_voltage, max_cell_voltage) = match self {
+            Chemistry::Lfp => (2.5, 3.65),
+            Chemistry::Nmc => (3.0, 4.2),
+            Chemistry::Lto => (1.5, 2.8),
+        };
+        PlausibleBounds {
+            min_cell_voltage,
+            max_cell_voltage,
+            min_temperature: -30.0,
+            max_temperature: 70.0,
+        }
+    }
+}
+
+/// Thresholds for `health_summary`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HealthThresholds {
+    pub life: LifeThresholds,
+    /// Cell voltage delta in V above which a failing cell or sensor is suspected
+    pub max_voltage_delta: f32,
+    /// Temperature delta in °C above which a failing sensor is suspected
+    pub max_temp_delta: f32,
+    /// Chemistry whose bounds readings are checked against (unchecked if unset)
+    pub chemistry: Option<Chemistry>,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        HealthThresholds {
+            life: LifeThresholds::default(),
+            max_voltage_delta: 0.5,
+            max_temp_delta: 15.0,
+            chemistry: None,
+        }
+    }
+}
+
+/// Derived health overview with advisory flags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthSummary {
+    pub life: BatteryLifeEstimate,
+    /// Max-min cell voltage in V (0x83)
+    pub voltage_delta: Option<f32>,
+    /// Max-min temperature in °C (0x84)
+    pub temp_delta: Option<f32>,
+    /// Voltage delta is implausibly large
+    pub voltage_delta_fault: bool,
+    /// Temperature delta is implausibly large
+    pub temp_delta_fault: bool,
+    /// Max or min cell voltage is outside the chemistry's bounds
+    pub cell_voltage_out_of_range: bool,
+    /// Max or min temperature is outside the chemistry's bounds
+    pub temperature_out_of_range: bool,
+    /// Set when the firmware is older than the supported protocol version
+    pub firmware_warning: Option<String>,
+}
+
+/// Evaluate life estimate and sensor plausibility against `thresholds`
+pub fn health_summary(data: &BmsData, thresholds: &HealthThresholds) -> HealthSummary {
+    let voltage_delta = data.cell_voltage.as_ref().map(|c| c.voltage_delta);
+    let temp_delta = data.temperature.as_ref().map(|t| t.temp_delta);
+    let bounds = thresholds.chemistry.map(Chemistry::bounds);
+    let outside = |min: f32, max: f32, low: f32, high: f32| min < low || max > high;
+
+    HealthSummary {
+        life: estimate_battery_life(data, &thresholds.life),
+        voltage_delta,
+        temp_delta,
+        voltage_delta_fault: voltage_delta.is_some_and(|d| d > thresholds.max_voltage_delta),
+        temp_delta_fault: temp_delta.is_some_and(|d| d > thresholds.max_temp_delta),
+        cell_voltage_out_of_range: bounds.is_some_and(|b| {
+            data.cell_voltage.as_ref().is_some_and(|c| {
+                outside(
+                    c.min_voltage,
+                    c.max_voltage,
+                    b.min_cell_voltage,
+                    b.max_cell_voltage,
+                )
+            })
+        }),
+        temperature_out_of_range: bounds.is_some_and(|b| {
+            data.temperature.as_ref().is_some_and(|t| {
+                outside(
+                    t.min_temperature,
+                    t.max_temperature,
+                    b.min_temperature,
+                    b.max_temperature,
+                )
+            })
+        }),
+        firmware_warning: (data.protocol_compatible == Some(false)).then(|| {
+            format!(
+                "BMS firmware {} is older than the supported protocol; \
+                 some fields may be decoded incorrectly",
+                data.software_version.as_deref().unwrap_or("?")
+            )
+        }),
+    }
+}
+
+/// Pack parameters for `estimate_time`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TimeEstimateConfig {
+    /// Nominal pack capacity in Ah
+    pub nominal_capacity_ah: f32,
+    /// Currents within ±this value in A are too small to extrapolate
+    pub min_current_a: f32,
+}
+
+impl Default for TimeEstimateConfig {
+    fn default() -> Self {
+        TimeEstimateConfig {
+            nominal_capacity_ah: 280.0,
+            min_current_a: 0.5,
+        }
+    }
+}
+
+/// Estimated minutes to full (charging) or to empty (discharging)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeEstimate {
+    /// `None` if SOC or current is missing
+    pub direction: Option<CurrentDirection>,
+    /// `None` when unknown, e.g. current near zero
+    pub minutes: Option<f32>,
+}
+
+/// Extrapolate the time to full/empty from SOC, current and nominal capacity
+pub fn estimate_time(data: &BmsData, config: &TimeEstimateConfig) -> TimeEstimate {
+    let (Some(soc_soh), Some(vc)) = (data.soc_soh.as_ref(), data.voltage_current.as_ref()) else {
+        return TimeEstimate {
+            direction: None,
+            minutes: None,
+        };
+    };
+
+    let soc = soc_soh.soc.min(100) as f32 / 100.0;
+    let hours_for = |fraction: f32| fraction * config.nominal_capacity_ah / vc.current.abs();
+
+    if vc.current < -config.min_current_a {
+        TimeEstimate {
+            direction: Some(CurrentDirection::Charging),
+            minutes: Some(hours_for(1.0 - soc) * 60.0),
+        }
+    } else if vc.current > config.min_current_a {
+        TimeEstimate {
+            direction: Some(CurrentDirection::Discharging),
+            minutes: Some(hours_for(soc) * 60.0),
+        }
+    } else {
+        TimeEstimate {
+            direction: Some(CurrentDirection::Idle),
+            minutes: None,
+        }
+    }
+}
+
+/// Lifetime energy totals (0x87) at the last `reset_energy_counters`, held in
+/// memory until the app exits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnergySnapshot {
+    /// Accumulated charging energy in kWh
+    pub charge_energy: f32,
+    /// Accumulated discharging energy in kWh
+    pub discharge_energy: f32,
+    /// Time of the snapshot in ms
+    pub taken_at: i64,
+}
+
+impl EnergySnapshot {
+    pub fn new(totals: &AccumulatedPowerData, taken_at: i64) -> Self {
+        EnergySnapshot {
+            charge_energy: totals.charge_energy,
+            discharge_energy: totals.discharge_energy,
+            taken_at,
+        }
+    }
+}
+
+/// Energy charged/discharged since an `EnergySnapshot`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEnergy {
+    /// Time of the snapshot in ms
+    pub since: i64,
+    /// Charged energy in kWh
+    pub charge_energy: f32,
+    /// Discharged energy in kWh
+    pub discharge_energy: f32,
+    /// A BMS counter went below the snapshot, so it was reset on the BMS side
+    pub counter_reset: bool,
+}
+
+/// Difference between the current totals and `snapshot`
+///
+/// If a BMS counter went backwards it was reset in between; everything it
+/// counted since then belongs to the session, so its current total is used.
+pub fn session_energy(snapshot: &EnergySnapshot, totals: &AccumulatedPowerData) -> SessionEnergy {
+    let delta = |now: f32, then: f32| if now >= then { now - then } else { now };
+
+    SessionEnergy {
+        since: snapshot.taken_at,
+        charge_energy: delta(totals.charge_energy, snapshot.charge_energy),
+        discharge_energy: delta(totals.discharge_energy, snapshot.discharge_energy),
+        counter_reset: totals.charge_energy < snapshot.charge_energy
+            || totals.discharge_energy < snapshot.discharge_energy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(soh: u16, cycles: u16) -> BmsData {
+        BmsData {
+            soc_soh: Some(SocSohData {
+                soc: 50,
+                soh,
+                backup_time_minutes: 0,
+            }),
+            accumulated_times: Some(AccumulatedTimesData {
+                charge_times: cycles,
+                discharge_times: cycles,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_life_estimate_boundaries() {
+        let thresholds = LifeThresholds {
+            min_soh: 80,
+            rated_cycles: 3000,
+        };
+
+        let at_limits = estimate_battery_life(&data(80, 3000), &thresholds);
+        assert!(!at_limits.end_of_life_warning);
+        assert_eq!(at_limits.remaining_cycles, Some(0));
+
+        assert!(estimate_battery_life(&data(79, 3000), &thresholds).end_of_life_warning);
+        assert!(estimate_battery_life(&data(80, 3001), &thresholds).end_of_life_warning);
+
+        let healthy = estimate_battery_life(&data(100, 98), &thresholds);
+        assert_eq!(healthy.remaining_cycles, Some(2902));
+        assert!(!healthy.end_of_life_warning);
+    }
+
+    fn deltas(voltage_delta: f32, temp_delta: f32) -> BmsData {
+        BmsData {
+            cell_voltage: Some(CellVoltageData {
+                max_voltage: 3.4,
+                max_voltage_pack_no: 1,
+                max_voltage_cell_no: 1,
+                min_voltage: 3.4 - voltage_delta,
+                min_voltage_pack_no: 1,
+                min_voltage_cell_no: 2,
+                voltage_delta,
+            }),
+            temperature: Some(TemperatureData {
+                max_temperature: 25.0 + temp_delta,
+                max_temp_pack_no: 1,
+                max_temp_sensor_no: 1,
+                min_temperature: 25.0,
+                min_temp_pack_no: 1,
+                min_temp_sensor_no: 2,
+                temp_delta,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_sensor_fault_thresholds() {
+        let thresholds = HealthThresholds::default();
+
+        let below = health_summary(&deltas(0.499, 14.9), &thresholds);
+        assert!(!below.voltage_delta_fault);
+        assert!(!below.temp_delta_fault);
+
+        let above = health_summary(&deltas(0.501, 15.1), &thresholds);
+        assert!(above.voltage_delta_fault);
+        assert!(above.temp_delta_fault);
+
+        let none = health_summary(&BmsData::default(), &thresholds);
+        assert!(!none.voltage_delta_fault && !none.temp_delta_fault);
+    }
+
+    #[test]
+    fn test_chemistry_bounds() {
+        let lfp = HealthThresholds {
+            chemistry: Some(Chemistry::Lfp),
+            ..Default::default()
+        };
+        let mut data = deltas(0.1, 5.0);
+
+        // 3.4V max / 3.3V min is normal for LFP
+        let ok = health_summary(&data, &lfp);
+        assert!(!ok.cell_voltage_out_of_range);
+        assert!(!ok.temperature_out_of_range);
+
+        // 4.1V is normal for NMC but not for LFP
+        data.cell_voltage.as_mut().unwrap().max_voltage = 4.1;
+        assert!(health_summary(&data, &lfp).cell_voltage_out_of_range);
+        let nmc = HealthThresholds {
+            chemistry: Some(Chemistry::Nmc),
+            ..Default::default()
+        };
+        assert!(!health_summary(&data, &nmc).cell_voltage_out_of_range);
+
+        // Unchecked without a chemistry; raw values are untouched
+        let unchecked = health_summary(&data, &HealthThresholds::default());
+        assert!(!unchecked.cell_voltage_out_of_range);
+        assert_eq!(data.cell_voltage.as_ref().unwrap().max_voltage, 4.1);
+
+        data.temperature.as_mut().unwrap().min_temperature = -40.0;
+        assert!(health_summary(&data, &lfp).temperature_out_of_range);
+    }
+
+    fn soc_current(soc: u16, current: f32) -> BmsData {
+        BmsData {
+            soc_soh: Some(SocSohData {
+                soc,
+                soh: 100,
+                backup_time_minutes: 0,
+            }),
+            voltage_current: Some(VoltageCurrentData {
+                voltage: 800.0,
+                current,
+                power: 0.0,
+                power_w: 0.0,
+                raw_current: current,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_time_estimate() {
+        let config = TimeEstimateConfig {
+            nominal_capacity_ah: 100.0,
+            min_current_a: 0.5,
+        };
+
+        // 60% of 100Ah left at 20A discharge = 3h
+        let discharging = estimate_time(&soc_current(60, 20.0), &config);
+        assert_eq!(discharging.direction, Some(CurrentDirection::Discharging));
+        assert!((discharging.minutes.unwrap() - 180.0).abs() < 0.01);
+
+        // 75Ah missing at 50A charge = 90min
+        let charging = estimate_time(&soc_current(25, -50.0), &config);
+        assert_eq!(charging.direction, Some(CurrentDirection::Charging));
+        assert!((charging.minutes.unwrap() - 90.0).abs() < 0.01);
+
+        let idle = estimate_time(&soc_current(50, 0.4), &config);
+        assert_eq!(idle.direction, Some(CurrentDirection::Idle));
+        assert_eq!(idle.minutes, None);
+
+        assert_eq!(estimate_time(&BmsData::default(), &config).minutes, None);
+    }
+
+    #[test]
+    fn test_life_estimate_without_data() {
+        let estimate = estimate_battery_life(&BmsData::default(), &LifeThresholds::default());
+        assert_eq!(estimate.soh, None);
+        assert_eq!(estimate.remaining_cycles, None);
+        assert!(!estimate.end_of_life_warning);
+    }
+
+    #[test]
+    fn test_session_energy_delta() {
+        // 17200kWh charged, 18275kWh discharged
+        let snapshot =
+            EnergySnapshot::new(&AccumulatedPowerData::from_raw(172_000, 182_750), 1_000);
+
+        let session = session_energy(&snapshot, &AccumulatedPowerData::from_raw(172_125, 182_750));
+        assert!((session.charge_energy - 12.5).abs() < 0.01);
+        assert_eq!(session.discharge_energy, 0.0);
+        assert_eq!(session.since, 1_000);
+        assert!(!session.counter_reset);
+
+        // Discharge counter reset on the BMS, then counted 3kWh again
+        let session = session_energy(&snapshot, &AccumulatedPowerData::from_raw(172_125, 30));
+        assert!((session.discharge_energy - 3.0).abs() < 0.01);
+        assert!(session.counter_reset);
+    }
+}
@@ -0,0 +1,390 @@
+//! SQLite Historian
+//! Long-term storage of key metrics, one row per query cycle, queried back as
+//! downsampled series. The database is only compiled with the `historian` feature.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "historian")]
+use crate::bms_types::BmsData;
+#[cfg(feature = "historian")]
+use crate::can_handler::LogLimiter;
+#[cfg(feature = "historian")]
+use parking_lot::Mutex;
+#[cfg(feature = "historian")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "historian")]
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+#[cfg(feature = "historian")]
+use std::sync::Arc;
+#[cfg(feature = "historian")]
+use std::thread::JoinHandle;
+
+/// Default maximum number of points returned by `query_history`
+pub const DEFAULT_HISTORY_POINTS: usize = 500;
+
+/// Rows buffered between the query cycle and the writer thread
+#[cfg(feature = "historian")]
+const HISTORIAN_QUEUE_SIZE: usize = 256;
+
+/// Write counters of the running historian
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistorianStats {
+    /// Rows dropped because the writer fell behind
+    pub dropped_rows: u64,
+    /// Rows dropped because the database write failed
+    pub failed_writes: u64,
+}
+
+/// Averaged metrics of one time bucket
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryPoint {
+    /// Mean timestamp of the bucket's rows in ms
+    pub timestamp: i64,
+    /// Rows averaged into this point
+    pub samples: u32,
+    /// State of Charge in %
+    pub soc: Option<f64>,
+    /// State of Health in %
+    pub soh: Option<f64>,
+    /// Battery voltage in V
+    pub voltage: Option<f64>,
+    /// Battery current in A
+    pub current: Option<f64>,
+    /// Power in kW
+    pub power: Option<f64>,
+    /// Highest cell voltage in V
+    pub max_cell_voltage: Option<f64>,
+    /// Lowest cell voltage in V
+    pub min_cell_voltage: Option<f64>,
+    /// Highest temperature in °C
+    pub max_temperature: Option<f64>,
+    /// Lowest temperature in °C
+    pub min_temperature: Option<f64>,
+}
+
+/// Bucket width in ms so that `[from, to)` yields at most `max_points` points
+///
+/// Computed in i128, as the span of an open-ended range such as
+/// `[0, i64::MAX)` or `[i64::MIN, 0)` overflows i64.
+pub fn bucket_width_ms(from: i64, to: i64, max_points: usize) -> i64 {
+    let span = (to as i128 - from as i128).max(1);
+    let points = max_points.max(1) as i128;
+    ((span + points - 1) / points).min(i64::MAX as i128) as i64
+}
+
+#[cfg(feature = "historian")]
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS samples (
+    timestamp INTEGER NOT NULL,
+    soc REAL,
+    soh REAL,
+    voltage REAL,
+    current REAL,
+    power REAL,
+    max_cell_voltage REAL,
+    min_cell_voltage REAL,
+    max_temperature REAL,
+    min_temperature REAL
+);
+CREATE INDEX IF NOT EXISTS samples_timestamp ON samples (timestamp);
+";
+
+/// Key metrics of one stored row
+#[cfg(feature = "historian")]
+struct Row {
+    timestamp: i64,
+    soc: Option<f64>,
+    soh: Option<f64>,
+    voltage: Option<f64>,
+    current: Option<f64>,
+    power: Option<f64>,
+    max_cell_voltage: Option<f64>,
+    min_cell_voltage: Option<f64>,
+    max_temperature: Option<f64>,
+    min_temperature: Option<f64>,
+}
+
+#[cfg(feature = "historian")]
+impl Row {
+    fn from_data(data: &BmsData) -> Self {
+        let soc_soh = data.soc_soh.as_ref();
+        let vc = data.voltage_current.as_ref();
+        let cells = data.cell_voltage.as_ref();
+        let temps = data.temperature.as_ref();
+        Row {
+            timestamp: data.timestamp,
+            soc: soc_soh.map(|s| s.soc as f64),
+            soh: soc_soh.map(|s| s.soh as f64),
+            voltage: vc.map(|v| v.voltage as f64),
+            current: vc.map(|v| v.current as f64),
+            power: vc.map(|v| v.power as f64),
+            max_cell_voltage: cells.map(|c| c.max_voltage as f64),
+            min_cell_voltage: cells.map(|c| c.min_voltage as f64),
+            max_temperature: temps.map(|t| t.max_temperature as f64),
+            min_temperature: temps.map(|t| t.min_temperature as f64),
+        }
+    }
+
+    fn insert(&self, db: &rusqlite::Connection) -> rusqlite::Result<usize> {
+        db.execute(
+            "INSERT INTO samples (timestamp, soc, soh, voltage, current, power, \
+             max_cell_voltage, min_cell_voltage, max_temperature, min_temperature) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                self.timestamp,
+                self.soc,
+                self.soh,
+                self.voltage,
+                self.current,
+                self.power,
+                self.max_cell_voltage,
+                self.min_cell_voltage,
+                self.max_temperature,
+                self.min_temperature,
+            ],
+        )
+    }
+}
+
+/// SQLite store of key metrics, written on a background thread
+///
+/// `record` never blocks: rows are dropped (and counted) when the writer
+/// falls behind, and a failed write is counted and logged (rate limited).
+#[cfg(feature = "historian")]
+pub struct Historian {
+    db: Arc<Mutex<rusqlite::Connection>>,
+    sender: Option<SyncSender<Row>>,
+    writer: Option<JoinHandle<()>>,
+    dropped: Arc<AtomicU64>,
+    failed_writes: Arc<AtomicU64>,
+}
+
+#[cfg(feature = "historian")]
+impl Historian {
+    /// Open (or create) the database at `path`; the schema is created on first run
+    pub fn open(path: &str) -> Result<Self, String> {
+        let db = rusqlite::Connection::open(path)
+            .map_err(|e| format!("Failed to open historian database {}: {}", path, e))?;
+        Self::with_connection(db)
+    }
+
+    /// Database that lives only as long as the historian, for tests
+    pub fn open_in_memory() -> Result<Self, String> {
+        let db = rusqlite::Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open historian database: {}", e))?;
+        Self::with_connection(db)
+    }
+
+    fn with_connection(db: rusqlite::Connection) -> Result<Self, String> {
+        db.execute_batch(SCHEMA)
+            .map_err(|e| format!("Failed to create historian schema: {}", e))?;
+        let db = Arc::new(Mutex::new(db));
+        let (sender, receiver) = sync_channel::<Row>(HISTORIAN_QUEUE_SIZE);
+        let failed_writes = Arc::new(AtomicU64::new(0));
+
+        let (thread_db, thread_failed) = (db.clone(), failed_writes.clone());
+        let writer = std::thread::Builder::new()
+            .name("historian".to_string())
+            .spawn(move || {
+                let mut write_log = LogLimiter::new(LogLimiter::INTERVAL);
+                for row in receiver {
+                    write_log.warn_suppressed();
+                    if let Err(e) = row.insert(&thread_db.lock()) {
+                        thread_failed.fetch_add(1, Ordering::Relaxed);
+                        write_log.warn(
+                            "Historian write failed",
+                            format!("Historian write failed: {}", e),
+                        );
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start historian writer: {}", e))?;
+
+        Ok(Historian {
+            db,
+            sender: Some(sender),
+            writer: Some(writer),
+            dropped: Arc::new(AtomicU64::new(0)),
+            failed_writes,
+        })
+    }
+
+    /// Queue one row for `data` without blocking; rows without a timestamp are skipped
+    pub fn record(&self, data: &BmsData) {
+        if data.timestamp <= 0 {
+            return;
+        }
+        let Some(ref sender) = self.sender else {
+            return;
+        };
+        match sender.try_send(Row::from_data(data)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                // Writer thread is gone; nothing left to write to
+            }
+        }
+    }
+
+    /// Rows in `[from, to)` averaged into at most `max_points` time buckets
+    pub fn query(
+        &self,
+        from: i64,
+        to: i64,
+        max_points: usize,
+    ) -> Result<Vec<HistoryPoint>, String> {
+        if to <= from {
+            return Err(format!("History range is empty: from {} to {}", from, to));
+        }
+        let width = bucket_width_ms(from, to, max_points);
+
+        let db = self.db.lock();
+        let mut statement = db
+            .prepare_cached(
+                "SELECT CAST(AVG(timestamp) AS INTEGER), COUNT(*), AVG(soc), AVG(soh), \
+                 AVG(voltage), AVG(current), AVG(power), AVG(max_cell_voltage), \
+                 AVG(min_cell_voltage), AVG(max_temperature), AVG(min_temperature) \
+                 FROM samples WHERE timestamp >= ?1 AND timestamp < ?2 \
+                 GROUP BY (timestamp - ?1) / ?3 ORDER BY 1",
+            )
+            .map_err(|e| format!("History query failed: {}", e))?;
+        let rows = statement
+            .query_map(rusqlite::params![from, to, width], |row| {
+                Ok(HistoryPoint {
+                    timestamp: row.get(0)?,
+                    samples: row.get(1)?,
+                    soc: row.get(2)?,
+                    soh: row.get(3)?,
+                    voltage: row.get(4)?,
+                    current: row.get(5)?,
+                    power: row.get(6)?,
+                    max_cell_voltage: row.get(7)?,
+                    min_cell_voltage: row.get(8)?,
+                    max_temperature: row.get(9)?,
+                    min_temperature: row.get(10)?,
+                })
+            })
+            .map_err(|e| format!("History query failed: {}", e))?;
+        rows.collect::<Result<_, _>>()
+            .map_err(|e| format!("History query failed: {}", e))
+    }
+
+    /// Rows dropped so far
+    pub fn stats(&self) -> HistorianStats {
+        HistorianStats {
+            dropped_rows: self.dropped.load(Ordering::Relaxed),
+            failed_writes: self.failed_writes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Write the queued rows and stop the writer; queries keep working
+    pub fn stop(&mut self) {
+        self.sender = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+#[cfg(feature = "historian")]
+impl Drop for Historian {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_width_covers_range() {
+        assert_eq!(bucket_width_ms(0, 1000, 10), 100);
+        assert_eq!(bucket_width_ms(0, 1001, 10), 101);
+        assert_eq!(bucket_width_ms(0, 5, 500), 1);
+        assert_eq!(bucket_width_ms(0, 1000, 0), 1000);
+
+        // Open-ended ranges don't overflow
+        assert_eq!(bucket_width_ms(0, i64::MAX, 1), i64::MAX);
+        assert_eq!(bucket_width_ms(i64::MIN, i64::MAX, 1), i64::MAX);
+        assert!(bucket_width_ms(i64::MIN, 0, 500) > 0);
+    }
+
+    #[cfg(feature = "historian")]
+    #[test]
+    fn test_insert_and_query_downsampled() {
+        use crate::bms_types::*;
+
+        let mut historian = Historian::open_in_memory().unwrap();
+        for (i, soc) in [80u16, 82, 84, 86].into_iter().enumerate() {
+            let data = BmsData {
+                timestamp: 1_000 + i as i64 * 1_000,
+                soc_soh: Some(SocSohData {
+                    soc,
+                    soh: 98,
+                    backup_time_minutes: 60,
+                }),
+                ..Default::default()
+            };
+            historian.record(&data);
+        }
+        // Rows without a timestamp are never stored
+        historian.record(&BmsData::default());
+        historian.stop();
+        assert_eq!(historian.stats(), HistorianStats::default());
+
+        let all = historian.query(0, 10_000, DEFAULT_HISTORY_POINTS).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].timestamp, 1_000);
+        assert_eq!(all[0].soc, Some(80.0));
+        assert_eq!(all[0].voltage, None);
+
+        // Two buckets of 2 s: (1 s, 2 s) and (3 s, 4 s)
+        let halves = historian.query(1_000, 5_000, 2).unwrap();
+        assert_eq!(halves.len(), 2);
+        assert_eq!(halves[0].samples, 2);
+        assert_eq!(halves[0].soc, Some(81.0));
+        assert_eq!(halves[1].timestamp, 3_500);
+        assert_eq!(halves[1].soc, Some(85.0));
+
+        assert!(historian.query(5_000, 1_000, 10).is_err());
+        assert!(historian.query(20_000, 30_000, 10).unwrap().is_empty());
+
+        let everything = historian
+            .query(0, i64::MAX, DEFAULT_HISTORY_POINTS)
+            .unwrap();
+        assert_eq!(everything.len(), 1);
+        assert_eq!(everything[0].samples, 4);
+    }
+
+    #[cfg(feature = "historian")]
+    #[test]
+    fn test_rows_are_dropped_when_writer_falls_behind() {
+        let mut historian = Historian::open_in_memory().unwrap();
+        let data = crate::bms_types::BmsData {
+            timestamp: 1_000,
+            ..Default::default()
+        };
+
+        // A stuck database backs up the queue instead of blocking `record`
+        let db = historian.db.clone();
+        let guard = db.lock();
+        for _ in 0..HISTORIAN_QUEUE_SIZE + 10 {
+            historian.record(&data);
+        }
+        drop(guard);
+        historian.stop();
+
+        let stats = historian.stats();
+        assert!(stats.dropped_rows >= 9, "{:?}", stats);
+        let stored = historian.query(0, 10_000, 1).unwrap()[0].samples as u64;
+        assert_eq!(
+            stored + stats.dropped_rows,
+            HISTORIAN_QUEUE_SIZE as u64 + 10
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Recent Sample History
+//! Bounded ring buffer of key metrics for trend/sparkline rendering
+
+use crate::bms_types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Default number of samples kept
+pub const DEFAULT_HISTORY_CAPACITY: usize = 300;
+
+/// One trend sample
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sample {
+    /// Timestamp in ms
+    pub timestamp: i64,
+    /// State of Charge in %
+    pub soc: Option<u16>,
+    /// Battery voltage in V
+    pub voltage: Option<f32>,
+    /// Battery current in A
+    pub current: Option<f32>,
+    /// Power in kW
+    pub power: Option<f32>,
+}
+
+impl Sample {
+    /// Capture the trend fields of the current data
+    pub fn from_data(data: &BmsData) -> Self {
+        Sample {
+            timestamp: data.timestamp,
+            soc: data.soc_soh.as_ref().map(|s| s.soc),
+            voltage: data.voltage_current.as_ref().map(|v| v.voltage),
+            current: data.voltage_current.as_ref().map(|v| v.current),
+            power: data.voltage_current.as_ref().map(|v| v.power),
+        }
+    }
+}
+
+/// Fixed-capacity history; the oldest sample is dropped when full
+#[derive(Debug, Clone)]
+pub struct SampleHistory {
+    samples: VecDeque<Sample>,
+    capacity: usize,
+}
+
+impl Default for SampleHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl SampleHistory {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        SampleHistory {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, sample: Sample) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Most recent `limit` samples, oldest first
+    pub fn latest(&self, limit: usize) -> Vec<Sample> {
+        let skip = self.samples.len().saturating_sub(limit);
+        self.samples.iter().skip(skip).copied().collect()
+    }
+
+    /// Change the capacity, dropping the oldest samples if needed
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: i64) -> Sample {
+        Sample {
+            timestamp,
+            soc: Some(80),
+            voltage: None,
+            current: None,
+            power: None,
+        }
+    }
+
+    #[test]
+    fn test_history_caps_at_capacity() {
+        let mut history = SampleHistory::new(5);
+        for t in 0..12 {
+            history.push(sample(t));
+        }
+
+        assert_eq!(history.len(), 5);
+        let timestamps: Vec<i64> = history.latest(100).iter().map(|s| s.timestamp).collect();
+        assert_eq!(timestamps, vec![7, 8, 9, 10, 11]);
+        assert_eq!(history.latest(2)[0].timestamp, 10);
+
+        history.set_capacity(3);
+        assert_eq!(history.latest(100)[0].timestamp, 9);
+    }
+}
@@ -0,0 +1,174 @@
+//! HTTP JSON API for headless deployments
+//! Minimal read-only HTTP/1.1 server (one request per connection):
+//! - `GET /api/bms` - current `BmsData`
+//! - `GET /api/alarms` - active alarms with descriptions and severity
+
+use crate::alarms::active_alarm_details;
+use crate::bms_types::*;
+use parking_lot::Mutex;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Maximum accepted request header size
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Build the HTTP response (status, body) for a request line
+pub fn route(method: &str, path: &str, data: &BmsData) -> (u16, String) {
+    if method != "GET" {
+        return (405, r#"{"error":"method not allowed"}"#.to_string());
+    }
+
+    let body = match path.split('?').next().unwrap_or_default() {
+        "/api/bms" => serde_json::to_string(data),
+        "/api/alarms" => serde_json::to_string(&active_alarm_details(data)),
+        _ => return (404, r#"{"error":"not found"}"#.to_string()),
+    };
+
+    match body {
+        Ok(body) => (200, body),
+        Err(e) => (500, format!(r#"{{"error":"{}"}}"#, e)),
+    }
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Running HTTP server
+pub struct HttpServer {
+    local_addr: SocketAddr,
+    shutdown: tokio::sync::watch::Sender<bool>,
+}
+
+impl HttpServer {
+    /// Bind to `port` on all interfaces and serve `bms_data`
+    pub async fn start(port: u16, bms_data: Arc<Mutex<BmsData>>) -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+        let local_addr = listener.local_addr()?;
+        let (shutdown, mut shutdown_rx) = tokio::sync::watch::channel(false);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            tokio::spawn(serve_client(stream, bms_data.clone()));
+                        }
+                        Err(e) => log::warn!("HTTP accept failed: {}", e),
+                    },
+                }
+            }
+            log::info!("HTTP server stopped");
+        });
+
+        log::info!("HTTP API listening on {}", local_addr);
+        Ok(HttpServer {
+            local_addr,
+            shutdown,
+        })
+    }
+
+    /// Address the server is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting connections
+    pub fn stop(&self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+async fn serve_client(mut stream: tokio::net::TcpStream, bms_data: Arc<Mutex<BmsData>>) {
+    let mut request = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => request.extend_from_slice(&chunk[..n]),
+        }
+        if request.len() > MAX_REQUEST_SIZE {
+            break;
+        }
+    }
+
+    let text = String::from_utf8_lossy(&request);
+    let mut parts = text.lines().next().unwrap_or_default().split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => {
+            let data = bms_data.lock().clone();
+            route(method, path, &data)
+        }
+        _ => (400, r#"{"error":"bad request"}"#.to_string()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        status_text(status),
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::can_handler::{AdapterType, CanConfig, CanManager};
+
+    async fn http_get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_get_bms_with_simulation() {
+        let bms_data = Arc::new(Mutex::new(BmsData::default()));
+        let config = CanConfig {
+            adapter_type: AdapterType::Simulation,
+            ..Default::default()
+        };
+        let mut manager = CanManager::new_with_mutex(config, bms_data.clone());
+        manager.connect().unwrap();
+        manager.query_all_data().unwrap();
+
+        let server = HttpServer::start(0, bms_data).await.unwrap();
+        let addr = SocketAddr::from(([127, 0, 0, 1], server.local_addr().port()));
+
+        let response = http_get(addr, "/api/bms").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        let data: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(data["socSoh"]["soc"], 80);
+
+        let response = http_get(addr, "/api/alarms").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        let response = http_get(addr, "/api/unknown").await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+
+        server.stop();
+    }
+}
@@ -0,0 +1,196 @@
+//! Localized Alarm and Status Strings
+//! English strings come from the protocol tables; other languages translate
+//! them one-to-one, so bit indices and severities never differ by language.
+
+use crate::bms_types::{AlarmBit, Prohibition, ALARM_TABLE};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Display language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "th")]
+    Thai,
+}
+
+/// Alarm description in the given language
+pub fn alarm_description(alarm: AlarmBit, language: Language) -> &'static str {
+    match language {
+        Language::English => alarm.info().description,
+        Language::Thai => alarm_description_th(alarm),
+    }
+}
+
+/// `(bit, description, severity)` of every defined alarm, built once per language
+pub fn alarm_descriptions(language: Language) -> &'static [(u8, &'static str, u8)] {
+    static ENGLISH: OnceLock<Vec<(u8, &'static str, u8)>> = OnceLock::new();
+    static THAI: OnceLock<Vec<(u8, &'static str, u8)>> = OnceLock::new();
+
+    let table = match language {
+        Language::English => &ENGLISH,
+        Language::Thai => &THAI,
+    };
+    table.get_or_init(|| {
+        ALARM_TABLE
+            .iter()
+            .map(|info| {
+                (
+                    info.bit as u8,
+                    alarm_description(info.bit, language),
+                    info.severity,
+                )
+            })
+            .collect()
+    })
+}
+
+fn alarm_description_th(alarm: AlarmBit) -> &'static str {
+    match alarm {
+        AlarmBit::CellOverVoltage => "แรงดันเซลล์สูงเกิน",
+        AlarmBit::CellUnderVoltage => "แรงดันเซลล์ต่ำเกิน",
+        AlarmBit::ChargingOverTempAlarm => "แจ้งเตือนอุณหภูมิสูงเกินขณะชาร์จ",
+        AlarmBit::ChargingLowTempAlarm => "แจ้งเตือนอุณหภูมิต่ำขณะชาร์จ",
+        AlarmBit::DischargingOverTempPrealarm => "เตือนล่วงหน้าอุณหภูมิสูงเกินขณะคายประจุ",
+        AlarmBit::DischargingLowTempPrealarm => "เตือนล่วงหน้าอุณหภูมิต่ำขณะคายประจุ",
+        AlarmBit::DischargingOverCurrentPrealarm => "เตือนล่วงหน้ากระแสคายประจุเกิน",
+        AlarmBit::ChargingOverCurrentPrealarm => "เตือนล่วงหน้ากระแสชาร์จเกิน",
+        AlarmBit::TotalOverVoltagePrealarm => "เตือนล่วงหน้าแรงดันรวมสูงเกิน",
+        AlarmBit::TotalUnderVoltageWarning => "เตือนแรงดันรวมต่ำเกิน",
+        AlarmBit::CircuitBreakerDisconnected => "เซอร์กิตเบรกเกอร์ตัดวงจร",
+        AlarmBit::BalancedChargingFailed => "การชาร์จปรับสมดุลล้มเหลว",
+        AlarmBit::PositivePackVoltageImbalance => "แรงดันแพ็กแบตเตอรี่ขั้วบวกไม่สมดุล",
+        AlarmBit::NegativePackVoltageImbalance => "แรงดันแพ็กแบตเตอรี่ขั้วลบไม่สมดุล",
+        AlarmBit::BmuCommunicationInterruption => "การสื่อสาร BMU ขาดหาย",
+        AlarmBit::WaterFloodingDetectionAlarm => "แจ้งเตือนตรวจพบน้ำท่วม",
+        AlarmBit::WaterFloodingProtection => "ตรวจพบน้ำท่วมและเข้าสู่การป้องกัน",
+        AlarmBit::ChargingOverTempProtection => "ป้องกันอุณหภูมิสูงเกินขณะชาร์จ",
+        AlarmBit::ChargingLowTempProtection => "ป้องกันอุณหภูมิต่ำขณะชาร์จ",
+        AlarmBit::DischargingOverTempProtection => "ป้องกันอุณหภูมิสูงเกินขณะคายประจุ",
+        AlarmBit::DischargingLowTempProtection => "ป้องกันอุณหภูมิต่ำขณะคายประจุ",
+        AlarmBit::DischargingOverCurrentProtectionL1 => "ป้องกันกระแสคายประจุเกิน ระดับ 1",
+        AlarmBit::DischargingOverCurrentProtectionL2 => "ป้องกันกระแสคายประจุเกิน ระดับ 2",
+        AlarmBit::ChargingOverCurrentProtectionL1 => "ป้องกันกระแสชาร์จเกิน ระดับ 1",
+        AlarmBit::ChargingOverCurrentProtectionL2 => "ป้องกันกระแสชาร์จเกิน ระดับ 2",
+        AlarmBit::ChargingOverCurrentProtectionL3 => "ป้องกันกระแสชาร์จเกิน ระดับ 3",
+        AlarmBit::TotalChargingOverVoltageProtection => "ป้องกันแรงดันรวมสูงเกินขณะชาร์จ",
+        AlarmBit::TotalChargingUnderVoltageProtection => "ป้องกันแรงดันรวมต่ำเกินขณะชาร์จ",
+        AlarmBit::ChargingDcContactorFailure => "คอนแทคเตอร์ DC ฝั่งชาร์จขัดข้อง",
+        AlarmBit::DischargingDcContactorFailure => "คอนแทคเตอร์ DC ฝั่งคายประจุขัดข้อง",
+        AlarmBit::EpoShutdown => "ปิดระบบฉุกเฉิน (EPO)",
+        AlarmBit::FireProtection => "ระบบป้องกันอัคคีภัยทำงาน",
+        AlarmBit::ParallelCommunicationAbnormality => "การสื่อสารแบบขนานผิดปกติ",
+        AlarmBit::ParallelAddressConflict => "ที่อยู่การต่อขนานซ้ำกัน",
+        AlarmBit::InsulationMonitoringAlarm => "แจ้งเตือนการตรวจวัดฉนวน",
+        AlarmBit::HydrogenProtection => "ป้องกันก๊าซไฮโดรเจน",
+        AlarmBit::BatteryPackFanMalfunction => "พัดลมแพ็กแบตเตอรี่ทำงานผิดปกติ",
+        AlarmBit::BatteryPackFuseTempHigh => "อุณหภูมิฟิวส์แพ็กแบตเตอรี่สูงเกิน",
+        AlarmBit::CanHallCommunicationInterruption => "การสื่อสาร CAN Hall ขาดหาย",
+        AlarmBit::CanHallDataFailure => "ข้อมูล CAN Hall ผิดพลาด",
+    }
+}
+
+/// Name for a status value the protocol doesn't define, e.g. "Unknown (9)"
+pub fn unknown_status_name(raw: u8, language: Language) -> String {
+    match language {
+        Language::English => format!("Unknown ({})", raw),
+        Language::Thai => format!("ไม่ทราบสถานะ ({})", raw),
+    }
+}
+
+/// System status name (0x85 byte 0)
+pub fn system_status_name(status: u8, language: Language) -> &'static str {
+    match language {
+        Language::English => match status {
+            0 => "Power On",
+            1 => "Start",
+            2 => "Alone",
+            3 => "Charging",
+            4 => "Discharging",
+            5 => "Wait to Charge",
+            6 => "Wait to Discharge",
+            7 => "Lock",
+            _ => "Unknown",
+        },
+        Language::Thai => match status {
+            0 => "เปิดเครื่อง",
+            1 => "เริ่มทำงาน",
+            2 => "ทำงานเดี่ยว",
+            3 => "กำลังชาร์จ",
+            4 => "กำลังคายประจุ",
+            5 => "รอชาร์จ",
+            6 => "รอคายประจุ",
+            7 => "ล็อก",
+            _ => "ไม่ทราบสถานะ",
+        },
+    }
+}
+
+/// Work status name (0x85 byte 1)
+pub fn work_status_name(status: u8, language: Language) -> &'static str {
+    match language {
+        Language::English => match status {
+            0 => "Empty",
+            1 => "Boot",
+            2 => "Shut Down",
+            _ => "Unknown",
+        },
+        Language::Thai => match status {
+            0 => "ว่าง",
+            1 => "เปิดระบบ",
+            2 => "ปิดระบบ",
+            _ => "ไม่ทราบสถานะ",
+        },
+    }
+}
+
+/// Operation status name (0x85 byte 2)
+pub fn operation_status_name(status: u8, language: Language) -> &'static str {
+    match language {
+        Language::English => match status {
+            0 => "Empty",
+            1 => "Normal",
+            2 => "Alarm",
+            3 => "Fault",
+            _ => "Unknown",
+        },
+        Language::Thai => match status {
+            0 => "ว่าง",
+            1 => "ปกติ",
+            2 => "แจ้งเตือน",
+            3 => "ขัดข้อง",
+            _ => "ไม่ทราบสถานะ",
+        },
+    }
+}
+
+/// Active prohibitions of a 0x85 prohibition byte, including undocumented bits
+pub fn prohibition_names(flags: u8, language: Language) -> Vec<String> {
+    (0..8)
+        .map(|bit| 1u8 << bit)
+        .filter(|mask| flags & mask != 0)
+        .map(|mask| {
+            let known = Prohibition::ALL.into_iter().find(|p| p.mask() == mask);
+            match (known, language) {
+                (Some(Prohibition::Discharge), Language::English) => {
+                    "Discharge prohibited".to_string()
+                }
+                (Some(Prohibition::Charge), Language::English) => "Charge prohibited".to_string(),
+                (Some(Prohibition::DischargeHard), Language::English) => {
+                    "Discharge prohibited until OC/UV cleared".to_string()
+                }
+                (Some(Prohibition::Discharge), Language::Thai) => "ห้ามคายประจุ".to_string(),
+                (Some(Prohibition::Charge), Language::Thai) => "ห้ามชาร์จ".to_string(),
+                (Some(Prohibition::DischargeHard), Language::Thai) => {
+                    "ห้ามคายประจุจนกว่า OC/UV จะหาย".to_string()
+                }
+                (None, Language::English) => {
+                    format!("Unknown prohibition (bit {})", mask.trailing_zeros())
+                }
+                (None, Language::Thai) => format!("ข้อห้ามที่ไม่ทราบ (บิต {})", mask.trailing_zeros()),
+            }
+        })
+        .collect()
+}
@@ -4,10 +4,14 @@
 //! This module requires the ControlCAN.dll or ECanVci64.dll to be present.
 
 use crate::bms_types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::time::Duration;
 
 #[cfg(target_os = "windows")]
 use libloading::{Library, Symbol};
+#[cfg(target_os = "windows")]
+use std::sync::Arc;
 
 /// VCI device types
 #[repr(u32)]
@@ -36,6 +40,109 @@ pub struct VciCanObj {
     pub reserved: [u8; 3],
 }
 
+/// Resolution of `VciCanObj::time_stamp`
+pub const VCI_TIMESTAMP_TICK: Duration = Duration::from_micros(100);
+
+impl VciCanObj {
+    /// Convert a received object into a frame stamped with `timestamp` (ms)
+    ///
+    /// The adapter's `time_stamp` is kept as `hw_timestamp` when `time_flag`
+    /// marks it valid; its unit is `VCI_TIMESTAMP_TICK`. A bogus `data_len`
+    /// above 8 from the driver is clamped to the 8 data bytes.
+    pub fn to_frame(&self, timestamp: i64) -> CanFrame {
+        let len = (self.data_len as usize).min(self.data.len());
+        if len < self.data_len as usize {
+            log::warn!(
+                "VCI frame 0x{:08X} reports data_len {}, using {} bytes",
+                self.id,
+                self.data_len,
+                len
+            );
+        }
+        CanFrame {
+            id: self.id,
+            format: if self.extern_flag != 0 {
+                FrameFormat::Extended
+            } else {
+                FrameFormat::Standard
+            },
+            data: self.data[..len].to_vec(),
+            timestamp,
+            hw_timestamp: (self.time_flag == 1).then_some(self.time_stamp),
+        }
+    }
+}
+
+/// Most frames read per `VCI_Receive` call
+pub const VCI_DRAIN_BATCH: usize = 64;
+
+/// Adapter backlog at which we can't catch up and clear the buffer instead
+pub const VCI_OVERFLOW_BACKLOG: u32 = 1000;
+
+/// Receive side of the VCI API, so the drain logic can run against a mock
+pub trait VciReceiver {
+    /// Frames waiting in the adapter buffer (`VCI_GetReceiveNum`)
+    fn receive_num(&self) -> Result<u32, String>;
+    /// Read up to `objs.len()` frames (`VCI_Receive`); returns how many were read
+    fn receive(&self, objs: &mut [VciCanObj], wait_ms: i32) -> Result<usize, String>;
+    /// Discard everything in the adapter buffer (`VCI_ClearBuffer`)
+    fn clear_buffer(&self) -> Result<(), String>;
+}
+
+/// Reads the adapter backlog in batches and recovers from buffer overflows
+///
+/// Reading one frame per call with a sleep in between falls behind on a busy
+/// bus for good, so each read takes up to `VCI_DRAIN_BATCH` frames and hands
+/// them out one at a time.
+#[derive(Debug, Default)]
+pub struct VciDrain {
+    queue: VecDeque<CanFrame>,
+    dropped_frames: u64,
+}
+
+impl VciDrain {
+    /// Next received frame, stamped with `timestamp` (ms) if read now
+    ///
+    /// A backlog of `VCI_OVERFLOW_BACKLOG` or more is cleared and counted as
+    /// dropped.
+    pub fn next_frame(
+        &mut self,
+        rx: &impl VciReceiver,
+        timestamp: i64,
+    ) -> Result<Option<CanFrame>, String> {
+        if let Some(frame) = self.queue.pop_front() {
+            return Ok(Some(frame));
+        }
+
+        let pending = rx.receive_num()?;
+        if pending >= VCI_OVERFLOW_BACKLOG {
+            rx.clear_buffer()?;
+            self.dropped_frames += pending as u64;
+            log::warn!("VCI receive buffer overflow, dropped {} frames", pending);
+            return Ok(None);
+        }
+        if pending == 0 {
+            return Ok(None);
+        }
+
+        let mut objs = vec![VciCanObj::default(); (pending as usize).min(VCI_DRAIN_BATCH)];
+        let read = rx.receive(&mut objs, 0)?.min(objs.len());
+        self.queue
+            .extend(objs[..read].iter().map(|obj| obj.to_frame(timestamp)));
+        Ok(self.queue.pop_front())
+    }
+
+    /// Frames discarded by buffer clears so far
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped_frames
+    }
+
+    /// Forget frames read ahead, e.g. on disconnect
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+}
+
 /// CAN init configuration
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -95,6 +202,46 @@ impl Default for VciBoardInfo {
     }
 }
 
+/// `VciBoardInfo` decoded for display
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardInfo {
+    pub serial_number: String,
+    pub hardware_type: String,
+    /// Versions formatted as `V<major>.<minor>`, e.g. 0x0100 -> "V1.00"
+    pub hardware_version: String,
+    pub firmware_version: String,
+    pub driver_version: String,
+    pub interface_version: String,
+    /// Number of CAN channels
+    pub can_channels: u8,
+}
+
+/// Text of a fixed-size, NUL-padded VCI string field
+fn vci_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+/// VCI version number as `V<major>.<minor>` (the high byte is the major version)
+fn vci_version(version: u16) -> String {
+    format!("V{:X}.{:02X}", version >> 8, version & 0xFF)
+}
+
+impl VciBoardInfo {
+    pub fn decode(&self) -> BoardInfo {
+        BoardInfo {
+            serial_number: vci_string(&self.str_serial_num),
+            hardware_type: vci_string(&self.str_hw_type),
+            hardware_version: vci_version(self.hw_version),
+            firmware_version: vci_version(self.fw_version),
+            driver_version: vci_version(self.dr_version),
+            interface_version: vci_version(self.in_version),
+            can_channels: self.can_num,
+        }
+    }
+}
+
 #[cfg(target_os = "windows")]
 type VciOpenDevice = unsafe extern "stdcall" fn(u32, u32, u32) -> u32;
 #[cfg(target_os = "windows")]
@@ -113,63 +260,84 @@ type VciReceive = unsafe extern "stdcall" fn(u32, u32, u32, *mut VciCanObj, u32,
 type VciGetReceiveNum = unsafe extern "stdcall" fn(u32, u32, u32) -> u32;
 #[cfg(target_os = "windows")]
 type VciReadBoardInfo = unsafe extern "stdcall" fn(u32, u32, *mut VciBoardInfo) -> u32;
+#[cfg(target_os = "windows")]
+type VciClearBuffer = unsafe extern "stdcall" fn(u32, u32, u32) -> u32;
 
-/// iTEKON USBCAN Handler
+/// One opened CAN channel of a loaded VCI library
 #[cfg(target_os = "windows")]
-pub struct ItekonHandler {
-    library: Option<Library>,
+struct VciChannel<'a> {
+    library: &'a Library,
     device_type: u32,
     device_index: u32,
     can_channel: u32,
-    connected: bool,
 }
 
 #[cfg(target_os = "windows")]
-impl ItekonHandler {
-    pub fn new() -> Self {
-        ItekonHandler {
-            library: None,
-            device_type: VciDeviceType::UsbCan2I as u32,
-            device_index: 0,
-            can_channel: 0,
-            connected: false,
-        }
+impl VciReceiver for VciChannel<'_> {
+    fn receive_num(&self) -> Result<u32, String> {
+        let get_receive_num: Symbol<VciGetReceiveNum> = unsafe {
+            self.library
+                .get(b"VCI_GetReceiveNum")
+                .map_err(|e| format!("VCI_GetReceiveNum not found: {}", e))?
+        };
+        Ok(unsafe { get_receive_num(self.device_type, self.device_index, self.can_channel) })
     }
 
-    pub fn set_device_type(&mut self, device_type: VciDeviceType) {
-        self.device_type = device_type as u32;
-    }
+    fn receive(&self, objs: &mut [VciCanObj], wait_ms: i32) -> Result<usize, String> {
+        let receive: Symbol<VciReceive> = unsafe {
+            self.library
+                .get(b"VCI_Receive")
+                .map_err(|e| format!("VCI_Receive not found: {}", e))?
+        };
 
-    pub fn set_channel(&mut self, channel: u32) {
-        self.can_channel = channel;
+        let result = unsafe {
+            receive(
+                self.device_type,
+                self.device_index,
+                self.can_channel,
+                objs.as_mut_ptr(),
+                objs.len() as u32,
+                wait_ms,
+            )
+        };
+        // Some drivers return -1 on a USB error
+        if result == u32::MAX {
+            return Err("VCI_Receive failed".to_string());
+        }
+        Ok(result as usize)
     }
 
-    /// Load the DLL and connect to the device
-    pub fn connect(&mut self) -> Result<(), String> {
-        // Build list of paths to try
-        let mut dll_paths: Vec<std::path::PathBuf> = Vec::new();
-
-        // First, try the bundled resources directory (where Tauri places it)
-        if let Ok(exe_path) = std::env::current_exe() {
-            if let Some(exe_dir) = exe_path.parent() {
-                // Windows: resources folder next to exe
-                dll_paths.push(exe_dir.join("resources").join("ControlCAN.dll"));
-                // Also try directly next to exe
-                dll_paths.push(exe_dir.join("ControlCAN.dll"));
-            }
+    fn clear_buffer(&self) -> Result<(), String> {
+        let clear_buffer: Symbol<VciClearBuffer> = unsafe {
+            self.library
+                .get(b"VCI_ClearBuffer")
+                .map_err(|e| format!("VCI_ClearBuffer not found: {}", e))?
+        };
+
+        let result = unsafe { clear_buffer(self.device_type, self.device_index, self.can_channel) };
+        if result != 1 {
+            return Err(format!("VCI_ClearBuffer failed. Error code: {}", result));
         }
+        Ok(())
+    }
+}
 
-        // Try current working directory
-        dll_paths.push(std::path::PathBuf::from("ControlCAN.dll"));
-        dll_paths.push(std::path::PathBuf::from("resources/ControlCAN.dll"));
+/// An opened VCI device, shared by the handlers of its channels
+///
+/// The device is opened once per adapter; `VCI_CloseDevice` closes every
+/// channel, so it's only called when the last handler lets go of it.
+#[cfg(target_os = "windows")]
+struct VciDevice {
+    library: Library,
+    device_type: u32,
+    device_index: u32,
+}
 
-        // Alternative DLL names (system paths)
-        let system_dlls = [
-            "ControlCAN.dll",
-            "ECanVci64.dll",
-            "ECANVCI.dll",
-            "USBCAN.dll",
-        ];
+#[cfg(target_os = "windows")]
+impl VciDevice {
+    /// Load the DLL and open the device
+    fn open(device_type: u32, device_index: u32) -> Result<Self, String> {
+        let dll_paths = bundled_library_paths();
 
         let mut lib = None;
 
@@ -191,7 +359,7 @@ impl ItekonHandler {
 
         // Fall back to system paths
         if lib.is_none() {
-            for name in &system_dlls {
+            for name in &VCI_LIBRARY_NAMES {
                 match unsafe { Library::new(name) } {
                     Ok(l) => {
                         log::info!("Loaded CAN library: {}", name);
@@ -216,80 +384,173 @@ impl ItekonHandler {
                 .map_err(|e| format!("VCI_OpenDevice not found: {}", e))?
         };
 
-        let result = unsafe { open_device(self.device_type, self.device_index, 0) };
+        let result = unsafe { open_device(device_type, device_index, 0) };
         if result != 1 {
             return Err(format!(
                 "VCI_OpenDevice failed. Device type: {}, Index: {}. Error code: {}",
-                self.device_type, self.device_index, result
+                device_type, device_index, result
             ));
         }
 
+        Ok(VciDevice {
+            library,
+            device_type,
+            device_index,
+        })
+    }
+
+    /// Initialize and start one CAN channel of the device
+    fn start_channel(&self, can_channel: u32) -> Result<(), String> {
         // Initialize CAN
         let init_can: Symbol<VciInitCan> = unsafe {
-            library
+            self.library
                 .get(b"VCI_InitCAN")
                 .map_err(|e| format!("VCI_InitCAN not found: {}", e))?
         };
 
         let config = VciInitConfig::default();
-        let result = unsafe {
-            init_can(
-                self.device_type,
-                self.device_index,
-                self.can_channel,
-                &config,
-            )
-        };
+        let result = unsafe { init_can(self.device_type, self.device_index, can_channel, &config) };
         if result != 1 {
             return Err(format!("VCI_InitCAN failed. Error code: {}", result));
         }
 
         // Start CAN
         let start_can: Symbol<VciStartCan> = unsafe {
-            library
+            self.library
                 .get(b"VCI_StartCAN")
                 .map_err(|e| format!("VCI_StartCAN not found: {}", e))?
         };
 
-        let result = unsafe { start_can(self.device_type, self.device_index, self.can_channel) };
+        let result = unsafe { start_can(self.device_type, self.device_index, can_channel) };
         if result != 1 {
             return Err(format!("VCI_StartCAN failed. Error code: {}", result));
         }
+        Ok(())
+    }
+
+    /// Stop one CAN channel, leaving the device and other channels open
+    fn reset_channel(&self, can_channel: u32) -> Result<(), String> {
+        let reset_can: Symbol<VciResetCan> = unsafe {
+            self.library
+                .get(b"VCI_ResetCAN")
+                .map_err(|e| format!("VCI_ResetCAN not found: {}", e))?
+        };
+
+        let result = unsafe { reset_can(self.device_type, self.device_index, can_channel) };
+        if result != 1 {
+            return Err(format!("VCI_ResetCAN failed. Error code: {}", result));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for VciDevice {
+    fn drop(&mut self) {
+        let close_device: Result<Symbol<VciCloseDevice>, _> =
+            unsafe { self.library.get(b"VCI_CloseDevice") };
+        match close_device {
+            Ok(close_device) => {
+                unsafe { close_device(self.device_type, self.device_index) };
+                log::info!("iTEKON USBCAN disconnected");
+            }
+            Err(e) => log::warn!("VCI_CloseDevice not found: {}", e),
+        }
+    }
+}
+
+/// iTEKON USBCAN Handler for one CAN channel
+///
+/// `connect` opens the device; handlers for its other channels come from
+/// `open_channel` and share that device instead of opening it again.
+#[cfg(target_os = "windows")]
+pub struct ItekonHandler {
+    device: Option<Arc<VciDevice>>,
+    device_type: u32,
+    device_index: u32,
+    can_channel: u32,
+    drain: VciDrain,
+}
+
+#[cfg(target_os = "windows")]
+impl ItekonHandler {
+    pub fn new() -> Self {
+        ItekonHandler {
+            device: None,
+            device_type: VciDeviceType::UsbCan2I as u32,
+            device_index: 0,
+            can_channel: 0,
+            drain: VciDrain::default(),
+        }
+    }
+
+    pub fn set_device_type(&mut self, device_type: VciDeviceType) {
+        self.device_type = device_type as u32;
+    }
+
+    pub fn set_channel(&mut self, channel: u32) {
+        self.can_channel = channel;
+    }
+
+    /// Load the DLL, open the device and start this handler's channel
+    pub fn connect(&mut self) -> Result<(), String> {
+        let device = VciDevice::open(self.device_type, self.device_index)?;
+        device.start_channel(self.can_channel)?;
 
-        self.library = Some(library);
-        self.connected = true;
+        self.device = Some(Arc::new(device));
         log::info!("iTEKON USBCAN connected successfully");
         Ok(())
     }
 
-    /// Disconnect from the device
+    /// Start `channel` on the device this handler opened
+    pub fn open_channel(&self, channel: u32) -> Result<ItekonHandler, String> {
+        let device = self
+            .device
+            .as_ref()
+            .ok_or_else(|| "Not connected".to_string())?;
+        device.start_channel(channel)?;
+
+        log::info!("iTEKON USBCAN channel {} started", channel);
+        Ok(ItekonHandler {
+            device: Some(device.clone()),
+            device_type: self.device_type,
+            device_index: self.device_index,
+            can_channel: channel,
+            drain: VciDrain::default(),
+        })
+    }
+
+    /// Stop this handler's channel; the device closes with its last channel
     pub fn disconnect(&mut self) -> Result<(), String> {
-        if let Some(ref library) = self.library {
-            let close_device: Symbol<VciCloseDevice> = unsafe {
-                library
-                    .get(b"VCI_CloseDevice")
-                    .map_err(|e| format!("VCI_CloseDevice not found: {}", e))?
-            };
-
-            unsafe { close_device(self.device_type, self.device_index) };
+        if let Some(device) = self.device.take() {
+            if let Err(e) = device.reset_channel(self.can_channel) {
+                log::warn!("{}", e);
+            }
         }
 
-        self.library = None;
-        self.connected = false;
-        log::info!("iTEKON USBCAN disconnected");
+        self.drain.clear();
         Ok(())
     }
 
     pub fn is_connected(&self) -> bool {
-        self.connected
+        self.device.is_some()
+    }
+
+    /// Frames discarded on receive-buffer overflows since creation
+    pub fn dropped_frames(&self) -> u64 {
+        self.drain.dropped_frames()
+    }
+
+    fn library(&self) -> Result<&Library, String> {
+        self.device
+            .as_ref()
+            .map(|device| &device.library)
+            .ok_or_else(|| "Not connected".to_string())
     }
 
     /// Send a CAN frame
     pub fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
-        let library = self
-            .library
-            .as_ref()
-            .ok_or_else(|| "Not connected".to_string())?;
+        let library = self.library()?;
 
         let transmit: Symbol<VciTransmit> = unsafe {
             library
@@ -299,8 +560,8 @@ impl ItekonHandler {
 
         let mut can_obj = VciCanObj::default();
         can_obj.id = frame.id;
-        can_obj.extern_flag = 1; // Extended frame (29-bit)
-        can_obj.data_len = frame.data.len() as u8;
+        can_obj.extern_flag = (frame.format == FrameFormat::Extended) as u8;
+        can_obj.data_len = frame.data.len().min(8) as u8;
         for (i, &byte) in frame.data.iter().enumerate() {
             if i < 8 {
                 can_obj.data[i] = byte;
@@ -325,72 +586,37 @@ impl ItekonHandler {
     }
 
     /// Receive CAN frames
-    pub fn receive_frame(&self, timeout: Duration) -> Result<Option<CanFrame>, String> {
-        let library = self
-            .library
+    ///
+    /// Frames are read from the adapter in batches, see `VciDrain`.
+    pub fn receive_frame(&mut self, timeout: Duration) -> Result<Option<CanFrame>, String> {
+        // Borrow the field only, `drain` is borrowed mutably below
+        let device = self
+            .device
             .as_ref()
             .ok_or_else(|| "Not connected".to_string())?;
-
-        // Check if data available
-        let get_receive_num: Symbol<VciGetReceiveNum> = unsafe {
-            library
-                .get(b"VCI_GetReceiveNum")
-                .map_err(|e| format!("VCI_GetReceiveNum not found: {}", e))?
-        };
-
-        let count =
-            unsafe { get_receive_num(self.device_type, self.device_index, self.can_channel) };
-
-        if count == 0 {
-            // Wait a bit and try again
-            std::thread::sleep(timeout);
-            let count =
-                unsafe { get_receive_num(self.device_type, self.device_index, self.can_channel) };
-            if count == 0 {
-                return Ok(None);
-            }
-        }
-
-        // Receive frame
-        let receive: Symbol<VciReceive> = unsafe {
-            library
-                .get(b"VCI_Receive")
-                .map_err(|e| format!("VCI_Receive not found: {}", e))?
-        };
-
-        let mut can_obj = VciCanObj::default();
-        let wait_time = timeout.as_millis() as i32;
-
-        let result = unsafe {
-            receive(
-                self.device_type,
-                self.device_index,
-                self.can_channel,
-                &mut can_obj,
-                1,
-                wait_time,
-            )
+        let channel = VciChannel {
+            library: &device.library,
+            device_type: self.device_type,
+            device_index: self.device_index,
+            can_channel: self.can_channel,
         };
 
-        if result == 0 {
-            return Ok(None);
+        let frame = self
+            .drain
+            .next_frame(&channel, chrono::Utc::now().timestamp_millis())?;
+        if frame.is_some() {
+            return Ok(frame);
         }
 
-        let data = can_obj.data[..can_obj.data_len as usize].to_vec();
-
-        Ok(Some(CanFrame {
-            id: can_obj.id,
-            data,
-            timestamp: chrono::Utc::now().timestamp_millis(),
-        }))
+        // Wait a bit and try again
+        std::thread::sleep(timeout);
+        self.drain
+            .next_frame(&channel, chrono::Utc::now().timestamp_millis())
     }
 
     /// Get device info
     pub fn get_board_info(&self) -> Result<VciBoardInfo, String> {
-        let library = self
-            .library
-            .as_ref()
-            .ok_or_else(|| "Not connected".to_string())?;
+        let library = self.library()?;
 
         let read_board_info: Symbol<VciReadBoardInfo> = unsafe {
             library
@@ -399,8 +625,7 @@ impl ItekonHandler {
         };
 
         let mut info = VciBoardInfo::default();
-        let result =
-            unsafe { read_board_info(self.device_type, self.device_index, &mut info) };
+        let result = unsafe { read_board_info(self.device_type, self.device_index, &mut info) };
 
         if result != 1 {
             return Err(format!("VCI_ReadBoardInfo failed. Error code: {}", result));
@@ -420,12 +645,60 @@ impl Default for ItekonHandler {
 #[cfg(target_os = "windows")]
 impl Drop for ItekonHandler {
     fn drop(&mut self) {
-        if self.connected {
+        if self.is_connected() {
             let _ = self.disconnect();
         }
     }
 }
 
+/// DLL names the VCI library ships under, tried in this order from the system paths
+pub const VCI_LIBRARY_NAMES: [&str; 4] = [
+    "ControlCAN.dll",
+    "ECanVci64.dll",
+    "ECANVCI.dll",
+    "USBCAN.dll",
+];
+
+/// Places the installer bundles ControlCAN.dll: next to the exe (in
+/// `resources` where Tauri puts it, or directly) and the working directory
+pub fn bundled_library_paths() -> Vec<std::path::PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(exe_dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+    {
+        paths.push(exe_dir.join("resources").join("ControlCAN.dll"));
+        paths.push(exe_dir.join("ControlCAN.dll"));
+    }
+    paths.push(std::path::PathBuf::from("ControlCAN.dll"));
+    paths.push(std::path::PathBuf::from("resources/ControlCAN.dll"));
+    paths
+}
+
+/// First of `candidates` that exists
+pub fn find_library(
+    candidates: impl IntoIterator<Item = std::path::PathBuf>,
+) -> Option<std::path::PathBuf> {
+    candidates.into_iter().find(|path| path.is_file())
+}
+
+/// Where `connect` would find the VCI library, without loading it
+///
+/// Checks the bundled locations, then `VCI_LIBRARY_NAMES` in each `PATH`
+/// directory. Always `None` off Windows, where the adapter isn't supported.
+pub fn locate_library() -> Option<std::path::PathBuf> {
+    if !cfg!(target_os = "windows") {
+        return None;
+    }
+    let path_dirs: Vec<std::path::PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    let system = path_dirs
+        .iter()
+        .flat_map(|dir| VCI_LIBRARY_NAMES.iter().map(move |name| dir.join(name)));
+    find_library(bundled_library_paths().into_iter().chain(system))
+}
+
 // Stub for non-Windows platforms
 #[cfg(not(target_os = "windows"))]
 pub struct ItekonHandler;
@@ -436,10 +709,16 @@ impl ItekonHandler {
         ItekonHandler
     }
 
+    pub fn set_channel(&mut self, _channel: u32) {}
+
     pub fn connect(&mut self) -> Result<(), String> {
         Err("iTEKON USBCAN is only supported on Windows".to_string())
     }
 
+    pub fn open_channel(&self, _channel: u32) -> Result<ItekonHandler, String> {
+        Err("iTEKON USBCAN is only supported on Windows".to_string())
+    }
+
     pub fn disconnect(&mut self) -> Result<(), String> {
         Ok(())
     }
@@ -452,7 +731,15 @@ impl ItekonHandler {
         Err("iTEKON USBCAN is only supported on Windows".to_string())
     }
 
-    pub fn receive_frame(&self, _timeout: Duration) -> Result<Option<CanFrame>, String> {
+    pub fn receive_frame(&mut self, _timeout: Duration) -> Result<Option<CanFrame>, String> {
+        Err("iTEKON USBCAN is only supported on Windows".to_string())
+    }
+
+    pub fn dropped_frames(&self) -> u64 {
+        0
+    }
+
+    pub fn get_board_info(&self) -> Result<VciBoardInfo, String> {
         Err("iTEKON USBCAN is only supported on Windows".to_string())
     }
 }
@@ -463,3 +750,153 @@ impl Default for ItekonHandler {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    /// Adapter buffer holding `backlog` frames
+    #[derive(Default)]
+    struct MockVci {
+        backlog: RefCell<VecDeque<VciCanObj>>,
+        receive_calls: Cell<usize>,
+        clears: Cell<usize>,
+    }
+
+    impl MockVci {
+        fn with_backlog(count: u32) -> Self {
+            let mock = MockVci::default();
+            mock.backlog
+                .borrow_mut()
+                .extend((0..count).map(|i| VciCanObj {
+                    id: i,
+                    extern_flag: 1,
+                    data_len: 1,
+                    ..Default::default()
+                }));
+            mock
+        }
+    }
+
+    impl VciReceiver for MockVci {
+        fn receive_num(&self) -> Result<u32, String> {
+            Ok(self.backlog.borrow().len() as u32)
+        }
+
+        fn receive(&self, objs: &mut [VciCanObj], _wait_ms: i32) -> Result<usize, String> {
+            self.receive_calls.set(self.receive_calls.get() + 1);
+            let mut backlog = self.backlog.borrow_mut();
+            let mut read = 0;
+            for obj in objs.iter_mut() {
+                let Some(next) = backlog.pop_front() else {
+                    break;
+                };
+                *obj = next;
+                read += 1;
+            }
+            Ok(read)
+        }
+
+        fn clear_buffer(&self) -> Result<(), String> {
+            self.clears.set(self.clears.get() + 1);
+            self.backlog.borrow_mut().clear();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_drain_reads_backlog_in_batches() {
+        let vci = MockVci::with_backlog(100);
+        let mut drain = VciDrain::default();
+
+        let mut ids = Vec::new();
+        while let Some(frame) = drain.next_frame(&vci, 0).unwrap() {
+            ids.push(frame.id);
+        }
+        assert_eq!(ids, (0..100).collect::<Vec<_>>());
+        // 64 + 36 instead of one call per frame
+        assert_eq!(vci.receive_calls.get(), 2);
+        assert_eq!(drain.dropped_frames(), 0);
+    }
+
+    #[test]
+    fn test_drain_clears_overflowed_buffer() {
+        let vci = MockVci::with_backlog(VCI_OVERFLOW_BACKLOG + 500);
+        let mut drain = VciDrain::default();
+
+        assert!(drain.next_frame(&vci, 0).unwrap().is_none());
+        assert_eq!(vci.clears.get(), 1);
+        assert_eq!(drain.dropped_frames(), 1500);
+        assert_eq!(vci.receive_calls.get(), 0);
+
+        // Back to normal once the buffer is empty
+        vci.backlog.borrow_mut().push_back(VciCanObj {
+            id: 7,
+            extern_flag: 1,
+            ..Default::default()
+        });
+        assert_eq!(drain.next_frame(&vci, 0).unwrap().unwrap().id, 7);
+    }
+
+    #[test]
+    fn test_decode_board_info() {
+        let mut info = VciBoardInfo {
+            hw_version: 0x0100,
+            fw_version: 0x0312,
+            dr_version: 0x0209,
+            in_version: 0x0201,
+            can_num: 2,
+            ..Default::default()
+        };
+        info.str_serial_num[..10].copy_from_slice(b"31F00012AB");
+        info.str_hw_type[..15].copy_from_slice(b"USBCAN-2I  \0xyz");
+
+        let board = info.decode();
+        assert_eq!(board.serial_number, "31F00012AB");
+        assert_eq!(board.hardware_type, "USBCAN-2I");
+        assert_eq!(board.hardware_version, "V1.00");
+        assert_eq!(board.firmware_version, "V3.12");
+        assert_eq!(board.driver_version, "V2.09");
+        assert_eq!(board.can_channels, 2);
+
+        // Unset fields
+        assert_eq!(VciBoardInfo::default().decode().serial_number, "");
+    }
+
+    #[test]
+    fn test_hw_timestamp_propagates() {
+        let mut can_obj = VciCanObj {
+            id: 0x1881_8001,
+            time_stamp: 123_456,
+            time_flag: 1,
+            extern_flag: 1,
+            data_len: 2,
+            ..Default::default()
+        };
+        can_obj.data[..2].copy_from_slice(&[0x50, 0x00]);
+
+        let frame = can_obj.to_frame(1_700_000_000_000);
+        assert_eq!(frame.hw_timestamp, Some(123_456));
+        assert_eq!(frame.timestamp, 1_700_000_000_000);
+        assert_eq!(frame.format, FrameFormat::Extended);
+        assert_eq!(frame.data, vec![0x50, 0x00]);
+
+        // Not valid without time_flag
+        can_obj.time_flag = 0;
+        assert_eq!(can_obj.to_frame(0).hw_timestamp, None);
+    }
+
+    #[test]
+    fn test_oversized_data_len_is_clamped() {
+        let can_obj = VciCanObj {
+            id: 0x1881_8001,
+            extern_flag: 1,
+            data_len: 20,
+            data: [1, 2, 3, 4, 5, 6, 7, 8],
+            ..Default::default()
+        };
+        let frame = can_obj.to_frame(0);
+        assert_eq!(frame.data, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+}
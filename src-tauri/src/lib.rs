@@ -1,13 +1,49 @@
 //! BMS Monitor - Main Library
 
+pub mod alarms;
 pub mod bms_parser;
 pub mod bms_types;
 pub mod can_handler;
+pub mod capture;
 pub mod commands;
+pub mod connection;
+pub mod diagnostics;
+pub mod direction;
+pub mod freeze;
+pub mod health;
+pub mod historian;
+pub mod history;
+#[cfg(feature = "http")]
+pub mod http_server;
+pub mod i18n;
 pub mod itekon_handler;
+pub mod logging;
+pub mod modbus;
+pub mod mqtt;
+pub mod report;
+pub mod snapshot;
+pub mod units;
 
+pub use alarms::*;
 pub use bms_parser::*;
 pub use bms_types::*;
 pub use can_handler::*;
+pub use capture::*;
 pub use commands::*;
+pub use connection::*;
+pub use diagnostics::*;
+pub use direction::*;
+pub use freeze::*;
+pub use health::*;
+pub use historian::*;
+pub use history::*;
+#[cfg(feature = "http")]
+pub use http_server::*;
+pub use i18n::*;
 pub use itekon_handler::*;
+pub use logging::*;
+pub use modbus::*;
+pub use mqtt::*;
+pub use report::*;
+pub use snapshot::*;
+pub use units::*;
@@ -0,0 +1,193 @@
+//! Runtime Log Level
+//! Records from this app (`bms_monitor*` targets) are gated by a level that
+//! can be changed from the UI without a restart. Every other target keeps
+//! whatever the wrapped logger (env_logger with `RUST_LOG`) allows.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Level used when `RUST_LOG` sets neither a plain level nor one for this app
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// Target prefix of this app's records, covering both the binary and the library
+pub const APP_TARGET: &str = "bms_monitor";
+
+/// Crate names a `RUST_LOG` directive can use for this app
+pub const APP_CRATES: [&str; 2] = ["bms_monitor", "bms_monitor_lib"];
+
+static APP_LEVEL: AtomicUsize = AtomicUsize::new(DEFAULT_LOG_LEVEL as usize);
+static OTHERS_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Off as usize);
+
+fn load(level: &AtomicUsize) -> LevelFilter {
+    LevelFilter::iter()
+        .nth(level.load(Ordering::Relaxed))
+        .unwrap_or(LevelFilter::Trace)
+}
+
+fn is_app_target(target: &str) -> bool {
+    target.starts_with(APP_TARGET)
+}
+
+/// Let `log`'s global gate through whatever either side may log
+fn update_max_level() {
+    log::set_max_level(load(&APP_LEVEL).max(load(&OTHERS_LEVEL)));
+}
+
+/// Wraps the real logger and applies the runtime level to this app's records
+struct RuntimeLevelLogger {
+    inner: Box<dyn Log>,
+}
+
+impl Log for RuntimeLevelLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if is_app_target(metadata.target()) && metadata.level() > load(&APP_LEVEL) {
+            return false;
+        }
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if is_app_target(record.target()) && record.level() > load(&APP_LEVEL) {
+            return;
+        }
+        self.inner.log(record)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+/// Install `inner` as the global logger. `inner` must let `APP_CRATES`
+/// through at every level; `others` is the most verbose level it allows for
+/// any other target. The runtime level starts from `initial_level(rust_log)`.
+pub fn install(
+    inner: Box<dyn Log>,
+    others: LevelFilter,
+    rust_log: Option<&str>,
+) -> Result<(), SetLoggerError> {
+    log::set_logger(Box::leak(Box::new(RuntimeLevelLogger { inner })))?;
+    APP_LEVEL.store(initial_level(rust_log) as usize, Ordering::Relaxed);
+    OTHERS_LEVEL.store(others as usize, Ordering::Relaxed);
+    update_max_level();
+    Ok(())
+}
+
+/// Parse a level name ("off", "error", "warn", "info", "debug", "trace")
+pub fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    level.trim().parse().map_err(|_| {
+        format!(
+            "Unknown log level {:?}, expected off, error, warn, info, debug or trace",
+            level
+        )
+    })
+}
+
+/// Change the active log level for this app's records
+pub fn set_level(level: &str) -> Result<LevelFilter, String> {
+    let filter = parse_level(level)?;
+    APP_LEVEL.store(filter as usize, Ordering::Relaxed);
+    update_max_level();
+    log::info!("Log level set to {}", filter);
+    Ok(filter)
+}
+
+/// Active log level for this app's records
+pub fn current_level() -> LevelFilter {
+    load(&APP_LEVEL)
+}
+
+/// Startup level from `RUST_LOG`: a directive naming this app
+/// (`bms_monitor_lib=debug`) wins over a plain level (`warn`); directives for
+/// single modules or other crates are left to the wrapped logger
+pub fn initial_level(rust_log: Option<&str>) -> LevelFilter {
+    let spec = rust_log.unwrap_or("");
+    // Anything after '/' is env_logger's message regex
+    let directives = spec.split('/').next().unwrap_or("");
+    let mut plain = None;
+    let mut app = None;
+    for directive in directives.split(',') {
+        match directive.split_once('=') {
+            Some((name, level)) if APP_CRATES.contains(&name.trim()) => {
+                app = parse_level(level).ok().or(app);
+            }
+            Some(_) => {}
+            None => plain = parse_level(directive).ok().or(plain),
+        }
+    }
+    app.or(plain).unwrap_or(DEFAULT_LOG_LEVEL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    /// Stands in for env_logger: this app at every level, other targets at warn
+    struct CaptureLogger;
+
+    impl Log for CaptureLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            is_app_target(metadata.target()) || metadata.level() <= LevelFilter::Warn
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                RECORDS.lock().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_set_level_changes_what_is_logged() {
+        install(
+            Box::new(CaptureLogger),
+            LevelFilter::Warn,
+            Some("serialport=warn,bms_monitor_lib=debug"),
+        )
+        .unwrap();
+        assert_eq!(current_level(), LevelFilter::Debug);
+        assert!(set_level("verbose").is_err());
+
+        set_level("info").unwrap();
+        log::debug!("hidden");
+        set_level(" TRACE ").unwrap();
+        assert_eq!(current_level(), LevelFilter::Trace);
+        log::debug!("shown");
+        // Other crates stay where the wrapped logger put them
+        log::info!(target: "serialport", "third-party info");
+        log::warn!(target: "serialport", "third-party warn");
+        set_level("info").unwrap();
+        assert_eq!(log::max_level(), LevelFilter::Info);
+
+        let records = RECORDS.lock();
+        assert!(records.iter().any(|r| r == "shown"));
+        assert!(records.iter().any(|r| r == "third-party warn"));
+        assert!(!records.iter().any(|r| r == "hidden"));
+        assert!(!records.iter().any(|r| r == "third-party info"));
+    }
+
+    #[test]
+    fn test_initial_level_from_rust_log() {
+        assert_eq!(initial_level(Some("warn")), LevelFilter::Warn);
+        assert_eq!(
+            initial_level(Some("bms_monitor_lib=debug")),
+            LevelFilter::Debug
+        );
+        assert_eq!(
+            initial_level(Some("trace,bms_monitor_lib=warn,tokio=info")),
+            LevelFilter::Warn
+        );
+        assert_eq!(
+            initial_level(Some("bms_monitor_lib::can_handler=trace")),
+            DEFAULT_LOG_LEVEL
+        );
+        assert_eq!(initial_level(Some("error/timeout")), LevelFilter::Error);
+        assert_eq!(initial_level(None), DEFAULT_LOG_LEVEL);
+    }
+}
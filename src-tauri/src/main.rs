@@ -3,25 +3,112 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use bms_monitor_lib::commands::*;
+use bms_monitor_lib::logging;
+use tauri::Manager;
 
 fn main() {
-    env_logger::init();
+    // Also receives `tracing` spans through tracing's `log` feature. Other
+    // crates follow `RUST_LOG` as usual; this app's own records are let
+    // through here and gated by the level `set_log_level` moves at runtime.
+    let others = env_logger::Builder::from_default_env().build().filter();
+    let mut builder = env_logger::Builder::from_default_env();
+    for name in logging::APP_CRATES {
+        builder.filter_module(name, log::LevelFilter::Trace);
+    }
+    let logger = builder.build();
+    logging::install(
+        Box::new(logger),
+        others,
+        std::env::var("RUST_LOG").ok().as_deref(),
+    )
+    .expect("logger already installed");
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(AppState::new())
+        .setup(|app| {
+            app.state::<AppState>().set_app_handle(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             list_ports,
+            list_bluetooth_ports,
+            list_adapters,
             connect,
+            get_active_config,
             disconnect,
+            run_diagnostics,
             is_connected,
+            get_connection_state,
+            get_adapter_info,
+            get_communication_status,
             get_bms_data,
+            get_all_bms_data,
+            get_dashboard_snapshot,
+            clear_bms_data,
+            get_bms_data_display,
+            export_report,
+            get_sample_history,
+            get_can_stats,
+            get_command_latencies,
+            get_bus_load,
+            get_detected_addresses,
+            start_frame_capture,
+            stop_frame_capture,
+            set_sample_history_capacity,
+            start_historian,
+            stop_historian,
+            query_history,
+            get_historian_stats,
             query_all_data,
+            refresh_static_data,
+            query_commands,
+            query_command,
+            ping_bms,
+            list_bms_commands,
+            set_log_level,
+            get_log_level,
+            decode_frame_id,
+            parse_frames,
+            encode_frame_id,
+            send_raw_frame,
+            set_charge_discharge_limits,
+            poll_data,
+            set_query_plan,
             start_receiving,
+            stop_receiving,
+            start_heartbeat,
+            stop_heartbeat,
+            get_link_alive,
+            set_simulation_scenario,
+            set_simulation_time_scale,
+            inject_simulated_alarm,
+            clear_simulated_alarms,
+            set_alarm_action,
+            get_alarm_action,
+            start_mqtt_publishing,
+            stop_mqtt_publishing,
+            start_modbus_server,
+            stop_modbus_server,
+            start_http_server,
+            stop_http_server,
             get_alarm_descriptions,
+            get_active_alarms_detailed,
+            get_data_ages,
+            snapshot_bms,
+            diff_snapshot,
+            get_current_direction,
+            set_direction_config,
+            acknowledge_alarm,
+            get_battery_life_estimate,
+            get_health_summary,
+            get_time_estimate,
+            reset_energy_counters,
+            get_session_energy,
             get_system_status_name,
             get_work_status_name,
             get_operation_status_name,
+            get_prohibition_names,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
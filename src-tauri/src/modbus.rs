@@ -0,0 +1,329 @@
+//! Modbus TCP Server
+//! Exposes the latest `BmsData` to SCADA systems (read-only).
+//!
+//! Register map (0-based addresses, unit id ignored):
+//!
+//! | Type             | Address | Value                                   |
+//! |------------------|---------|-----------------------------------------|
+//! | Input register   | 0       | SOC (%)                                 |
+//! | Input register   | 1       | SOH (%)                                 |
+//! | Input register   | 2       | Battery voltage (V × 10)                |
+//! | Input register   | 3       | Battery current (A × 10, signed i16)    |
+//! | Input register   | 4       | Max temperature (°C × 10, signed i16)   |
+//! | Input register   | 5       | Min temperature (°C × 10, signed i16)   |
+//! | Input register   | 6       | Max cell voltage (mV)                   |
+//! | Input register   | 7       | Min cell voltage (mV)                   |
+//! | Input register   | 8       | Backup time (minutes)                   |
+//! | Input register   | 9       | Max alarm severity (0-3)                |
+//! | Input register   | 10      | Connected (0/1)                         |
+//! | Coil / discrete  | 0-63    | Alarm status bits (same as 0xC0 frame)  |
+//!
+//! Supported functions: 0x01 Read Coils, 0x02 Read Discrete Inputs,
+//! 0x04 Read Input Registers. Missing data reads as 0.
+
+use crate::bms_types::*;
+
+#[cfg(feature = "modbus")]
+use parking_lot::Mutex;
+#[cfg(feature = "modbus")]
+use std::net::SocketAddr;
+#[cfg(feature = "modbus")]
+use std::sync::Arc;
+
+/// Number of input registers in the map
+pub const INPUT_REGISTER_COUNT: usize = 11;
+/// Number of alarm coils in the map
+pub const ALARM_COIL_COUNT: usize = 64;
+
+const FN_READ_COILS: u8 = 0x01;
+const FN_READ_DISCRETE_INPUTS: u8 = 0x02;
+const FN_READ_INPUT_REGISTERS: u8 = 0x04;
+
+const EX_ILLEGAL_FUNCTION: u8 = 0x01;
+const EX_ILLEGAL_DATA_ADDRESS: u8 = 0x02;
+const EX_ILLEGAL_DATA_VALUE: u8 = 0x03;
+
+/// Build the input register bank from BMS data
+pub fn build_input_registers(data: &BmsData) -> [u16; INPUT_REGISTER_COUNT] {
+    let mut registers = [0u16; INPUT_REGISTER_COUNT];
+
+    if let Some(ref soc_soh) = data.soc_soh {
+        registers[0] = soc_soh.soc;
+        registers[1] = soc_soh.soh;
+        registers[8] = soc_soh.backup_time_minutes;
+    }
+    if let Some(ref vc) = data.voltage_current {
+        registers[2] = (vc.voltage * 10.0).round() as u16;
+        registers[3] = (vc.current * 10.0).round() as i16 as u16;
+    }
+    if let Some(ref temp) = data.temperature {
+        registers[4] = (temp.max_temperature * 10.0).round() as i16 as u16;
+        registers[5] = (temp.min_temperature * 10.0).round() as i16 as u16;
+    }
+    if let Some(ref cv) = data.cell_voltage {
+        registers[6] = (cv.max_voltage * 1000.0).round() as u16;
+        registers[7] = (cv.min_voltage * 1000.0).round() as u16;
+    }
+    if let Some(ref alarm) = data.alarm_status {
+        registers[9] = alarm.max_severity as u16;
+    }
+    registers[10] = data.connected as u16;
+
+    registers
+}
+
+/// Build the alarm coil bank from BMS data
+pub fn build_alarm_coils(data: &BmsData) -> u64 {
+    data.alarm_status.as_ref().map_or(0, |a| a.raw_status)
+}
+
+/// Handle a Modbus PDU (function code + data) and return the response PDU
+pub fn handle_pdu(pdu: &[u8], data: &BmsData) -> Vec<u8> {
+    let Some(&function) = pdu.first() else {
+        return exception(0, EX_ILLEGAL_FUNCTION);
+    };
+
+    if !matches!(
+        function,
+        FN_READ_COILS | FN_READ_DISCRETE_INPUTS | FN_READ_INPUT_REGISTERS
+    ) {
+        return exception(function, EX_ILLEGAL_FUNCTION);
+    }
+
+    if pdu.len() < 5 {
+        return exception(function, EX_ILLEGAL_DATA_VALUE);
+    }
+
+    let start = u16::from_be_bytes([pdu[1], pdu[2]]) as usize;
+    let quantity = u16::from_be_bytes([pdu[3], pdu[4]]) as usize;
+
+    match function {
+        FN_READ_INPUT_REGISTERS => {
+            if quantity == 0 || quantity > 125 {
+                return exception(function, EX_ILLEGAL_DATA_VALUE);
+            }
+            if start + quantity > INPUT_REGISTER_COUNT {
+                return exception(function, EX_ILLEGAL_DATA_ADDRESS);
+            }
+
+            let registers = build_input_registers(data);
+            let mut response = vec![function, (quantity * 2) as u8];
+            for value in &registers[start..start + quantity] {
+                response.extend_from_slice(&value.to_be_bytes());
+            }
+            response
+        }
+        _ => {
+            if quantity == 0 || quantity > 2000 {
+                return exception(function, EX_ILLEGAL_DATA_VALUE);
+            }
+            if start + quantity > ALARM_COIL_COUNT {
+                return exception(function, EX_ILLEGAL_DATA_ADDRESS);
+            }
+
+            let coils = build_alarm_coils(data);
+            let byte_count = quantity.div_ceil(8);
+            let mut response = vec![function, byte_count as u8];
+            response.resize(2 + byte_count, 0);
+            for i in 0..quantity {
+                if (coils >> (start + i)) & 1 == 1 {
+                    response[2 + i / 8] |= 1 << (i % 8);
+                }
+            }
+            response
+        }
+    }
+}
+
+fn exception(function: u8, code: u8) -> Vec<u8> {
+    vec![function | 0x80, code]
+}
+
+/// Running Modbus TCP server
+#[cfg(feature = "modbus")]
+pub struct ModbusServer {
+    local_addr: SocketAddr,
+    shutdown: tokio::sync::watch::Sender<bool>,
+}
+
+#[cfg(feature = "modbus")]
+impl ModbusServer {
+    /// Bind to `port` on all interfaces and serve `bms_data`
+    pub async fn start(port: u16, bms_data: Arc<Mutex<BmsData>>) -> std::io::Result<Self> {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+        let local_addr = listener.local_addr()?;
+        let (shutdown, mut shutdown_rx) = tokio::sync::watch::channel(false);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, peer)) => {
+                            log::info!("Modbus client connected: {}", peer);
+                            let data = bms_data.clone();
+                            let client_shutdown = shutdown_rx.clone();
+                            tokio::spawn(serve_client(stream, data, client_shutdown));
+                        }
+                        Err(e) => log::warn!("Modbus accept failed: {}", e),
+                    },
+                }
+            }
+            log::info!("Modbus server stopped");
+        });
+
+        log::info!("Modbus TCP server listening on {}", local_addr);
+        Ok(ModbusServer {
+            local_addr,
+            shutdown,
+        })
+    }
+
+    /// Address the server is bound to
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting and close all client connections
+    pub fn stop(&self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+#[cfg(feature = "modbus")]
+impl Drop for ModbusServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(feature = "modbus")]
+async fn serve_client(
+    mut stream: tokio::net::TcpStream,
+    bms_data: Arc<Mutex<BmsData>>,
+    mut shutdown: tokio::sync::watch::Receiver<bool>,
+) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    loop {
+        // MBAP header: transaction(2) protocol(2) length(2) unit(1)
+        let mut header = [0u8; 7];
+        let read = tokio::select! {
+            _ = shutdown.changed() => return,
+            read = stream.read_exact(&mut header) => read,
+        };
+        if read.is_err() {
+            return;
+        }
+
+        let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+        if !(2..=254).contains(&length) {
+            return;
+        }
+
+        let mut pdu = vec![0u8; length - 1];
+        if stream.read_exact(&mut pdu).await.is_err() {
+            return;
+        }
+
+        let response_pdu = {
+            let data = bms_data.lock();
+            handle_pdu(&pdu, &data)
+        };
+
+        let mut response = Vec::with_capacity(7 + response_pdu.len());
+        response.extend_from_slice(&header[0..4]);
+        response.extend_from_slice(&((response_pdu.len() + 1) as u16).to_be_bytes());
+        response.push(header[6]);
+        response.extend_from_slice(&response_pdu);
+
+        if stream.write_all(&response).await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_data() -> BmsData {
+        BmsData {
+            connected: true,
+            soc_soh: Some(SocSohData {
+                soc: 80,
+                soh: 100,
+                backup_time_minutes: 60,
+            }),
+            voltage_current: Some(VoltageCurrentData {
+                voltage: 812.1,
+                current: -120.0,
+                power: 97.45,
+                power_w: 97_452.0,
+                raw_current: -120.0,
+            }),
+            alarm_status: Some(AlarmStatus {
+                raw_status: (1 << 0) | (1 << 31),
+                active_alarms: vec![0, 31],
+                unknown_alarms: vec![],
+                max_severity: 3,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_input_register_scaling() {
+        let registers = build_input_registers(&sample_data());
+
+        assert_eq!(registers[0], 80);
+        assert_eq!(registers[1], 100);
+        assert_eq!(registers[2], 8121);
+        assert_eq!(registers[3] as i16, -1200);
+        assert_eq!(registers[9], 3);
+        assert_eq!(registers[10], 1);
+    }
+
+    #[test]
+    fn test_read_coils_and_exceptions() {
+        let data = sample_data();
+
+        // Coils 0..40: bit 0 and bit 31 set
+        let response = handle_pdu(&[0x01, 0x00, 0x00, 0x00, 0x28], &data);
+        assert_eq!(response, vec![0x01, 5, 0x01, 0x00, 0x00, 0x80, 0x00]);
+
+        // Register out of range
+        let response = handle_pdu(&[0x04, 0x00, 0x0A, 0x00, 0x02], &data);
+        assert_eq!(response, vec![0x84, EX_ILLEGAL_DATA_ADDRESS]);
+
+        // Writes are not supported
+        let response = handle_pdu(&[0x06, 0x00, 0x00, 0x00, 0x01], &data);
+        assert_eq!(response, vec![0x86, EX_ILLEGAL_FUNCTION]);
+    }
+
+    #[cfg(feature = "modbus")]
+    #[tokio::test]
+    async fn test_read_register_over_tcp() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let bms_data = Arc::new(Mutex::new(sample_data()));
+        let server = ModbusServer::start(0, bms_data).await.unwrap();
+        let addr = SocketAddr::from(([127, 0, 0, 1], server.local_addr().port()));
+
+        let mut client = tokio::net::TcpStream::connect(addr).await.unwrap();
+        // Transaction 0x0001, read 1 input register at address 0 (SOC)
+        let request = [
+            0x00, 0x01, 0x00, 0x00, 0x00, 0x06, 0x01, 0x04, 0x00, 0x00, 0x00, 0x01,
+        ];
+        client.write_all(&request).await.unwrap();
+
+        let mut response = [0u8; 11];
+        client.read_exact(&mut response).await.unwrap();
+        assert_eq!(&response[0..2], &[0x00, 0x01]);
+        assert_eq!(response[7], 0x04);
+        assert_eq!(response[8], 2);
+        assert_eq!(u16::from_be_bytes([response[9], response[10]]), 80);
+
+        server.stop();
+    }
+}
@@ -0,0 +1,266 @@
+//! MQTT Telemetry Publisher
+//! Publishes each updated `BmsData` as JSON to `<prefix>/<bms address>/data`.
+//! The broker client is only compiled with the `mqtt` feature.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[cfg(feature = "mqtt")]
+use crate::bms_types::BmsData;
+#[cfg(feature = "mqtt")]
+use parking_lot::Mutex;
+#[cfg(feature = "mqtt")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "mqtt")]
+use std::sync::Arc;
+#[cfg(feature = "mqtt")]
+use std::time::Duration;
+
+/// MQTT broker connection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MqttConfig {
+    /// Broker host name or IP
+    pub host: String,
+    /// Broker port
+    pub port: u16,
+    /// Optional username
+    pub username: Option<String>,
+    /// Optional password
+    pub password: Option<String>,
+    /// Topic prefix, samples go to `<prefix>/<address>/data`
+    pub topic_prefix: String,
+    /// MQTT client id
+    pub client_id: String,
+    /// Maximum samples kept while the broker is unreachable
+    pub buffer_size: usize,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        MqttConfig {
+            host: "localhost".to_string(),
+            port: 1883,
+            username: None,
+            password: None,
+            topic_prefix: "bms".to_string(),
+            client_id: "bms-monitor".to_string(),
+            buffer_size: 100,
+        }
+    }
+}
+
+impl MqttConfig {
+    /// Data topic for a BMS address
+    pub fn data_topic(&self, bms_address: u8) -> String {
+        format!(
+            "{}/{}/data",
+            self.topic_prefix.trim_end_matches('/'),
+            bms_address
+        )
+    }
+}
+
+/// Bounded FIFO of pending samples; the oldest sample is dropped when full
+#[derive(Debug)]
+pub struct SampleBuffer<T = Vec<u8>> {
+    samples: VecDeque<T>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl<T> SampleBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        SampleBuffer {
+            samples: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            dropped: 0,
+        }
+    }
+
+    pub fn push(&mut self, payload: T) {
+        if self.samples.len() >= self.capacity {
+            self.samples.pop_front();
+            self.dropped += 1;
+        }
+        self.samples.push_back(payload);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.samples.pop_front()
+    }
+
+    /// Put a payload back at the head after a failed publish
+    pub fn push_front(&mut self, payload: T) {
+        if self.samples.len() >= self.capacity {
+            self.dropped += 1;
+            return;
+        }
+        self.samples.push_front(payload);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Number of samples discarded because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+/// Topic and JSON payload of a sample waiting for the broker
+#[cfg(feature = "mqtt")]
+type PendingSample = (String, Vec<u8>);
+
+/// MQTT publisher for BMS telemetry
+#[cfg(feature = "mqtt")]
+pub struct MqttPublisher {
+    client: rumqttc::Client,
+    config: MqttConfig,
+    buffer: Arc<Mutex<SampleBuffer<PendingSample>>>,
+    connected: Arc<AtomicBool>,
+    running: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "mqtt")]
+impl MqttPublisher {
+    /// Connect to the broker and start the network thread
+    pub fn start(config: &MqttConfig) -> Result<Self, String> {
+        use rumqttc::{Client, Event, MqttOptions, Packet};
+
+        if config.host.is_empty() {
+            return Err("MQTT host is empty".to_string());
+        }
+
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(ref username) = config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+
+        let (client, mut connection) = Client::new(options, 10);
+        let connected = Arc::new(AtomicBool::new(false));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_connected = connected.clone();
+        let thread_running = running.clone();
+        std::thread::Builder::new()
+            .name("mqtt-publisher".to_string())
+            .spawn(move || {
+                for event in connection.iter() {
+                    if !thread_running.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    match event {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            thread_connected.store(true, Ordering::SeqCst);
+                            log::info!("MQTT broker connected");
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            if thread_connected.swap(false, Ordering::SeqCst) {
+                                log::warn!("MQTT broker connection lost: {}", e);
+                            }
+                            // The iterator reconnects on the next poll
+                            std::thread::sleep(Duration::from_secs(1));
+                        }
+                    }
+                }
+                thread_connected.store(false, Ordering::SeqCst);
+            })
+            .map_err(|e| format!("Failed to start MQTT thread: {}", e))?;
+
+        Ok(MqttPublisher {
+            client,
+            config: config.clone(),
+            buffer: Arc::new(Mutex::new(SampleBuffer::new(config.buffer_size))),
+            connected,
+            running,
+        })
+    }
+
+    /// Queue a sample of `bms_address` and flush the buffer if the broker is
+    /// reachable
+    pub fn publish(&self, bms_address: u8, data: &BmsData) {
+        let payload = match serde_json::to_vec(data) {
+            Ok(payload) => payload,
+            Err(e) => {
+                log::warn!("Failed to serialize BMS data for MQTT: {}", e);
+                return;
+            }
+        };
+
+        let mut buffer = self.buffer.lock();
+        buffer.push((self.config.data_topic(bms_address), payload));
+
+        if !self.connected.load(Ordering::SeqCst) {
+            return;
+        }
+
+        while let Some((topic, payload)) = buffer.pop() {
+            let qos = rumqttc::QoS::AtLeastOnce;
+            if let Err(e) = self.client.try_publish(&topic, qos, false, payload.clone()) {
+                log::debug!("MQTT publish deferred: {}", e);
+                buffer.push_front((topic, payload));
+                break;
+            }
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    /// Number of samples dropped while the broker was unreachable
+    pub fn dropped_samples(&self) -> u64 {
+        self.buffer.lock().dropped()
+    }
+
+    /// Disconnect from the broker and stop the network thread
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        let _ = self.client.try_disconnect();
+        log::info!("MQTT publishing stopped");
+    }
+}
+
+#[cfg(feature = "mqtt")]
+impl Drop for MqttPublisher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_buffer_is_bounded() {
+        let mut buffer = SampleBuffer::new(3);
+        for i in 0..5u8 {
+            buffer.push(vec![i]);
+        }
+
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.dropped(), 2);
+        assert_eq!(buffer.pop(), Some(vec![2]));
+        assert_eq!(buffer.pop(), Some(vec![3]));
+        assert_eq!(buffer.pop(), Some(vec![4]));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_data_topic() {
+        let config = MqttConfig {
+            topic_prefix: "site1/bms/".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.data_topic(1), "site1/bms/1/data");
+    }
+}
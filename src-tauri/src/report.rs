@@ -0,0 +1,202 @@
+//! Text Report
+//! Markdown summary of the current `BmsData` for pasting into support tickets.
+
+use crate::alarms::active_alarm_details;
+use crate::bms_types::*;
+use std::fmt::Write;
+
+/// Shown for sections whose command hasn't been decoded yet
+pub const NO_DATA: &str = "no data";
+
+/// Format `data` as a Markdown report; `generated_ms` is the report time
+pub fn format_report(data: &BmsData, generated_ms: i64) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# BMS Report");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Generated: {}", format_time(generated_ms));
+
+    section(
+        &mut out,
+        "Connection",
+        Some(vec![
+            format!("Connected: {}", if data.connected { "yes" } else { "no" }),
+            format!(
+                "Last update: {}",
+                if data.timestamp > 0 {
+                    format_time(data.timestamp)
+                } else {
+                    NO_DATA.to_string()
+                }
+            ),
+        ]),
+    );
+
+    section(
+        &mut out,
+        "State of Charge",
+        data.soc_soh.as_ref().map(|s| {
+            vec![
+                format!("SOC: {} %", s.soc),
+                format!("SOH: {} %", s.soh),
+                format!("Backup time: {} min", s.backup_time_minutes),
+            ]
+        }),
+    );
+
+    section(
+        &mut out,
+        "Voltage and Current",
+        data.voltage_current.as_ref().map(|vc| {
+            vec![
+                format!("Voltage: {:.1} V", vc.voltage),
+                format!("Current: {:.1} A (positive = discharge)", vc.current),
+                format!("Power: {:.2} kW", vc.power),
+            ]
+        }),
+    );
+
+    section(
+        &mut out,
+        "Cell Voltages",
+        data.cell_voltage.as_ref().map(|c| {
+            vec![
+                format!(
+                    "Max: {:.3} V (pack {}, cell {})",
+                    c.max_voltage, c.max_voltage_pack_no, c.max_voltage_cell_no
+                ),
+                format!(
+                    "Min: {:.3} V (pack {}, cell {})",
+                    c.min_voltage, c.min_voltage_pack_no, c.min_voltage_cell_no
+                ),
+                format!("Delta: {:.0} mV", c.voltage_delta * 1000.0),
+            ]
+        }),
+    );
+
+    section(
+        &mut out,
+        "Temperatures",
+        data.temperature.as_ref().map(|t| {
+            vec![
+                format!(
+                    "Max: {:.1} °C (pack {}, sensor {})",
+                    t.max_temperature, t.max_temp_pack_no, t.max_temp_sensor_no
+                ),
+                format!(
+                    "Min: {:.1} °C (pack {}, sensor {})",
+                    t.min_temperature, t.min_temp_pack_no, t.min_temp_sensor_no
+                ),
+                format!("Delta: {:.1} °C", t.temp_delta),
+            ]
+        }),
+    );
+
+    section(
+        &mut out,
+        "Active Alarms",
+        data.alarm_status.as_ref().map(|_| {
+            let alarms = active_alarm_details(data);
+            if alarms.is_empty() {
+                return vec!["none".to_string()];
+            }
+            alarms
+                .iter()
+                .map(|a| format!("{} (bit {}, severity {})", a.description, a.bit, a.severity))
+                .collect()
+        }),
+    );
+
+    section(
+        &mut out,
+        "Software Version",
+        data.software_version.as_ref().map(|v| vec![v.clone()]),
+    );
+
+    out
+}
+
+/// Append a `## title` section with one bullet per line, or `NO_DATA`
+fn section(out: &mut String, title: &str, lines: Option<Vec<String>>) {
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## {}", title);
+    let _ = writeln!(out);
+    for line in lines.unwrap_or_else(|| vec![NO_DATA.to_string()]) {
+        let _ = writeln!(out, "- {}", line);
+    }
+}
+
+/// Unix time in ms as UTC date and time
+fn format_time(ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_contains_fields() {
+        let empty = format_report(&BmsData::default(), 0);
+        assert!(empty.contains("Connected: no"));
+        assert!(empty.contains("## State of Charge\n\n- no data"));
+        assert!(empty.contains("## Active Alarms\n\n- no data"));
+
+        let data = BmsData {
+            timestamp: 1_767_225_600_000,
+            connected: true,
+            soc_soh: Some(SocSohData {
+                soc: 80,
+                soh: 98,
+                backup_time_minutes: 60,
+            }),
+            voltage_current: Some(VoltageCurrentData {
+                voltage: 812.1,
+                current: -120.0,
+                power: 97.45,
+                power_w: 97_452.0,
+                raw_current: -120.0,
+            }),
+            temperature: Some(TemperatureData {
+                max_temperature: 27.5,
+                max_temp_pack_no: 2,
+                max_temp_sensor_no: 4,
+                min_temperature: 25.0,
+                min_temp_pack_no: 1,
+                min_temp_sensor_no: 1,
+                temp_delta: 2.5,
+            }),
+            alarm_status: Some(AlarmStatus {
+                raw_status: 1 << 31,
+                active_alarms: vec![31],
+                unknown_alarms: vec![],
+                max_severity: 3,
+            }),
+            software_version: Some("V2.19S".to_string()),
+            ..Default::default()
+        };
+        let report = format_report(&data, 1_767_225_660_000);
+
+        for expected in [
+            "Generated: 2026-01-01 00:01:00 UTC",
+            "Connected: yes",
+            "Last update: 2026-01-01 00:00:00 UTC",
+            "SOC: 80 %",
+            "SOH: 98 %",
+            "Voltage: 812.1 V",
+            "Current: -120.0 A",
+            "Max: 27.5 °C (pack 2, sensor 4)",
+            "EPO shut down (bit 31, severity 3)",
+            "- V2.19S",
+        ] {
+            assert!(
+                report.contains(expected),
+                "{:?} missing in\n{}",
+                expected,
+                report
+            );
+        }
+        assert!(report.contains("## Cell Voltages\n\n- no data"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Data Snapshots
+//! Captured `BmsData` states for before/after comparison, e.g. around a reset
+
+use crate::bms_types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Snapshots kept; the oldest is dropped when a new one is taken
+pub const MAX_SNAPSHOTS: usize = 32;
+
+/// Reads one metric from the data
+type Metric = fn(&BmsData) -> Option<f64>;
+
+/// Key metrics compared by `diff_data`
+const DIFF_FIELDS: [(&str, Metric); 13] = [
+    ("soc", |d| d.soc_soh.as_ref().map(|s| s.soc as f64)),
+    ("soh", |d| d.soc_soh.as_ref().map(|s| s.soh as f64)),
+    ("voltage", |d| {
+        d.voltage_current.as_ref().map(|v| v.voltage as f64)
+    }),
+    ("current", |d| {
+        d.voltage_current.as_ref().map(|v| v.current as f64)
+    }),
+    ("power", |d| {
+        d.voltage_current.as_ref().map(|v| v.power as f64)
+    }),
+    ("maxCellVoltage", |d| {
+        d.cell_voltage.as_ref().map(|c| c.max_voltage as f64)
+    }),
+    ("minCellVoltage", |d| {
+        d.cell_voltage.as_ref().map(|c| c.min_voltage as f64)
+    }),
+    ("maxTemperature", |d| {
+        d.temperature.as_ref().map(|t| t.max_temperature as f64)
+    }),
+    ("minTemperature", |d| {
+        d.temperature.as_ref().map(|t| t.min_temperature as f64)
+    }),
+    ("chargeCurrentLimit", |d| {
+        d.limits.as_ref().map(|l| l.charge_current_limit as f64)
+    }),
+    ("dischargeCurrentLimit", |d| {
+        d.limits.as_ref().map(|l| l.discharge_current_limit as f64)
+    }),
+    ("chargeEnergy", |d| {
+        d.accumulated_power.as_ref().map(|p| p.charge_energy as f64)
+    }),
+    ("dischargeEnergy", |d| {
+        d.accumulated_power
+            .as_ref()
+            .map(|p| p.discharge_energy as f64)
+    }),
+];
+
+/// One metric that differs between a snapshot and the current data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    /// Metric name, as in the frontend's camelCase data
+    pub field: String,
+    /// Value in the snapshot (None if not received yet)
+    pub before: Option<f64>,
+    /// Current value (None if cleared)
+    pub after: Option<f64>,
+    /// `after - before`, when both are known
+    pub delta: Option<f64>,
+}
+
+/// Differences between a snapshot and the current data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+    /// Snapshot compared against
+    pub snapshot_id: u64,
+    /// Time from the snapshot's data to the current data in ms
+    pub elapsed_ms: i64,
+    /// Metrics whose value changed
+    pub changes: Vec<FieldChange>,
+    /// Alarm bits active now but not in the snapshot
+    pub new_alarms: Vec<u8>,
+    /// Alarm bits active in the snapshot but not now
+    pub cleared_alarms: Vec<u8>,
+}
+
+/// Active alarm bits, defined and reserved, in ascending order
+fn alarm_bits(data: &BmsData) -> Vec<u8> {
+    let mut bits: Vec<u8> = data
+        .alarm_status
+        .iter()
+        .flat_map(|a| a.active_alarms.iter().chain(&a.unknown_alarms))
+        .copied()
+        .collect();
+    bits.sort_unstable();
+    bits
+}
+
+/// Compare two data states; unchanged metrics are left out
+pub fn diff_data(snapshot_id: u64, before: &BmsData, after: &BmsData) -> SnapshotDiff {
+    let changes = DIFF_FIELDS
+        .iter()
+        .filter_map(|(field, value)| {
+            let (old, new) = (value(before), value(after));
+            (old != new).then(|| FieldChange {
+                field: field.to_string(),
+                before: old,
+                after: new,
+                delta: old.zip(new).map(|(old, new)| new - old),
+            })
+        })
+        .collect();
+
+    let (old_alarms, new_alarms) = (alarm_bits(before), alarm_bits(after));
+    SnapshotDiff {
+        snapshot_id,
+        elapsed_ms: after.timestamp - before.timestamp,
+        changes,
+        new_alarms: new_alarms
+            .iter()
+            .filter(|bit| !old_alarms.contains(bit))
+            .copied()
+            .collect(),
+        cleared_alarms: old_alarms
+            .iter()
+            .filter(|bit| !new_alarms.contains(bit))
+            .copied()
+            .collect(),
+    }
+}
+
+/// Snapshots by id, bounded to `MAX_SNAPSHOTS`
+#[derive(Debug, Default)]
+pub struct SnapshotStore {
+    snapshots: BTreeMap<u64, BmsData>,
+    last_id: u64,
+}
+
+impl SnapshotStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep a copy of `data`; returns its id
+    pub fn take(&mut self, data: &BmsData) -> u64 {
+        if self.snapshots.len() == MAX_SNAPSHOTS {
+            self.snapshots.pop_first();
+        }
+        self.last_id += 1;
+        self.snapshots.insert(self.last_id, data.clone());
+        self.last_id
+    }
+
+    /// Compare snapshot `id` with `current`
+    pub fn diff(&self, id: u64, current: &BmsData) -> Result<SnapshotDiff, String> {
+        let snapshot = self
+            .snapshots
+            .get(&id)
+            .ok_or_else(|| format!("Unknown snapshot id {}", id))?;
+        Ok(diff_data(id, snapshot, current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_reports_soc_and_alarm_changes() {
+        let mut data = BmsData {
+            timestamp: 1_000,
+            soc_soh: Some(SocSohData {
+                soc: 80,
+                soh: 98,
+                backup_time_minutes: 60,
+            }),
+            alarm_status: Some(AlarmStatus {
+                raw_status: 1 << 3,
+                active_alarms: vec![3],
+                unknown_alarms: vec![],
+                max_severity: 1,
+            }),
+            ..Default::default()
+        };
+
+        let mut store = SnapshotStore::new();
+        let id = store.take(&data);
+        assert!(store.diff(id, &data).unwrap().changes.is_empty());
+
+        data.timestamp = 61_000;
+        data.soc_soh.as_mut().unwrap().soc = 75;
+        data.alarm_status = Some(AlarmStatus {
+            raw_status: 1 << 5,
+            active_alarms: vec![5],
+            unknown_alarms: vec![],
+            max_severity: 2,
+        });
+
+        let diff = store.diff(id, &data).unwrap();
+        assert_eq!(diff.elapsed_ms, 60_000);
+        assert_eq!(
+            diff.changes,
+            vec![FieldChange {
+                field: "soc".to_string(),
+                before: Some(80.0),
+                after: Some(75.0),
+                delta: Some(-5.0),
+            }]
+        );
+        assert_eq!(diff.new_alarms, vec![5]);
+        assert_eq!(diff.cleared_alarms, vec![3]);
+
+        assert!(store.diff(id + 1, &data).is_err());
+        for _ in 0..MAX_SNAPSHOTS {
+            store.take(&data);
+        }
+        assert!(store.diff(id, &data).is_err());
+    }
+}
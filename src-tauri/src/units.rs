@@ -0,0 +1,170 @@
+//! Unit Conversion Helpers
+//! `BmsData` is always stored in SI units; conversions are applied for display only
+
+use crate::bms_types::*;
+use serde::{Deserialize, Serialize};
+
+/// Display unit system
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitSystem {
+    /// °C, A (as reported by the BMS)
+    #[default]
+    Metric,
+    /// °F, A
+    Imperial,
+}
+
+impl UnitSystem {
+    /// Temperature unit label
+    pub fn temperature_unit(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "°C",
+            UnitSystem::Imperial => "°F",
+        }
+    }
+}
+
+/// Convert an absolute temperature from °C to °F
+pub fn celsius_to_fahrenheit(celsius: f32) -> f32 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+/// Convert an absolute temperature from °F to °C
+pub fn fahrenheit_to_celsius(fahrenheit: f32) -> f32 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+/// Convert a temperature difference from °C to °F (no offset)
+pub fn celsius_delta_to_fahrenheit(delta: f32) -> f32 {
+    delta * 9.0 / 5.0
+}
+
+/// Convert a temperature difference from °F to °C (no offset)
+pub fn fahrenheit_delta_to_celsius(delta: f32) -> f32 {
+    delta * 5.0 / 9.0
+}
+
+/// Convert current from A to mA
+pub fn amps_to_milliamps(amps: f32) -> f32 {
+    amps * 1000.0
+}
+
+/// Convert current from mA to A
+pub fn milliamps_to_amps(milliamps: f32) -> f32 {
+    milliamps / 1000.0
+}
+
+impl TemperatureData {
+    /// Maximum temperature in the given unit system
+    pub fn max_temperature_in(&self, units: UnitSystem) -> f32 {
+        convert_temperature(self.max_temperature, units)
+    }
+
+    /// Minimum temperature in the given unit system
+    pub fn min_temperature_in(&self, units: UnitSystem) -> f32 {
+        convert_temperature(self.min_temperature, units)
+    }
+
+    /// Temperature difference in the given unit system
+    pub fn temp_delta_in(&self, units: UnitSystem) -> f32 {
+        match units {
+            UnitSystem::Metric => self.temp_delta,
+            UnitSystem::Imperial => celsius_delta_to_fahrenheit(self.temp_delta),
+        }
+    }
+}
+
+fn convert_temperature(celsius: f32, units: UnitSystem) -> f32 {
+    match units {
+        UnitSystem::Metric => celsius,
+        UnitSystem::Imperial => celsius_to_fahrenheit(celsius),
+    }
+}
+
+/// BMS data converted for display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BmsDataDisplay {
+    /// Unit system the values are expressed in
+    pub unit_system: UnitSystem,
+    /// Temperature unit label
+    pub temperature_unit: String,
+    /// Converted data
+    pub data: BmsData,
+}
+
+impl BmsDataDisplay {
+    /// Build a display copy of `data` in the given unit system
+    pub fn new(data: &BmsData, units: UnitSystem) -> Self {
+        let mut data = data.clone();
+        data.temperature = data.temperature.map(|temp| TemperatureData {
+            max_temperature: temp.max_temperature_in(units),
+            min_temperature: temp.min_temperature_in(units),
+            temp_delta: temp.temp_delta_in(units),
+            ..temp
+        });
+        for temperature in &mut data.sensor_temperatures {
+            *temperature = convert_temperature(*temperature, units);
+        }
+
+        BmsDataDisplay {
+            unit_system: units,
+            temperature_unit: units.temperature_unit().to_string(),
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temperature_round_trip() {
+        for celsius in [-40.0f32, -17.5, 0.0, 24.8, 27.0, 100.0] {
+            let back = fahrenheit_to_celsius(celsius_to_fahrenheit(celsius));
+            assert!((back - celsius).abs() < 0.001);
+        }
+        assert!((celsius_to_fahrenheit(100.0) - 212.0).abs() < 0.001);
+        assert!((celsius_to_fahrenheit(-40.0) - (-40.0)).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_delta_and_current_round_trip() {
+        let delta = 2.2f32;
+        let back = fahrenheit_delta_to_celsius(celsius_delta_to_fahrenheit(delta));
+        assert!((back - delta).abs() < 0.001);
+
+        let amps = -120.5f32;
+        assert!((milliamps_to_amps(amps_to_milliamps(amps)) - amps).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_display_converts_only_temperatures() {
+        let data = BmsData {
+            temperature: Some(TemperatureData {
+                max_temperature: 27.0,
+                max_temp_pack_no: 1,
+                max_temp_sensor_no: 3,
+                min_temperature: 25.0,
+                min_temp_pack_no: 2,
+                min_temp_sensor_no: 5,
+                temp_delta: 2.0,
+            }),
+            sensor_temperatures: vec![25.0, -40.0],
+            ..Default::default()
+        };
+
+        let display = BmsDataDisplay::new(&data, UnitSystem::Imperial);
+        let temp = display.data.temperature.unwrap();
+        assert!((temp.max_temperature - 80.6).abs() < 0.01);
+        assert!((temp.min_temperature - 77.0).abs() < 0.01);
+        assert!((temp.temp_delta - 3.6).abs() < 0.01);
+        assert_eq!(temp.max_temp_sensor_no, 3);
+        assert_eq!(display.data.sensor_temperatures, vec![77.0, -40.0]);
+        assert_eq!(display.temperature_unit, "°F");
+
+        // Source data stays in SI
+        assert_eq!(data.temperature.unwrap().max_temperature, 27.0);
+    }
+}
@@ -1,7 +1,7 @@
 // BMS Store - Svelte 5 reactive state management
 import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
-import type { BMSData, ChargeDischargeLimits, SocSohData, VoltageCurrentData, CellVoltageData, TemperatureData, OperationStatusData, AccumulatedTimesData, AccumulatedPowerData } from '../types/bms';
+import type { BMSData, ChargeDischargeLimits, SocSohData, VoltageCurrentData, CellVoltageData, PackVoltage, TemperatureData, OperationStatusData, BalancingStatus, AccumulatedTimesData, AccumulatedPowerData, SoftwareVersion, DebugStatusData } from '../types/bms';
 import { logCanFrame, getIsRecording } from './debugStore.svelte';
 
 // CAN frame event payload from Rust
@@ -26,12 +26,21 @@ let bmsData = $state<BMSData>({
   socSoh: null,
   voltageCurrent: null,
   cellVoltage: null,
+  packVoltages: [],
   temperature: null,
+  sensorTemperatures: [],
   operationStatus: null,
+  balancing: null,
   accumulatedTimes: null,
   accumulatedPower: null,
   softwareVersion: null,
+  softwareVersionInfo: null,
+  protocolCompatible: null,
+  indexErrors: [],
+  possiblyFrozen: false,
   alarmStatus: null,
+  debugStatus: null,
+  updatedAt: {},
 });
 
 // Polling interval
@@ -72,6 +81,7 @@ export async function connect(): Promise<boolean> {
       serial_port: (adapterType !== 'simulation' && adapterType !== 'itekon') ? selectedPort : null,
       serial_baud_rate: 115200,
       bms_address: 0x01,
+      host_address: 0x80,
     };
 
     const result = await invoke<{ success: boolean; data: boolean | null; error: string | null }>('connect', { config });
@@ -122,12 +132,21 @@ export async function disconnect(): Promise<void> {
       socSoh: null,
       voltageCurrent: null,
       cellVoltage: null,
+      packVoltages: [],
       temperature: null,
+      sensorTemperatures: [],
       operationStatus: null,
+      balancing: null,
       accumulatedTimes: null,
       accumulatedPower: null,
       softwareVersion: null,
+      softwareVersionInfo: null,
+      protocolCompatible: null,
+      indexErrors: [],
+      possiblyFrozen: false,
       alarmStatus: null,
+      debugStatus: null,
+      updatedAt: {},
     };
   } catch (error) {
     console.error('Disconnect error:', error);
@@ -150,12 +169,21 @@ export async function fetchBmsData(): Promise<void> {
       socSoh: SocSohData | null;
       voltageCurrent: VoltageCurrentData | null;
       cellVoltage: CellVoltageData | null;
+      packVoltages: PackVoltage[];
       temperature: TemperatureData | null;
+      sensorTemperatures: number[];
       operationStatus: OperationStatusData | null;
+      balancing: BalancingStatus | null;
       accumulatedTimes: AccumulatedTimesData | null;
       accumulatedPower: AccumulatedPowerData | null;
       softwareVersion: string | null;
-      alarmStatus: { rawStatus: number; activeAlarms: number[]; maxSeverity: number } | null;
+      softwareVersionInfo: SoftwareVersion | null;
+      protocolCompatible: boolean | null;
+      indexErrors: string[];
+      possiblyFrozen: boolean;
+      alarmStatus: { rawStatus: number; activeAlarms: number[]; unknownAlarms: number[]; maxSeverity: number } | null;
+      debugStatus: DebugStatusData | null;
+      updatedAt: Record<string, number>;
     }>('get_bms_data');
 
     // Data is already in camelCase from Rust
@@ -166,16 +194,26 @@ export async function fetchBmsData(): Promise<void> {
       socSoh: data.socSoh,
       voltageCurrent: data.voltageCurrent,
       cellVoltage: data.cellVoltage,
+      packVoltages: data.packVoltages,
       temperature: data.temperature,
+      sensorTemperatures: data.sensorTemperatures,
       operationStatus: data.operationStatus,
+      balancing: data.balancing,
       accumulatedTimes: data.accumulatedTimes,
       accumulatedPower: data.accumulatedPower,
       softwareVersion: data.softwareVersion,
+      softwareVersionInfo: data.softwareVersionInfo,
+      protocolCompatible: data.protocolCompatible,
+      indexErrors: data.indexErrors,
+      possiblyFrozen: data.possiblyFrozen,
       alarmStatus: data.alarmStatus ? {
         rawStatus: BigInt(data.alarmStatus.rawStatus),
         activeAlarms: data.alarmStatus.activeAlarms,
+        unknownAlarms: data.alarmStatus.unknownAlarms,
         maxSeverity: data.alarmStatus.maxSeverity,
       } : null,
+      debugStatus: data.debugStatus,
+      updatedAt: data.updatedAt,
     };
   } catch (error) {
     console.error('Failed to fetch BMS data:', error);
@@ -142,8 +142,12 @@ export interface VoltageCurrentData {
   voltage: number;
   /** Battery output current in A (0.1A resolution, positive=discharge, negative=charge) */
   current: number;
-  /** Power in kW (calculated) */
+  /** DC power in kW (calculated; magnitude, direction is the sign of current) */
   power: number;
+  /** DC power in W */
+  powerW: number;
+  /** Current as sent by the BMS, before applying the current sign convention */
+  rawCurrent: number;
 }
 
 /** Command 0x83 - Cell Voltage Data */
@@ -164,6 +168,14 @@ export interface CellVoltageData {
   voltageDelta: number;
 }
 
+/** Per-pack cell voltages from 0x83 continuation frames */
+export interface PackVoltage {
+  /** PACK number */
+  packNo: number;
+  /** Cell voltages in V by 0-based cell index */
+  cellVoltages: number[];
+}
+
 /** Command 0x84 - Temperature Data */
 export interface TemperatureData {
   /** Maximum temperature in °C (0.1°C resolution) */
@@ -196,6 +208,8 @@ export interface OperationStatusData {
   chargeProhibited: boolean;
   /** Discharge prohibited (cannot be allowed after OC/UV cleared) */
   dischargeProhibitedHard: boolean;
+  /** Raw prohibition byte, including undocumented bits (see get_prohibition_names) */
+  prohibitionFlags: number;
 }
 
 /** Command 0x86 - Accumulated Times */
@@ -212,6 +226,22 @@ export interface AccumulatedPowerData {
   chargeEnergy: number;
   /** Accumulated discharging energy in kWh (0.1kWh resolution) */
   dischargeEnergy: number;
+  /** Raw charging counter in 0.1kWh, exact where the kWh float is not */
+  chargeEnergyRaw: number;
+  /** Raw discharging counter in 0.1kWh */
+  dischargeEnergyRaw: number;
+}
+
+/** Command 0x8F - Software version with parsed numbers */
+export interface SoftwareVersion {
+  /** Raw version string as reported (e.g. "V2.19S") */
+  raw: string;
+  /** Major version number */
+  major: number | null;
+  /** Minor version number */
+  minor: number | null;
+  /** Trailing suffix after the numbers (e.g. "S") */
+  suffix: string | null;
 }
 
 /** Command 0xC0 - Alarm Status */
@@ -220,10 +250,142 @@ export interface AlarmStatus {
   rawStatus: bigint;
   /** Active alarm bits */
   activeAlarms: AlarmBit[];
+  /** Active bits without a defined alarm (reserved 17 and 41-63) */
+  unknownAlarms: number[];
   /** Severity level (1=mild, 2=moderate, 3=severe) */
   maxSeverity: number;
 }
 
+/** Command 0xD0 - Debug Status (undocumented bits, kept raw) */
+export interface DebugStatusData {
+  /** Raw payload bytes */
+  raw: number[];
+  /** Payload as a 64-bit status word; a JSON number, so exact only below 2^53 */
+  rawStatus: number;
+}
+
+/** Active alarm joined with its description (get_active_alarms_detailed) */
+export interface AlarmDetail {
+  bit: AlarmBit;
+  description: string;
+  /** Severity level (1=mild, 2=moderate, 3=severe) */
+  severity: number;
+  /** Acknowledged by the operator since it was last raised */
+  acknowledged: boolean;
+}
+
+/**
+ * Action run for newly raised critical alarms (set_alarm_action). Command
+ * arguments may contain {bit}, {description} and {severity}; no shell is used.
+ */
+export type AlarmAction =
+  | { kind: 'command'; program: string; args?: string[] }
+  | { kind: 'webhook'; url: string };
+
+/** End-of-life thresholds (get_battery_life_estimate) */
+export interface LifeThresholds {
+  /** Warn when SOH drops below this value in % */
+  minSoh: number;
+  /** Warn when the cycle count exceeds this value */
+  ratedCycles: number;
+}
+
+/** Remaining useful life estimate (get_battery_life_estimate) */
+export interface BatteryLifeEstimate {
+  soh: number | null;
+  cycleCount: number | null;
+  remainingCycles: number | null;
+  endOfLifeWarning: boolean;
+}
+
+/** Thresholds for get_health_summary */
+export interface HealthThresholds {
+  life: LifeThresholds;
+  /** Cell voltage delta in V above which a sensor fault is suspected */
+  maxVoltageDelta: number;
+  /** Temperature delta in °C above which a sensor fault is suspected */
+  maxTempDelta: number;
+  /** Chemistry whose bounds readings are checked against (unchecked if null) */
+  chemistry: Chemistry | null;
+}
+
+/** Cell chemistry of the pack */
+export type Chemistry = 'Lfp' | 'Nmc' | 'Lto';
+
+/** Derived health overview; flags are app-side heuristics, not BMS alarms */
+export interface HealthSummary {
+  life: BatteryLifeEstimate;
+  voltageDelta: number | null;
+  tempDelta: number | null;
+  voltageDeltaFault: boolean;
+  tempDeltaFault: boolean;
+  /** Max or min cell voltage is outside the chemistry's bounds */
+  cellVoltageOutOfRange: boolean;
+  /** Max or min temperature is outside the chemistry's bounds */
+  temperatureOutOfRange: boolean;
+  /** Set when the firmware is older than the supported protocol version */
+  firmwareWarning: string | null;
+}
+
+/** Pack parameters for get_time_estimate */
+export interface TimeEstimateConfig {
+  /** Nominal pack capacity in Ah */
+  nominalCapacityAh: number;
+  /** Currents within ±this value in A count as idle */
+  minCurrentA: number;
+}
+
+/** Estimated minutes to full/empty (get_time_estimate) */
+export interface TimeEstimate {
+  direction: 'Idle' | 'Charging' | 'Discharging' | null;
+  /** null when unknown, e.g. current near zero */
+  minutes: number | null;
+}
+
+/** Lifetime energy totals at the last counter reset (reset_energy_counters); lost on app restart */
+export interface EnergySnapshot {
+  /** Accumulated charging energy in kWh */
+  chargeEnergy: number;
+  /** Accumulated discharging energy in kWh */
+  dischargeEnergy: number;
+  /** Time of the snapshot in ms */
+  takenAt: number;
+}
+
+/** Energy since the last counter reset (get_session_energy) */
+export interface SessionEnergy {
+  /** Time of the snapshot in ms */
+  since: number;
+  /** Charged energy in kWh */
+  chargeEnergy: number;
+  /** Discharged energy in kWh */
+  dischargeEnergy: number;
+  /** A BMS counter was reset since the snapshot */
+  counterReset: boolean;
+}
+
+/** Frame rate and bus utilization (get_bus_load) */
+export interface BusLoad {
+  /** Received frames per second */
+  framesPerSec: number;
+  /** Sent and received bits per second */
+  bitsPerSec: number;
+  /** Percentage of the 125kbps CAN bus */
+  utilizationPercent: number;
+}
+
+/** Cells one pack is balancing */
+export interface PackBalancing {
+  packNo: number;
+  /** 0-based indices of the cells being balanced */
+  cells: number[];
+}
+
+/** Cell balancing activity per pack */
+export interface BalancingStatus {
+  packs: PackBalancing[];
+}
+
 /** Complete BMS Data */
 export interface BMSData {
   /** Timestamp of last update */
@@ -238,28 +400,209 @@ export interface BMSData {
   voltageCurrent: VoltageCurrentData | null;
   /** Cell voltage data */
   cellVoltage: CellVoltageData | null;
+  /** Per-pack cell voltages (empty unless reported) */
+  packVoltages: PackVoltage[];
   /** Temperature data */
   temperature: TemperatureData | null;
+  /** Temperature per sensor in °C (empty unless reported) */
+  sensorTemperatures: number[];
   /** Operation status */
   operationStatus: OperationStatusData | null;
+  /** Cell balancing (only if the BMS sends 0x85 continuation frames) */
+  balancing: BalancingStatus | null;
   /** Accumulated times */
   accumulatedTimes: AccumulatedTimesData | null;
   /** Accumulated power */
   accumulatedPower: AccumulatedPowerData | null;
   /** BMS software version */
   softwareVersion: string | null;
+  /** BMS software version with major/minor parsed out */
+  softwareVersionInfo: SoftwareVersion | null;
+  /** Firmware is at least the configured minimum; null until the version is known */
+  protocolCompatible: boolean | null;
+  /** Reported pack/cell indices outside the configured pack and cell counts */
+  indexErrors: string[];
+  /** SOC, voltage and current haven't changed for a while (firmware fault or battery at rest) */
+  possiblyFrozen: boolean;
   /** Alarm status */
   alarmStatus: AlarmStatus | null;
+  /** Debug status */
+  debugStatus: DebugStatusData | null;
+  /** Time in ms each command's data was last decoded, keyed by command name */
+  updatedAt: Record<string, number>;
+}
+
+/** Link counters, reset on every connect (get_can_stats) */
+export interface CanStats {
+  framesSent: number;
+  framesReceived: number;
+  parseFailures: number;
+  checksumErrors: number;
+  timeouts: number;
+  addressMismatches: number;
+  /** Frames the iTEKON adapter discarded on receive-buffer overflows */
+  droppedFrames: number;
+  /** Repeated frames dropped by the dedupWindowMs filter */
+  duplicateFrames: number;
+}
+
+/** Version query round trips (ping_bms) */
+export interface PingStats {
+  sent: number;
+  /** Queries that got no response */
+  timeouts: number;
+  latenciesMs: number[];
+  minMs: number | null;
+  avgMs: number | null;
+  maxMs: number | null;
+}
+
+/** Response time of one command from its last query (get_command_latencies) */
+export interface CommandLatency {
+  command: string;
+  /** End of the cycle's sends to response in ms, null if no response arrived */
+  latencyMs: number | null;
+}
+
+/** Everything one dashboard refresh needs (get_dashboard_snapshot) */
+export interface DashboardSnapshot {
+  data: BMSData;
+  alarms: AlarmDetail[];
+  connected: boolean;
+  /** No data yet, or none decoded within staleAfterMs */
+  stale: boolean;
+  stats: CanStats;
+}
+
+/** Debounced current direction (get_current_direction) */
+export type CurrentDirection = 'Idle' | 'Charging' | 'Discharging';
+
+/** Hysteresis settings for the direction indicator (set_direction_config) */
+export interface DirectionConfig {
+  /** Currents within ±deadbandA count as idle */
+  deadbandA: number;
+  /** A new direction must hold this long in ms before it is reported */
+  minDwellMs: number;
+}
+
+/** Adapters usable on this machine (list_adapters) */
+export interface AdapterAvailability {
+  serialPorts: string[];
+  bluetoothPorts: string[];
+  /** Linux only */
+  socketCanInterfaces: string[];
+  socketCanAvailable: boolean;
+  /** Path of the located VCI library (Windows only) */
+  itekonLibrary: string | null;
+  itekonAvailable: boolean;
+  simulationAvailable: boolean;
+}
+
+/** Bucket-averaged metrics from the SQLite historian (query_history) */
+export interface HistoryPoint {
+  /** Mean timestamp of the bucket in ms */
+  timestamp: number;
+  /** Rows averaged into this point */
+  samples: number;
+  soc: number | null;
+  soh: number | null;
+  voltage: number | null;
+  current: number | null;
+  /** kW */
+  power: number | null;
+  maxCellVoltage: number | null;
+  minCellVoltage: number | null;
+  maxTemperature: number | null;
+  minTemperature: number | null;
+}
+
+/** Rows the historian dropped (get_historian_stats) */
+export interface HistorianStats {
+  /** Writer fell behind */
+  droppedRows: number;
+  /** Database write failed */
+  failedWrites: number;
+}
+
+/** One metric that differs from a snapshot */
+export interface FieldChange {
+  field: string;
+  before: number | null;
+  after: number | null;
+  /** after - before, when both are known */
+  delta: number | null;
+}
+
+/** Changes since a snapshot_bms snapshot (diff_snapshot) */
+export interface SnapshotDiff {
+  snapshotId: number;
+  elapsedMs: number;
+  changes: FieldChange[];
+  newAlarms: number[];
+  clearedAlarms: number[];
+}
+
+/** Age of one command's data (get_data_ages) */
+export interface DataAge {
+  command: string;
+  updatedAt: number;
+  ageMs: number;
+}
+
+/** iTEKON board info with versions formatted as `V<major>.<minor>` */
+export interface BoardInfo {
+  serialNumber: string;
+  hardwareType: string;
+  hardwareVersion: string;
+  firmwareVersion: string;
+  driverVersion: string;
+  interfaceVersion: string;
+  canChannels: number;
+}
+
+/** Adapter description (get_adapter_info) */
+export interface AdapterInfo {
+  adapterType: 'UsbCan' | 'BluetoothCan' | 'ItekonCan' | 'SocketCan' | 'Simulation';
+  serialPort: string | null;
+  connected: boolean;
+  /** Only for a connected iTEKON adapter */
+  board: BoardInfo | null;
+}
+
+/** Payload of the `connection-state` event (get_connection_state) */
+export interface ConnectionEvent {
+  connected: boolean;
+  adapterType: 'UsbCan' | 'BluetoothCan' | 'ItekonCan' | 'SocketCan' | 'Simulation';
+  reason: 'Connected' | 'Disconnected' | 'LinkLost' | 'LinkRestored';
+}
+
+/** Why our link to the BMS is considered down */
+export type LinkFault = 'Disconnected' | 'NoData' | 'Stale';
+
+/** Link state vs BMS-internal comms faults (get_communication_status) */
+export interface CommunicationStatus {
+  /** Adapter/serial/VCI link fault, null while healthy */
+  linkFault: LinkFault | null;
+  /** Time since the last decoded frame in ms */
+  dataAgeMs: number | null;
+  /** Last alarm frame carried a communication fault bit */
+  bmsCommFault: boolean;
+  /** Active communication alarm bits (14, 33, 39) */
+  bmsCommAlarms: number[];
 }
 
 /** CAN Frame structure */
 export interface CANFrame {
-  /** 29-bit extended identifier */
+  /** 29-bit extended or 11-bit standard identifier */
   id: number;
+  /** Identifier format */
+  format?: 'Standard' | 'Extended';
   /** Data bytes (1-8 bytes) */
   data: Uint8Array;
   /** Timestamp */
   timestamp: number;
+  /** Adapter arrival time in 0.1ms ticks (iTEKON only, wraps) */
+  hwTimestamp?: number | null;
 }
 
 /** Parsed CAN Frame ID */
@@ -276,6 +619,21 @@ export interface ParsedFrameId {
   cnt: boolean;
 }
 
+/** Raw CAN id split into its fields (decode_frame_id) */
+export interface DecodedFrameId {
+  id: number;
+  /** Serialized with snake_case keys, as passed back to encode_frame_id */
+  fields: {
+    ptp: boolean;
+    command: number;
+    destination_address: number;
+    source_address: number;
+    cnt: boolean;
+  };
+  /** Command name if the code is defined by the protocol */
+  commandName: string | null;
+}
+
 /** Serial port configuration */
 export interface SerialPortConfig {
   /** Port name (e.g., COM3, /dev/ttyUSB0) */